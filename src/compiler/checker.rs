@@ -0,0 +1,312 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{compiler::constant::Constant, lexer::lexer::Token, parser::expressions::{ASTExpr, data_types::DataType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+/// How a name was bound, only variables can be assigned to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindingKind {
+    Argument,
+    Constant,
+    Variable,
+}
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub data_type: DataType,
+    pub kind: BindingKind,
+    /// Value of a constant, if it is known at compile time
+    pub value: Option<Constant>,
+}
+
+/// Types a function was declared with
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub arguments: Vec<DataType>,
+    pub return_type: DataType,
+}
+
+/// Fields and methods of a class, private members can only be used inside of the class
+#[derive(Debug, Clone)]
+pub struct ClassSignature {
+    pub fields: Vec<Field>,
+    pub methods: HashMap<String, Method>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub data_type: DataType,
+    pub public: bool,
+}
+
+/// Arguments of the signature do not include `this`
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub signature: Signature,
+    pub public: bool,
+    pub is_static: bool,
+    pub this: bool,
+}
+
+impl ClassSignature {
+    /// Index of the field in the struct and its type
+    pub fn field(&self, name: &str) -> Option<(u32, &Field)> {
+        self.fields.iter().enumerate().find(|(_, field)| field.name == name).map(|(i, field)| (i as u32, field))
+    }
+}
+
+/// Semantic analysis that runs over the AST before any code is generated,
+/// errors are collected so all of them can be reported at once
+pub struct TypeChecker {
+    scopes: Vec<HashMap<String, Binding>>,
+    functions: HashMap<String, Signature>,
+    /// Functions whose bodies were checked already
+    defined: HashSet<String>,
+    /// Field types of every variant of the enums
+    enums: HashMap<String, Vec<(String, Vec<DataType>)>>,
+    classes: HashMap<String, ClassSignature>,
+    /// Class whose methods are being checked, its private members can be used
+    class: Option<String>,
+    /// Return type of the function that is being checked
+    return_type: Option<DataType>,
+    /// Whether each of the enclosing loops contains a `break`
+    loops: Vec<bool>,
+    errors: Vec<Error>,
+}
+
+impl TypeChecker {
+    pub fn new() -> TypeChecker {
+        TypeChecker {
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            defined: HashSet::new(),
+            enums: HashMap::new(),
+            classes: HashMap::new(),
+            class: None,
+            return_type: None,
+            loops: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// Checks every expression of the AST, returns all errors that were found
+    pub fn check(mut self, ast: &[Box<dyn ASTExpr>]) -> Result<(), Vec<Error>> {
+        for expr in ast.iter() {
+            expr.check(&mut self);
+        }
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    pub fn error(&mut self, error: Error) {
+        self.errors.push(error);
+    }
+
+    pub fn declare_function(&mut self, name: &str, arguments: Vec<DataType>, return_type: DataType) {
+        self.functions.insert(name.to_string(), Signature { arguments, return_type });
+    }
+
+    pub fn function(&self, name: &str) -> Option<Signature> {
+        self.functions.get(name).cloned()
+    }
+
+    /// Marks the body of the function as checked, returns false if it already has one
+    pub fn define_function(&mut self, name: &str) -> bool {
+        self.defined.insert(name.to_string())
+    }
+
+    pub fn declare_enum(&mut self, name: &str, variants: Vec<(String, Vec<DataType>)>) {
+        self.enums.insert(name.to_string(), variants);
+    }
+
+    pub fn enum_variants(&self, name: &str) -> Option<Vec<(String, Vec<DataType>)>> {
+        self.enums.get(name).cloned()
+    }
+
+    pub fn declare_class(&mut self, name: &str, signature: ClassSignature) {
+        self.classes.insert(name.to_string(), signature);
+    }
+
+    pub fn class(&self, name: &str) -> Option<ClassSignature> {
+        self.classes.get(name).cloned()
+    }
+
+    /// Starts checking members of the class, returns the class that was checked before
+    pub fn enter_class(&mut self, name: &str) -> Option<String> {
+        self.class.replace(name.to_string())
+    }
+
+    pub fn exit_class(&mut self, saved: Option<String>) {
+        self.class = saved;
+    }
+
+    /// Methods are checked while their class is entered
+    pub fn in_class(&self) -> bool {
+        self.class.is_some()
+    }
+
+    /// Reports the member if it is private and used outside of its class
+    pub fn check_visible(&mut self, class: &str, member: &str, public: bool, token: &Token) {
+        if !public && self.class.as_deref() != Some(class) {
+            self.error(error!(ErrorKind::TypeError, "Member is private",
+                              ErrorTokenComponent::new(format!("{}::{} can only be used inside of the class", class, member), Some(token.clone()))));
+        }
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Binds the name in the innermost scope, it shadows bindings with the same name
+    pub fn define(&mut self, name: &str, data_type: DataType, kind: BindingKind) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), Binding { data_type, kind, value: None });
+    }
+
+    pub fn define_constant(&mut self, name: &str, value: Constant) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), Binding { data_type: value.data_type(), kind: BindingKind::Constant, value: Some(value) });
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).cloned()
+    }
+
+    /// Starts checking the body of a function, returns the state of the enclosing function
+    pub fn enter_function(&mut self, return_type: DataType) -> (Option<DataType>, Vec<bool>) {
+        self.enter_scope();
+        let loops = std::mem::take(&mut self.loops);
+        (self.return_type.replace(return_type), loops)
+    }
+
+    pub fn exit_function(&mut self, saved: (Option<DataType>, Vec<bool>)) {
+        self.exit_scope();
+        self.return_type = saved.0;
+        self.loops = saved.1;
+    }
+
+    pub fn return_type(&self) -> Option<DataType> {
+        self.return_type.clone()
+    }
+
+    pub fn enter_loop(&mut self) {
+        self.loops.push(false);
+    }
+
+    /// Returns whether the loop contains a `break`
+    pub fn exit_loop(&mut self) -> bool {
+        self.loops.pop().unwrap_or(false)
+    }
+
+    /// Marks the innermost loop as exited, returns false if there is no loop
+    pub fn break_loop(&mut self) -> bool {
+        match self.loops.last_mut() {
+            Some(broken) => {
+                *broken = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn in_loop(&self) -> bool {
+        !self.loops.is_empty()
+    }
+
+    /// Reports enums and classes in the type that are not declared, `this` outside of methods is reported by the function
+    pub fn check_type(&mut self, data_type: &DataType, token: &Token) {
+        for name in data_type.names() {
+            if name != "this" && !self.enums.contains_key(name) && !self.classes.contains_key(name) {
+                self.error(error!(ErrorKind::TypeError, "Unknown type",
+                                  ErrorTokenComponent::new(format!("Type {} is not declared", name), Some(token.clone()))));
+            }
+        }
+    }
+
+    /// Checks that the value can be used as a condition, integers are true when they are not zero
+    pub fn check_condition(&mut self, condition: &dyn ASTExpr, token: &Token) {
+        let data_type = condition.check(self);
+        if !is_condition(&data_type) {
+            self.error(error!(ErrorKind::TypeError, "Condition has to be a bool",
+                              ErrorTokenComponent::new("Invalid condition".to_string(), Some(token.clone()))
+                              NameErrorComponent::new(format!("Found: {}", data_type))));
+        }
+    }
+}
+
+/// Whether the value of type `found` can be used where `expected` is needed,
+/// unknown types were already reported and values that never exist fit anywhere
+pub fn compatible(expected: &DataType, found: &DataType) -> bool {
+    match (expected, found) {
+        // Unknown elements come from earlier errors, so they should not cause more
+        (DataType::Tuple(expected), DataType::Tuple(found)) => expected.len() == found.len() && expected.iter().zip(found).all(|(e, f)| compatible(e, f)),
+        _ => matches!(expected, DataType::Unknown) || matches!(found, DataType::Unknown | DataType::Never) || expected == found,
+    }
+}
+
+pub fn is_condition(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Bool | DataType::Unknown | DataType::Never) || data_type.is_integer()
+}
+
+/// Checks the source and describes every error by its kind and message, functions declare themselves when they are checked
+#[cfg(test)]
+pub fn check_source(source: &str) -> Vec<String> {
+    let ast = crate::parser::parser::parse(&crate::lexer::lexer::tokenize(source)).unwrap();
+    match TypeChecker::new().check(&ast) {
+        Ok(()) => vec![],
+        Err(errors) => errors.iter().map(|err| format!("{:?}: {}", err.kind(), err.message())).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_source;
+
+    #[test]
+    fn check_valid_program() {
+        let source = "fun add(a: i32, b: i32): i32 { a + b } fun main(): i32 { var x: u8 = 200u8; loop { if (x > 100u8) { break; } x += 1u8; } add(x as i32, 2) }";
+        assert!(check_source(source).is_empty());
+    }
+
+    #[test]
+    fn check_reports_all_errors() {
+        let source = "fun add(a: i32, b: i32): i32 { a = b; a } fun main(): i32 { var x: i32 = 1.5; x = y; x + 2u8; add(1); 2.0 }";
+        assert_eq!(check_source(source), vec![
+            "TypeError: Can not assign to function argument",
+            "TypeError: Variable types do not match",
+            "TypeError: Variable does not exist",
+            "TypeError: Binary operation types do not match",
+            "TypeError: Wrong number of arguments",
+            "TypeError: Return type does not match",
+        ]);
+    }
+
+    #[test]
+    fn check_unknown_types() {
+        assert!(check_source("enum Color { Red } class Point { var x: i32; } fun f(c: Color): i32 { var p: Point[2]; 0 }").is_empty());
+        assert_eq!(check_source("enum Shape { Circle(Color) }"), vec!["TypeError: Unknown type"]);
+        assert_eq!(check_source("class Box { var item: Item; }"), vec!["TypeError: Unknown type"]);
+        assert_eq!(check_source("fun f(s: never): void {}"), vec!["TypeError: Unknown type"]);
+        assert_eq!(check_source("fun f(n: (i32, Size)): void {}"), vec!["TypeError: Unknown type"]);
+        assert_eq!(check_source("fun f(): void { var x: Point[2]; }"), vec!["TypeError: Unknown type"]);
+    }
+
+    #[test]
+    fn check_control_flow() {
+        assert!(check_source("fun f(x: i32): i32 { if (x) { return 1; } else { return 2; } }").is_empty());
+        assert!(check_source("fun f(): i32 { loop { return 1; } }").is_empty());
+        assert_eq!(check_source("fun f(): i32 { loop { break; } }"), vec!["TypeError: Missing return value"]);
+        assert_eq!(check_source("fun f(): i32 { 1 } fun f(): i32 { 2 }"), vec!["TypeError: Function is defined more than once"]);
+        assert_eq!(check_source("class A { fun g(this: &this): void {} fun g(this: &this): void {} }"), vec!["TypeError: Member is defined more than once"]);
+        assert_eq!(check_source("fun f(): void { break; 1.5 as bool; }"), vec!["TypeError: Break outside of a loop"]);
+        assert_eq!(check_source("fun f(): void { if (1.5) { return 1; } }"), vec![
+            "TypeError: Condition has to be a bool",
+            "TypeError: Can not return a value from void function",
+        ]);
+    }
+}
@@ -1,33 +1,251 @@
-use inkwell::{context::Context, module::Module};
+use std::{cell::RefCell, env, fs, path::Path, process};
 
-use crate::parser::expressions::{ASTExpr, scope::ScopeManager};
+use inkwell::{context::Context, module::Module, targets::{Target, TargetMachine, InitializationConfig, RelocMode, CodeModel, FileType}, values::FunctionValue, types::BasicTypeEnum, passes::{PassManager, PassManagerBuilder}};
+
+use crate::{parser::expressions::{ASTExpr, scope::ScopeManager}, utils::{error::{Error, ErrorKind}, error_components::name_component::NameErrorComponent}, error};
+
+use super::{profile::Profile, units};
 
 pub struct Compiler<'ctx> {
     context: &'ctx Context,
     module: Module<'ctx>,
+    profile: Profile,
+    scope_manager: RefCell<ScopeManager<'ctx>>,
 }
 
 impl<'ctx> Compiler<'ctx> {
-    pub fn new(context: &'ctx Context, module: Module<'ctx>) -> Compiler<'ctx> {
+    pub fn new(context: &'ctx Context, module: Module<'ctx>, profile: Profile) -> Compiler<'ctx> {
+        // Layouts of types depend on the target, so the module is configured before any code is generated
+        let machine = create_target_machine(&module, &profile).unwrap_or_else(|err| err.panic());
+        let mut scope_manager = ScopeManager::new(machine.get_target_data());
+        scope_manager.error_handler = profile.error_handler;
         Compiler {
             context: &context,
             module,
+            profile,
+            scope_manager: RefCell::new(scope_manager),
         }
     }
 
-    pub fn compile(&self, ast: Vec<Box<dyn ASTExpr>>) -> () {
+    /// Sets the directory that import paths are relative to
+    pub fn set_source_root(&self, root: &Path) {
+        self.scope_manager.borrow_mut().units.set_root(root);
+    }
+
+    /// Generates code for the AST, verifies the module and optimizes it according to the profile
+    pub fn compile(&self, ast: Vec<Box<dyn ASTExpr>>) -> Result<(), Error> {
+        units::generate(self.context, &self.module, &mut self.scope_manager.borrow_mut(), ast);
+        self.link()?;
+        self.finish()
+    }
+
+    /// Compiles a single source file into its own module and links it into the program,
+    /// files that were already compiled through imports are skipped
+    pub fn compile_unit(&self, path: &Path, ast: Vec<Box<dyn ASTExpr>>) -> Result<(), Error> {
+        {
+            let mut sm = self.scope_manager.borrow_mut();
+            if !sm.units.is_compiled(path) {
+                units::compile_ast(self.context, &mut sm, path, ast)?;
+            }
+        }
+        self.link()
+    }
 
-        // Create a builder
-        let builder = self.context.create_builder();
+    /// Verifies the whole program and optimizes it according to the profile
+    pub fn finish(&self) -> Result<(), Error> {
+        self.verify()?;
+        self.optimize();
+        Ok(())
+    }
 
-        // Go through AST and compile each expression
-        let mut sm = ScopeManager::new();
-        for expr in ast {
-            expr.generate(self.context, &self.module, &builder, &mut sm); 
+    /// Links modules of all compiled files into the program
+    fn link(&self) -> Result<(), Error> {
+        for module in self.scope_manager.borrow_mut().units.take_modules() {
+            let name = module.get_name().to_string_lossy().to_string();
+            self.module.link_in_module(module).map_err(|err| {
+                error!(ErrorKind::CompilerError, "Could not link modules",
+                       NameErrorComponent::new(format!("in module {}: {}", name, err)))
+            })?;
         }
+        Ok(())
+    }
 
-        // Print generated llvm
-        let llvm_str = self.module.print_to_string();
-        println!("\n\nGenerated LLVM IR:\n{}", llvm_str.to_string());
+    pub fn verify(&self) -> Result<(), Error> {
+        self.module.verify().map_err(|err| {
+            error!(ErrorKind::CompilerError, "Generated module is invalid", NameErrorComponent::new(err.to_string()))
+        })
     }
-}
\ No newline at end of file
+
+    /// Runs optimization passes selected by the profile's `opt-level` and `lto` settings
+    pub fn optimize(&self) {
+        let builder = PassManagerBuilder::create();
+        builder.set_optimization_level(self.profile.optimization_level());
+        if self.profile.opt_level >= 3 {
+            builder.set_inliner_with_threshold(250);
+        } else if self.profile.opt_level == 2 {
+            builder.set_inliner_with_threshold(225);
+        }
+
+        if self.profile.opt_level > 0 {
+            let pass_manager: PassManager<Module> = PassManager::create(());
+            builder.populate_module_pass_manager(&pass_manager);
+            pass_manager.run_on(&self.module);
+        }
+
+        // Link time passes run on the fully linked program after the module pipeline
+        if self.profile.lto {
+            let lto_pass_manager: PassManager<Module> = PassManager::create(());
+            builder.populate_lto_pass_manager(&lto_pass_manager, true, true);
+            lto_pass_manager.run_on(&self.module);
+        }
+    }
+
+    pub fn to_llvm_ir(&self) -> String {
+        self.module.print_to_string().to_string()
+    }
+
+    pub fn write_llvm_ir(&self, path: &Path) -> Result<(), Error> {
+        self.module.print_to_file(path).map_err(|err| {
+            error!(ErrorKind::DriverError, "Could not write LLVM IR",
+                   NameErrorComponent::new(format!("{}: {}", path.display(), err)))
+        })
+    }
+
+    pub fn write_bitcode(&self, path: &Path) -> Result<(), Error> {
+        if !self.module.write_bitcode_to_path(path) {
+            return Err(error!(ErrorKind::DriverError, "Could not write LLVM bitcode",
+                              NameErrorComponent::new(format!("{}", path.display()))));
+        }
+        Ok(())
+    }
+
+    pub fn write_native(&self, path: &Path, file_type: FileType) -> Result<(), Error> {
+        let machine = create_target_machine(&self.module, &self.profile)?;
+        machine.write_to_file(&self.module, file_type, path).map_err(|err| {
+            error!(ErrorKind::DriverError, "Could not write native code",
+                   NameErrorComponent::new(format!("{}: {}", path.display(), err)))
+        })
+    }
+
+    /// Finds the program entry point, which should be `fun main(): i32` or `fun main(): void`
+    fn get_main(&self) -> Result<FunctionValue<'ctx>, Error> {
+        let main = match self.module.get_function("main") {
+            Some(main) => main,
+            None => return Err(error!(ErrorKind::DriverError, "Invalid entry point",
+                                      NameErrorComponent::new("Program does not define a 'main' function".to_string()))),
+        };
+        if main.count_params() != 0 {
+            return Err(error!(ErrorKind::DriverError, "Invalid entry point",
+                              NameErrorComponent::new("Function 'main' should not take any arguments".to_string())));
+        }
+        match main.get_type().get_return_type() {
+            None => Ok(main),
+            Some(BasicTypeEnum::IntType(t)) if t.get_bit_width() == 32 => Ok(main),
+            Some(_) => Err(error!(ErrorKind::DriverError, "Invalid entry point",
+                                  NameErrorComponent::new("Function 'main' should return i32 or void".to_string()))),
+        }
+    }
+
+    /// Runs `main` using the JIT execution engine and returns its exit code
+    pub fn run_main(&self) -> Result<i32, Error> {
+        let main = self.get_main()?;
+        Target::initialize_native(&InitializationConfig::default()).map_err(|err| {
+            error!(ErrorKind::DriverError, "Could not initialize native target", NameErrorComponent::new(err))
+        })?;
+        let engine = self.module.create_jit_execution_engine(self.profile.optimization_level()).map_err(|err| {
+            error!(ErrorKind::DriverError, "Could not create execution engine", NameErrorComponent::new(err.to_string()))
+        })?;
+
+        let lookup_error = |err| error!(ErrorKind::DriverError, "Could not find 'main' in execution engine",
+                                        NameErrorComponent::new(format!("{}", err)));
+        unsafe {
+            if main.get_type().get_return_type().is_none() {
+                let function = engine.get_function::<unsafe extern "C" fn()>("main").map_err(lookup_error)?;
+                function.call();
+                Ok(0)
+            } else {
+                let function = engine.get_function::<unsafe extern "C" fn() -> i32>("main").map_err(lookup_error)?;
+                Ok(function.call())
+            }
+        }
+    }
+
+    /// Writes object file and links it into an executable using the system linker (`$CC` or `cc`)
+    pub fn write_executable(&self, path: &Path) -> Result<(), Error> {
+        self.get_main()?;
+
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "main".to_string());
+        let object = env::temp_dir().join(format!("{}-{}.o", stem, process::id()));
+        self.write_native(&object, FileType::Object)?;
+
+        let linker = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let status = process::Command::new(&linker)
+            .arg(&object)
+            .arg("-o")
+            .arg(path)
+            .status();
+        let _ = fs::remove_file(&object);
+
+        match status {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => Err(error!(ErrorKind::DriverError, "Linking failed",
+                                     NameErrorComponent::new(format!("'{}' exited with {}", linker, status)))),
+            Err(err) => Err(error!(ErrorKind::DriverError, "Could not run linker",
+                                   NameErrorComponent::new(format!("'{}': {}", linker, err)))),
+        }
+    }
+}
+
+/// Creates target machine for the host and configures the module for it
+fn create_target_machine(module: &Module, profile: &Profile) -> Result<TargetMachine, Error> {
+    Target::initialize_native(&InitializationConfig::default()).map_err(|err| {
+        error!(ErrorKind::DriverError, "Could not initialize native target", NameErrorComponent::new(err))
+    })?;
+
+    let triple = TargetMachine::get_default_triple();
+    let target = Target::from_triple(&triple).map_err(|err| {
+        error!(ErrorKind::DriverError, "Unsupported target", NameErrorComponent::new(err.to_string()))
+    })?;
+    let machine = target.create_target_machine(
+        &triple,
+        &TargetMachine::get_host_cpu_name().to_string(),
+        &TargetMachine::get_host_cpu_features().to_string(),
+        profile.optimization_level(),
+        RelocMode::PIC,
+        CodeModel::Default,
+    ).ok_or_else(|| error!(ErrorKind::DriverError, "Could not create target machine",
+                           NameErrorComponent::new(triple.to_string())))?;
+
+    module.set_triple(&triple);
+    module.set_data_layout(&machine.get_target_data().get_data_layout());
+    Ok(machine)
+}
+
+/// Compiles source code and runs its `main` function, returning its result
+#[cfg(test)]
+pub fn run_source(source: &str) -> i32 {
+    let tokens = crate::lexer::lexer::tokenize(source);
+    let ast = crate::parser::parser::parse(&tokens).unwrap();
+    let context = Context::create();
+    let module = context.create_module("test");
+    let compiler = Compiler::new(&context, module, Profile::debug());
+    compiler.compile(ast).unwrap();
+    compiler.run_main().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+
+    use crate::compiler::profile::Profile;
+
+    use super::Compiler;
+
+    #[test]
+    fn run_without_main() {
+        let context = Context::create();
+        let compiler = Compiler::new(&context, context.create_module("test"), Profile::debug());
+        compiler.compile(vec![]).unwrap();
+        assert_eq!(compiler.run_main().unwrap_err().message(), "Invalid entry point");
+    }
+}
@@ -0,0 +1,213 @@
+use inkwell::{context::Context, module::Module, values::BasicValueEnum};
+
+use crate::parser::expressions::{data_types::DataType, math_expression::MathOperation, comparison_expression::CompareOperation, string_expression::build_string_constant};
+
+/// Value of a constant expression, it is known at compile time
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    /// Integer of the type, it wraps around like at runtime
+    Int(i128, DataType),
+    Float(f64, DataType),
+    Bool(bool),
+    Str(String),
+}
+
+impl Constant {
+    pub fn int(value: i128, data_type: DataType) -> Constant {
+        Constant::Int(wrap(value, &data_type), data_type)
+    }
+
+    pub fn float(value: f64, data_type: DataType) -> Constant {
+        // f32 loses precision after every operation
+        let value = if data_type == DataType::F32 { value as f32 as f64 } else { value };
+        Constant::Float(value, data_type)
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Constant::Int(_, data_type) | Constant::Float(_, data_type) => data_type.clone(),
+            Constant::Bool(_) => DataType::Bool,
+            Constant::Str(_) => DataType::String,
+        }
+    }
+
+    /// Integers are true when they are not zero
+    pub fn is_true(&self) -> Option<bool> {
+        match *self {
+            Constant::Bool(value) => Some(value),
+            Constant::Int(value, _) => Some(value != 0),
+            Constant::Float(..) | Constant::Str(_) => None,
+        }
+    }
+
+    pub fn into_llvm<'a>(&self, context: &'a Context, module: &Module<'a>) -> BasicValueEnum<'a> {
+        match self {
+            Constant::Int(value, data_type) => BasicValueEnum::IntValue(data_type.into_basic_type(context).into_int_type().const_int(*value as u64, false)),
+            Constant::Float(value, data_type) => BasicValueEnum::FloatValue(data_type.into_basic_type(context).into_float_type().const_float(*value)),
+            Constant::Bool(value) => BasicValueEnum::IntValue(context.bool_type().const_int(*value as u64, false)),
+            Constant::Str(value) => BasicValueEnum::StructValue(build_string_constant(context, module, value)),
+        }
+    }
+
+    /// Same as the operation at runtime, operations that would be undefined are not constant
+    pub fn math(operation: MathOperation, lhs: &Constant, rhs: &Constant) -> Option<Constant> {
+        match (lhs, rhs) {
+            (Constant::Int(lhs, data_type), Constant::Int(rhs, _)) => {
+                let bits = bit_width(data_type) as i128;
+                let value = match operation {
+                    MathOperation::ADD => lhs + rhs,
+                    MathOperation::SUBTRACT => lhs - rhs,
+                    MathOperation::MULTIPLY => lhs.wrapping_mul(*rhs),
+                    MathOperation::DIVIDE if *rhs != 0 => lhs / rhs,
+                    MathOperation::MODULO if *rhs != 0 => lhs % rhs,
+                    MathOperation::DIVIDE | MathOperation::MODULO => return None,
+                    MathOperation::BIT_AND => lhs & rhs,
+                    MathOperation::BIT_OR => lhs | rhs,
+                    MathOperation::BIT_XOR => lhs ^ rhs,
+                    MathOperation::SHIFT_LEFT if (0..bits).contains(rhs) => lhs << rhs,
+                    // Signed values are stored with their sign, so the shift keeps it
+                    MathOperation::SHIFT_RIGHT if (0..bits).contains(rhs) => lhs >> rhs,
+                    MathOperation::SHIFT_LEFT | MathOperation::SHIFT_RIGHT => return None,
+                };
+                Some(Constant::int(value, data_type.clone()))
+            }
+            (Constant::Float(lhs, data_type), Constant::Float(rhs, _)) => {
+                let value = match operation {
+                    MathOperation::ADD => lhs + rhs,
+                    MathOperation::SUBTRACT => lhs - rhs,
+                    MathOperation::MULTIPLY => lhs * rhs,
+                    MathOperation::DIVIDE => lhs / rhs,
+                    MathOperation::MODULO => lhs % rhs,
+                    _ => return None,
+                };
+                Some(Constant::float(value, data_type.clone()))
+            }
+            (Constant::Bool(lhs), Constant::Bool(rhs)) => match operation {
+                MathOperation::BIT_AND => Some(Constant::Bool(lhs & rhs)),
+                MathOperation::BIT_OR => Some(Constant::Bool(lhs | rhs)),
+                MathOperation::BIT_XOR => Some(Constant::Bool(lhs ^ rhs)),
+                _ => None,
+            },
+            (Constant::Str(lhs), Constant::Str(rhs)) => match operation {
+                MathOperation::ADD => Some(Constant::Str(format!("{}{}", lhs, rhs))),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn compare(operation: CompareOperation, lhs: &Constant, rhs: &Constant) -> Option<Constant> {
+        let ordering = match (lhs, rhs) {
+            (Constant::Int(lhs, _), Constant::Int(rhs, _)) => lhs.partial_cmp(rhs),
+            (Constant::Float(lhs, _), Constant::Float(rhs, _)) => lhs.partial_cmp(rhs),
+            (Constant::Bool(lhs), Constant::Bool(rhs)) => lhs.partial_cmp(rhs),
+            (Constant::Str(lhs), Constant::Str(rhs)) => lhs.partial_cmp(rhs),
+            _ => return None,
+        };
+        // Comparisons with NaN are false
+        let ordering = match ordering {
+            Some(ordering) => ordering,
+            None => return Some(Constant::Bool(false)),
+        };
+        Some(Constant::Bool(match operation {
+            CompareOperation::EQUAL => ordering.is_eq(),
+            CompareOperation::NOT_EQUAL => ordering.is_ne(),
+            CompareOperation::LESS => ordering.is_lt(),
+            CompareOperation::GREATER => ordering.is_gt(),
+            CompareOperation::LESS_EQUAL => ordering.is_le(),
+            CompareOperation::GREATER_EQUAL => ordering.is_ge(),
+        }))
+    }
+
+    pub fn negate(&self) -> Option<Constant> {
+        match self {
+            Constant::Int(value, data_type) => Some(Constant::int(-value, data_type.clone())),
+            Constant::Float(value, data_type) => Some(Constant::float(-value, data_type.clone())),
+            Constant::Bool(_) | Constant::Str(_) => None,
+        }
+    }
+
+    pub fn cast(&self, data_type: &DataType) -> Option<Constant> {
+        let value = match *self {
+            Constant::Int(value, ref from) if *data_type == DataType::String && from.is_integer() => return Some(Constant::Str(value.to_string())),
+            Constant::Int(value, _) => value,
+            Constant::Bool(value) => value as i128,
+            Constant::Float(value, _) if data_type.is_float() => return Some(Constant::float(value, data_type.clone())),
+            Constant::Float(value, _) => value as i128,
+            Constant::Str(_) => return None,
+        };
+        if data_type.is_integer() || *data_type == DataType::Char {
+            Some(Constant::int(value, data_type.clone()))
+        } else if data_type.is_float() {
+            Some(Constant::float(value as f64, data_type.clone()))
+        } else if *data_type == DataType::Bool {
+            Some(Constant::Bool(value != 0))
+        } else {
+            None
+        }
+    }
+}
+
+/// Smallest and largest value of the type, chars are Unicode code points
+pub fn bounds(data_type: &DataType) -> Option<(i128, i128)> {
+    match data_type {
+        DataType::Bool => Some((0, 1)),
+        DataType::Char => Some((0, 0x10FFFF)),
+        data_type if data_type.is_unsigned() => Some((0, (1i128 << bit_width(data_type)) - 1)),
+        data_type if data_type.is_integer() => Some((-(1i128 << (bit_width(data_type) - 1)), (1i128 << (bit_width(data_type) - 1)) - 1)),
+        _ => None,
+    }
+}
+
+fn bit_width(data_type: &DataType) -> u32 {
+    match data_type {
+        DataType::I8 | DataType::U8 => 8,
+        DataType::I16 | DataType::U16 => 16,
+        DataType::I32 | DataType::U32 | DataType::Char => 32,
+        _ => 64,
+    }
+}
+
+/// Truncates the value to the width of the type, signed values keep their sign
+fn wrap(value: i128, data_type: &DataType) -> i128 {
+    let bits = bit_width(data_type);
+    let value = value & ((1i128 << bits) - 1);
+    if !data_type.is_unsigned() && value >> (bits - 1) == 1 {
+        value - (1i128 << bits)
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::expressions::{data_types::DataType, math_expression::MathOperation, comparison_expression::CompareOperation};
+
+    use super::Constant;
+
+    #[test]
+    fn wrap_integers() {
+        let max = Constant::int(127, DataType::I8);
+        assert_eq!(Constant::math(MathOperation::ADD, &max, &Constant::int(1, DataType::I8)), Some(Constant::Int(-128, DataType::I8)));
+        assert_eq!(Constant::int(-1, DataType::U16), Constant::Int(65535, DataType::U16));
+        assert_eq!(Constant::int(300, DataType::I32).cast(&DataType::I8), Some(Constant::Int(44, DataType::I8)));
+        assert_eq!(Constant::int(-1, DataType::U32).cast(&DataType::String), Some(Constant::Str("4294967295".to_string())));
+        assert_eq!(Constant::math(MathOperation::SHIFT_RIGHT, &Constant::int(-8, DataType::I32), &Constant::int(1, DataType::I32)), Some(Constant::Int(-4, DataType::I32)));
+    }
+
+    #[test]
+    fn undefined_operations_are_not_constant() {
+        let zero = Constant::int(0, DataType::I32);
+        assert_eq!(Constant::math(MathOperation::DIVIDE, &Constant::int(1, DataType::I32), &zero), None);
+        assert_eq!(Constant::math(MathOperation::SHIFT_LEFT, &zero, &Constant::int(32, DataType::I32)), None);
+    }
+
+    #[test]
+    fn compare_values() {
+        let nan = Constant::float(f64::NAN, DataType::F64);
+        assert_eq!(Constant::compare(CompareOperation::NOT_EQUAL, &nan, &nan), Some(Constant::Bool(false)));
+        assert_eq!(Constant::compare(CompareOperation::LESS, &Constant::int(200, DataType::U8), &Constant::int(100, DataType::U8)), Some(Constant::Bool(false)));
+        let greeting = Constant::math(MathOperation::ADD, &Constant::Str("Hello, ".to_string()), &Constant::Str("world".to_string())).unwrap();
+        assert_eq!(Constant::compare(CompareOperation::EQUAL, &greeting, &Constant::Str("Hello, world".to_string())), Some(Constant::Bool(true)));
+    }
+}
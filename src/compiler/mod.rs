@@ -1 +1,5 @@
-pub mod compiler;
\ No newline at end of file
+pub mod checker;
+pub mod constant;
+pub mod compiler;
+pub mod profile;
+pub mod units;
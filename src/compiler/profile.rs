@@ -0,0 +1,49 @@
+use inkwell::OptimizationLevel;
+
+/// Build settings, as described by the `profiles` section of `arclight.yml`
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub opt_level: u32,
+    pub lto: bool,
+    pub error_handler: bool,
+}
+
+impl Profile {
+    pub fn debug() -> Profile {
+        Profile {
+            opt_level: 0,
+            lto: false,
+            error_handler: true,
+        }
+    }
+
+    pub fn release() -> Profile {
+        Profile {
+            opt_level: 3,
+            lto: true,
+            error_handler: false,
+        }
+    }
+
+    pub fn optimization_level(&self) -> OptimizationLevel {
+        match self.opt_level {
+            0 => OptimizationLevel::None,
+            1 => OptimizationLevel::Less,
+            2 => OptimizationLevel::Default,
+            _ => OptimizationLevel::Aggressive,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::OptimizationLevel;
+
+    use super::Profile;
+
+    #[test]
+    fn profile_optimization_levels() {
+        assert_eq!(Profile::debug().optimization_level(), OptimizationLevel::None);
+        assert_eq!(Profile::release().optimization_level(), OptimizationLevel::Aggressive);
+    }
+}
@@ -0,0 +1,295 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, rc::Rc};
+
+use inkwell::{context::Context, module::{Linkage, Module}, targets::TargetMachine};
+
+use crate::{lexer::lexer, compiler::checker::{TypeChecker, Signature}, parser::{parser, expressions::{ASTExpr, scope::{ScopeManager, Enum, Class}}}, utils::{error::{Error, ErrorKind}, error_components::name_component::NameErrorComponent}, error};
+
+pub const SOURCE_EXTENSION: &str = "arl";
+
+/// Everything a file makes visible to the files that import it
+#[derive(Debug, Default)]
+pub struct Exports<'a> {
+    /// Functions are declared again in every module that imports them,
+    /// values of the exporting module are gone once it is linked
+    pub functions: HashMap<String, Signature>,
+    /// Types belong to the context, so they are shared by all modules
+    pub enums: HashMap<String, Rc<Enum<'a>>>,
+    pub classes: HashMap<String, Rc<Class<'a>>>,
+}
+
+/// Source files of the program, every file is compiled into its own module
+#[derive(Debug)]
+pub struct Units<'a> {
+    root: Option<PathBuf>,
+    /// Files that are being compiled, the innermost one is last
+    stack: Vec<PathBuf>,
+    /// Symbols exported by every compiled file
+    exports: HashMap<PathBuf, Exports<'a>>,
+    /// Compiled modules that are not yet linked into the program
+    modules: Vec<Module<'a>>,
+}
+
+impl<'a> Units<'a> {
+    pub fn new() -> Units<'a> {
+        Units {
+            root: None,
+            stack: vec![],
+            exports: HashMap::new(),
+            modules: vec![],
+        }
+    }
+
+    /// Sets the directory that import paths are relative to
+    pub fn set_root(&mut self, root: &Path) {
+        self.root = Some(normalize(root));
+    }
+
+    /// Finds the file for the longest prefix of the import path,
+    /// returns the file and the number of path segments it uses
+    pub fn resolve(&self, path: &[String]) -> Option<(PathBuf, usize)> {
+        let root = self.root.as_ref()?;
+        for len in (1..=path.len()).rev() {
+            let file = root.join(path[..len].join("/")).with_extension(SOURCE_EXTENSION);
+            if file.is_file() {
+                return Some((normalize(&file), len));
+            }
+        }
+        None
+    }
+
+    pub fn is_compiled(&self, file: &Path) -> bool {
+        self.exports.contains_key(&normalize(file))
+    }
+
+    /// Returns the chain of imports if compiling the file would create a cycle
+    pub fn cycle(&self, file: &Path) -> Option<Vec<PathBuf>> {
+        let start = self.stack.iter().position(|f| f == file)?;
+        let mut chain = self.stack[start..].to_vec();
+        chain.push(file.to_path_buf());
+        Some(chain)
+    }
+
+    pub fn exports(&self, file: &Path) -> Option<&Exports<'a>> {
+        self.exports.get(file)
+    }
+
+    pub fn take_modules(&mut self) -> Vec<Module<'a>> {
+        std::mem::take(&mut self.modules)
+    }
+
+    /// Shortens the path for error messages
+    pub fn display(&self, file: &Path) -> String {
+        match self.root {
+            Some(ref root) => file.strip_prefix(root).unwrap_or(file).display().to_string(),
+            None => file.display().to_string(),
+        }
+    }
+}
+
+/// Reads, parses and compiles the source file
+pub fn compile_file<'a>(context: &'a Context, scope_manager: &mut ScopeManager<'a>, file: &Path) -> Result<(), Error> {
+    let content = fs::read_to_string(file).map_err(|err| {
+        error!(ErrorKind::CompilerError, "Could not read source file",
+               NameErrorComponent::new(format!("{}: {}", file.display(), err)))
+    })?;
+    let tokens = lexer::tokenize(&content);
+    let ast = parser::parse(&tokens).map_err(|mut err| {
+        err.with_component(Box::new(NameErrorComponent::new(format!("in file {}", file.display()))));
+        err
+    })?;
+    compile_ast(context, scope_manager, file, ast)
+}
+
+/// Compiles the AST of the source file into a new module, that is linked into the program later
+pub fn compile_ast<'a>(context: &'a Context, scope_manager: &mut ScopeManager<'a>, file: &Path, ast: Vec<Box<dyn ASTExpr>>) -> Result<(), Error> {
+    let file = normalize(file);
+    let name = file.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "main".to_string());
+    let module = context.create_module(&name);
+    // Linked into the program, so the module uses the same target
+    module.set_triple(&TargetMachine::get_default_triple());
+    module.set_data_layout(&scope_manager.target_data.get_data_layout());
+
+    // Every file starts with an empty global scope
+    scope_manager.units.stack.push(file.clone());
+    let saved = scope_manager.enter_file();
+    generate(context, &module, scope_manager, ast);
+    // Functions that are defined in the file and visible outside of it are exported
+    let mut exports = Exports::default();
+    for (name, function) in scope_manager.scope.functions.iter() {
+        if function.value.count_basic_blocks() > 0 && function.value.get_linkage() == Linkage::External {
+            exports.functions.insert(name.clone(), Signature { arguments: function.arguments.clone(), return_type: function.return_type.clone() });
+        }
+    }
+    for (name, declared) in scope_manager.scope.enums.iter().filter(|(_, declared)| declared.exported) {
+        exports.enums.insert(name.clone(), declared.clone());
+    }
+    for (name, declared) in scope_manager.scope.classes.iter().filter(|(_, declared)| declared.exported) {
+        exports.classes.insert(name.clone(), declared.clone());
+    }
+    scope_manager.exit_file(saved);
+    scope_manager.units.stack.pop();
+
+    module.verify().map_err(|err| {
+        error!(ErrorKind::CompilerError, "Generated module is invalid",
+               NameErrorComponent::new(format!("in file {}: {}", file.display(), err)))
+    })?;
+
+    scope_manager.units.exports.insert(file, exports);
+    scope_manager.units.modules.push(module);
+    Ok(())
+}
+
+/// Declares, checks and then generates code for every expression of the AST
+pub fn generate<'a>(context: &'a Context, module: &Module<'a>, scope_manager: &mut ScopeManager<'a>, ast: Vec<Box<dyn ASTExpr>>) {
+    for expr in ast.iter() {
+        expr.declare_types(context, scope_manager);
+    }
+    for expr in ast.iter() {
+        expr.declare(context, module, scope_manager);
+    }
+    check(scope_manager, &ast);
+    let builder = context.create_builder();
+    for expr in ast {
+        expr.generate(context, module, &builder, scope_manager);
+    }
+}
+
+/// Runs the type checker over the AST and reports all errors it found
+fn check(scope_manager: &ScopeManager, ast: &[Box<dyn ASTExpr>]) {
+    let mut checker = TypeChecker::new();
+    for (name, function) in scope_manager.scope.functions.iter() {
+        checker.declare_function(name, function.arguments.clone(), function.return_type.clone());
+    }
+    for (name, declared) in scope_manager.scope.enums.iter() {
+        checker.declare_enum(name, declared.variants.clone());
+    }
+    for (name, declared) in scope_manager.scope.classes.iter() {
+        checker.declare_class(name, declared.signature.clone());
+    }
+    if let Err(errors) = checker.check(ast) {
+        for err in errors.iter() {
+            err.print_err();
+        }
+        error!(ErrorKind::TypeError, "Type checking failed",
+               NameErrorComponent::new(format!("{} errors found", errors.len()))).panic()
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let path = if path.as_os_str().is_empty() { Path::new(".") } else { path };
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use inkwell::context::Context;
+
+    use crate::{compiler::{compiler::Compiler, profile::Profile}, lexer::lexer::{tokenize, TokenType}, parser::{expressions::enum_expression::check_type_name, parser::parse}, test_token};
+    use crate::utils::error::{CompilationFailed, ErrorKind};
+
+    use super::Units;
+
+    /// Writes the files into a new directory and returns its path
+    fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = env::temp_dir().join(format!("arclight-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        for (file, source) in files.iter() {
+            fs::write(root.join(file), source).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn resolve_longest_path() {
+        let root = env::temp_dir().join(format!("arclight-units-{}", std::process::id()));
+        fs::create_dir_all(root.join("lib")).unwrap();
+        fs::write(root.join("lib.arl"), "").unwrap();
+        fs::write(root.join("lib").join("math.arl"), "").unwrap();
+
+        let mut units = Units::new();
+        units.set_root(&root);
+        let path = |segments: &[&str]| segments.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        let (file, used) = units.resolve(&path(&["lib", "math", "pow"])).unwrap();
+        assert_eq!(units.display(&file), "lib/math.arl");
+        assert_eq!(used, 2);
+
+        let (file, used) = units.resolve(&path(&["lib", "Lib"])).unwrap();
+        assert_eq!(units.display(&file), "lib.arl");
+        assert_eq!(used, 1);
+
+        assert!(units.resolve(&path(&["math", "pow"])).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn run_shared_import() {
+        // Both files import the library after its module was already linked into the program
+        let root = project("shared", &[
+            ("a.arl", "import lib { square }; export fun four(): i32 { square(2) }"),
+            ("b.arl", "import lib; export fun nine(): i32 { square(3) }"),
+            ("lib.arl", "export fun square(n: i32): i32 { n * n }"),
+            ("main.arl", "import a { four }; import b { nine }; fun main(): i32 { four() * 10 + nine() }"),
+        ]);
+
+        let context = Context::create();
+        let compiler = Compiler::new(&context, context.create_module("shared"), Profile::debug());
+        compiler.set_source_root(&root);
+        for file in ["a.arl", "b.arl", "lib.arl", "main.arl"] {
+            let ast = parse(&tokenize(&fs::read_to_string(root.join(file)).unwrap())).unwrap();
+            compiler.compile_unit(&root.join(file), ast).unwrap();
+        }
+        compiler.finish().unwrap();
+        assert_eq!(compiler.run_main().unwrap(), 49);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Compiles the files in the order the driver would
+    fn compile_project<'a>(context: &'a Context, root: &PathBuf) -> Compiler<'a> {
+        let compiler = Compiler::new(context, context.create_module("project"), Profile::debug());
+        compiler.set_source_root(root);
+        let mut files = fs::read_dir(root).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<PathBuf>>();
+        files.sort();
+        for file in files {
+            let ast = parse(&tokenize(&fs::read_to_string(&file).unwrap())).unwrap();
+            compiler.compile_unit(&file, ast).unwrap();
+        }
+        compiler.finish().unwrap();
+        compiler
+    }
+
+    #[test]
+    fn run_exported_types() {
+        let root = project("types", &[
+            ("lib.arl", "export enum Color { Red, Green(i32) } \
+                         export class Counter { public var value: i32 = 1; \
+                         public static fun new(this: &this): void {} \
+                         public fun add(this: &this, n: i32): i32 { this.value += n; this.value } }"),
+            ("main.arl", "import lib { Color, Counter }; \
+                          fun main(): i32 { var c = Counter::new(); c.add(2); match Color::Green(4) { Green(n) => c.add(n), _ => 0 } }"),
+        ]);
+        let context = Context::create();
+        let compiler = compile_project(&context, &root);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(compiler.run_main().unwrap(), 7);
+    }
+
+    #[test]
+    fn reject_duplicate_type_names() {
+        // Named types belong to the context, a second `Color` would silently get another name
+        let root = project("duplicate", &[
+            ("a.arl", "enum Color { Red } export fun red(): Color { Color::Red }"),
+            ("b.arl", "enum Color { Green, Blue }"),
+        ]);
+        let context = Context::create();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { compile_project(&context, &root); }));
+        fs::remove_dir_all(&root).unwrap();
+        assert!(result.unwrap_err().is::<CompilationFailed>());
+        // The first `Color` stays in the context, so declaring it again reports the error
+        let err = check_type_name(&context, "Color", &test_token!(TokenType::Identifier("Color".to_string()))).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::CompilerError);
+        assert_eq!(err.message(), "Type is declared more than once");
+    }
+}
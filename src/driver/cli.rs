@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Compiler for the Arclight programming language
+#[derive(Debug, Parser)]
+#[command(name = "arclight", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Compile the project or source files and write the result to a file
+    Build(OutputArgs),
+    /// Check source files for errors without writing any output
+    Check(InputArgs),
+    /// Print a single compilation stage to stdout (or to a file with `-o`)
+    Emit(OutputArgs),
+    /// Compile and run the program
+    Run(InputArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct InputArgs {
+    /// Source files to compile, the project in the current directory is built if there are none
+    pub files: Vec<PathBuf>,
+
+    /// Build with the release profile
+    #[arg(long)]
+    pub release: bool,
+
+    /// Build with a profile from `arclight.yml`
+    #[arg(long, value_name = "NAME", conflicts_with = "release")]
+    pub profile: Option<String>,
+
+    /// Override optimization level of the profile
+    #[arg(short = 'O', long = "opt-level", value_parser = clap::value_parser!(u32).range(0..=3))]
+    pub opt_level: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct OutputArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Path of the output file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Compilation stage to output
+    #[arg(long, value_enum)]
+    pub emit: Option<EmitKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmitKind {
+    Tokens,
+    Ast,
+    LlvmIr,
+    Bitcode,
+    Asm,
+    Obj,
+}
+
+impl EmitKind {
+    /// Extension of the file that is written when no output path is given
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EmitKind::Tokens => "tokens",
+            EmitKind::Ast => "ast",
+            EmitKind::LlvmIr => "ll",
+            EmitKind::Bitcode => "bc",
+            EmitKind::Asm => "s",
+            EmitKind::Obj => "o",
+        }
+    }
+
+    /// Whether this stage can be printed to the terminal
+    pub fn is_textual(&self) -> bool {
+        !matches!(self, EmitKind::Bitcode | EmitKind::Obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::{Cli, Command, EmitKind};
+
+    #[test]
+    fn parse_emit_command() {
+        let cli = Cli::try_parse_from(["arclight", "emit", "--emit=llvm-ir", "main.arl"]).unwrap();
+        match cli.command {
+            Command::Emit(args) => {
+                assert_eq!(args.emit, Some(EmitKind::LlvmIr));
+                assert_eq!(args.input.files.len(), 1);
+                assert!(args.output.is_none());
+            }
+            _ => panic!("Expected emit command"),
+        }
+    }
+
+    #[test]
+    fn parse_build_command_with_output() {
+        let cli = Cli::try_parse_from(["arclight", "build", "a.arl", "b.arl", "-o", "out.o", "--emit", "obj"]).unwrap();
+        match cli.command {
+            Command::Build(args) => {
+                assert_eq!(args.emit, Some(EmitKind::Obj));
+                assert_eq!(args.input.files.len(), 2);
+                assert_eq!(args.output.unwrap().to_str(), Some("out.o"));
+            }
+            _ => panic!("Expected build command"),
+        }
+    }
+
+    #[test]
+    fn parse_profile_flags() {
+        let cli = Cli::try_parse_from(["arclight", "run", "--release", "-O", "2", "main.arl"]).unwrap();
+        match cli.command {
+            Command::Run(args) => {
+                assert!(args.release);
+                assert_eq!(args.opt_level, Some(2));
+            }
+            _ => panic!("Expected run command"),
+        }
+        assert!(Cli::try_parse_from(["arclight", "run", "-O", "4", "main.arl"]).is_err());
+    }
+
+    #[test]
+    fn parse_project_command() {
+        let cli = Cli::try_parse_from(["arclight", "build", "--profile", "bench"]).unwrap();
+        match cli.command {
+            Command::Build(args) => {
+                assert!(args.input.files.is_empty());
+                assert_eq!(args.input.profile.as_deref(), Some("bench"));
+            }
+            _ => panic!("Expected build command"),
+        }
+        assert!(Cli::try_parse_from(["arclight", "check", "--release", "--profile", "bench"]).is_err());
+    }
+}
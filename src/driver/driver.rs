@@ -0,0 +1,243 @@
+use std::{env, fs, path::{Path, PathBuf}};
+
+use inkwell::{context::Context, targets::FileType};
+
+use crate::{compiler::{compiler::Compiler, profile::Profile}, lexer::lexer::{self, Token}, parser::{parser, expressions::ASTExpr}, utils::{error::{Error, ErrorKind}, error_components::name_component::NameErrorComponent}, error};
+
+use super::{cli::{Cli, Command, EmitKind, InputArgs, OutputArgs}, manifest::{Project, MANIFEST_NAME}};
+
+struct SourceFile {
+    path: PathBuf,
+    tokens: Vec<Token>,
+}
+
+/// Everything needed to compile a program, taken from the project or from the command line
+struct Build {
+    name: String,
+    sources: Vec<SourceFile>,
+    profile: Profile,
+    /// Directory that import paths are relative to
+    source_root: PathBuf,
+    output_dir: PathBuf,
+}
+
+impl Build {
+    /// Path of the output file with the extension, creating the output directory if needed
+    fn output_path(&self, extension: &str) -> Result<PathBuf, Error> {
+        fs::create_dir_all(&self.output_dir).map_err(|err| {
+            error!(ErrorKind::DriverError, "Could not create output directory",
+                   NameErrorComponent::new(format!("{}: {}", self.output_dir.display(), err)))
+        })?;
+        Ok(self.output_dir.join(&self.name).with_extension(extension))
+    }
+}
+
+/// Runs the command and returns the exit code of the process
+pub fn run(cli: Cli) -> Result<i32, Error> {
+    match cli.command {
+        Command::Build(args) => build(&args).map(|_| 0),
+        Command::Check(args) => check(&args).map(|_| 0),
+        Command::Emit(args) => emit(&args).map(|_| 0),
+        Command::Run(args) => run_program(&args),
+    }
+}
+
+fn build(args: &OutputArgs) -> Result<(), Error> {
+    let build = prepare(&args.input)?;
+    let kind = match args.emit {
+        Some(kind) => kind,
+        None => {
+            // Without a stage to emit, build an executable
+            let output = match args.output {
+                Some(ref path) => path.clone(),
+                None => build.output_path(env::consts::EXE_EXTENSION)?,
+            };
+            let context = Context::create();
+            let compiler = compile(&context, &build)?;
+            return compiler.write_executable(&output);
+        }
+    };
+    let output = match args.output {
+        Some(ref path) => path.clone(),
+        None => build.output_path(kind.extension())?,
+    };
+    write_stage(kind, &build, Some(&output))
+}
+
+fn check(args: &InputArgs) -> Result<(), Error> {
+    let build = prepare(args)?;
+    let context = Context::create();
+    compile(&context, &build)?;
+    Ok(())
+}
+
+fn run_program(args: &InputArgs) -> Result<i32, Error> {
+    let build = prepare(args)?;
+    let context = Context::create();
+    let compiler = compile(&context, &build)?;
+    compiler.run_main()
+}
+
+fn emit(args: &OutputArgs) -> Result<(), Error> {
+    let build = prepare(&args.input)?;
+    let kind = args.emit.unwrap_or(EmitKind::LlvmIr);
+    if args.output.is_none() && !kind.is_textual() {
+        return Err(error!(ErrorKind::DriverError, "Binary output can not be printed to the terminal",
+                          NameErrorComponent::new("Use '-o' to choose the output file".to_string())));
+    }
+    write_stage(kind, &build, args.output.as_deref())
+}
+
+/// Writes the requested compilation stage to the file, or to stdout if there is no file
+fn write_stage(kind: EmitKind, build: &Build, output: Option<&Path>) -> Result<(), Error> {
+    match kind {
+        EmitKind::Tokens => {
+            let mut text = String::new();
+            for source in build.sources.iter() {
+                for token in source.tokens.iter() {
+                    text.push_str(&format!("{:?}\n", token));
+                }
+            }
+            write_text(&text, output)
+        }
+        EmitKind::Ast => {
+            let mut text = String::new();
+            for source in build.sources.iter() {
+                for expr in parse_source(source)? {
+                    text.push_str(&format!("{}\n", expr.to_string()));
+                }
+            }
+            write_text(&text, output)
+        }
+        EmitKind::LlvmIr => {
+            let context = Context::create();
+            let compiler = compile(&context, build)?;
+            match output {
+                Some(path) => compiler.write_llvm_ir(path),
+                None => write_text(&compiler.to_llvm_ir(), None),
+            }
+        }
+        EmitKind::Bitcode => {
+            let context = Context::create();
+            let compiler = compile(&context, build)?;
+            compiler.write_bitcode(output.unwrap())
+        }
+        EmitKind::Asm => {
+            let context = Context::create();
+            let compiler = compile(&context, build)?;
+            match output {
+                Some(path) => compiler.write_native(path, FileType::Assembly),
+                None => {
+                    // Assembly is printed through a temporary file, as target machine can only write files
+                    let path = env::temp_dir().join(format!("{}-{}.s", build.name, std::process::id()));
+                    compiler.write_native(&path, FileType::Assembly)?;
+                    let text = fs::read_to_string(&path);
+                    let _ = fs::remove_file(&path);
+                    write_text(&text.unwrap_or_default(), None)
+                }
+            }
+        }
+        EmitKind::Obj => {
+            let context = Context::create();
+            let compiler = compile(&context, build)?;
+            compiler.write_native(output.unwrap(), FileType::Object)
+        }
+    }
+}
+
+fn write_text(text: &str, output: Option<&Path>) -> Result<(), Error> {
+    match output {
+        Some(path) => fs::write(path, text).map_err(|err| {
+            error!(ErrorKind::DriverError, "Could not write output file",
+                   NameErrorComponent::new(format!("{}: {}", path.display(), err)))
+        }),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Collects sources and settings, either from the given files or from the project
+fn prepare(args: &InputArgs) -> Result<Build, Error> {
+    if !args.files.is_empty() {
+        let mut profile = match args.profile.as_deref() {
+            None | Some("debug") if !args.release => Profile::debug(),
+            None | Some("release") => Profile::release(),
+            Some(name) => return Err(error!(ErrorKind::DriverError, "Unknown profile",
+                                            NameErrorComponent::new(format!("Only 'debug' and 'release' profiles can be used without {}, got '{}'", MANIFEST_NAME, name)))),
+        };
+        override_profile(&mut profile, args);
+        return Ok(Build {
+            name: file_stem(&args.files[0]),
+            sources: load_sources(&args.files)?,
+            profile,
+            source_root: args.files[0].parent().map(Path::to_path_buf).unwrap_or_default(),
+            output_dir: PathBuf::new(),
+        });
+    }
+
+    let current_dir = env::current_dir().map_err(|err| {
+        error!(ErrorKind::DriverError, "Could not read current directory", NameErrorComponent::new(err.to_string()))
+    })?;
+    let project = Project::discover(&current_dir)?;
+    let profile_name = match args.profile {
+        Some(ref name) => name.as_str(),
+        None if args.release => "release",
+        None => "debug",
+    };
+    let mut profile = project.profile(profile_name)?;
+    override_profile(&mut profile, args);
+    Ok(Build {
+        name: project.output_name(),
+        sources: load_sources(&project.source_files()?)?,
+        profile,
+        source_root: project.source_dir(),
+        output_dir: project.root.join("target").join(profile_name),
+    })
+}
+
+fn override_profile(profile: &mut Profile, args: &InputArgs) {
+    if let Some(opt_level) = args.opt_level {
+        profile.opt_level = opt_level;
+    }
+}
+
+fn load_sources(files: &[PathBuf]) -> Result<Vec<SourceFile>, Error> {
+    let mut sources = Vec::new();
+    for path in files.iter() {
+        let content = fs::read_to_string(path).map_err(|err| {
+            error!(ErrorKind::DriverError, "Could not read source file",
+                   NameErrorComponent::new(format!("{}: {}", path.display(), err)))
+        })?;
+        sources.push(SourceFile {
+            path: path.clone(),
+            tokens: lexer::tokenize(&content),
+        });
+    }
+    Ok(sources)
+}
+
+fn parse_source(source: &SourceFile) -> Result<Vec<Box<dyn ASTExpr>>, Error> {
+    parser::parse(&source.tokens).map_err(|mut err| {
+        err.with_component(Box::new(NameErrorComponent::new(format!("in file {}", source.path.display()))));
+        err
+    })
+}
+
+/// Compiles every source file into its own module and links them into the program
+fn compile<'ctx>(context: &'ctx Context, build: &Build) -> Result<Compiler<'ctx>, Error> {
+    let module = context.create_module(&build.name);
+    let compiler = Compiler::new(context, module, build.profile.clone());
+    compiler.set_source_root(&build.source_root);
+    for source in build.sources.iter() {
+        let ast = parse_source(source)?;
+        compiler.compile_unit(&source.path, ast)?;
+    }
+    compiler.finish()?;
+    Ok(compiler)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "main".to_string())
+}
@@ -0,0 +1,216 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+use serde::Deserialize;
+
+use crate::{compiler::{profile::Profile, units::SOURCE_EXTENSION}, utils::{error::{Error, ErrorKind}, error_components::name_component::NameErrorComponent}, error};
+
+pub const MANIFEST_NAME: &str = "arclight.yml";
+
+/// Contents of the `arclight.yml` file
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    pub project: ProjectInfo,
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub version: String,
+}
+
+/// Profile settings, every setting that is not present is taken from the base profile
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProfileConfig {
+    pub opt_level: Option<u32>,
+    pub lto: Option<bool>,
+    pub error_handler: Option<bool>,
+}
+
+impl Manifest {
+    pub fn parse(content: &str) -> Result<Manifest, Error> {
+        let manifest: Manifest = serde_yaml::from_str(content).map_err(|err| {
+            error!(ErrorKind::DriverError, "Invalid project manifest", NameErrorComponent::new(err.to_string()))
+        })?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Checks settings that the YAML types alone do not restrict
+    fn validate(&self) -> Result<(), Error> {
+        for (name, profile) in self.profiles.iter() {
+            if let Some(opt_level) = profile.opt_level {
+                if opt_level > 3 {
+                    return Err(error!(ErrorKind::DriverError, "Invalid opt-level in project manifest",
+                                      NameErrorComponent::new(format!("Profile '{}' sets opt-level to {}, expected a value from 0 to 3", name, opt_level))));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Project described by `arclight.yml`, with sources in the `src` directory next to it
+#[derive(Debug)]
+pub struct Project {
+    pub root: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Project {
+    /// Finds `arclight.yml` in the directory or any of its parents
+    pub fn discover(start: &Path) -> Result<Project, Error> {
+        let mut dir = Some(start);
+        while let Some(current) = dir {
+            let path = current.join(MANIFEST_NAME);
+            if path.is_file() {
+                let content = fs::read_to_string(&path).map_err(|err| {
+                    error!(ErrorKind::DriverError, "Could not read project manifest",
+                           NameErrorComponent::new(format!("{}: {}", path.display(), err)))
+                })?;
+                let manifest = Manifest::parse(&content).map_err(|mut err| {
+                    err.with_component(Box::new(NameErrorComponent::new(format!("in file {}", path.display()))));
+                    err
+                })?;
+                return Ok(Project {
+                    root: current.to_path_buf(),
+                    manifest,
+                });
+            }
+            dir = current.parent();
+        }
+
+        Err(error!(ErrorKind::DriverError, "Could not find project manifest",
+                   NameErrorComponent::new(format!("No {} found in {} or any parent directory, pass source files to compile them directly", MANIFEST_NAME, start.display()))))
+    }
+
+    pub fn source_dir(&self) -> PathBuf {
+        self.root.join("src")
+    }
+
+    /// Lists all source files in the `src` tree, in a stable order
+    pub fn source_files(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut files = Vec::new();
+        collect_sources(&self.source_dir(), &mut files)?;
+        files.sort();
+        if files.is_empty() {
+            return Err(error!(ErrorKind::DriverError, "Project does not contain any source files",
+                              NameErrorComponent::new(format!("No .{} files found in {}", SOURCE_EXTENSION, self.source_dir().display()))));
+        }
+        Ok(files)
+    }
+
+    /// Builds the profile, starting from the built-in `debug` or `release` settings
+    pub fn profile(&self, name: &str) -> Result<Profile, Error> {
+        let config = self.manifest.profiles.get(name);
+        let mut profile = match name {
+            "debug" => Profile::debug(),
+            "release" => Profile::release(),
+            _ if config.is_some() => Profile::debug(),
+            _ => return Err(error!(ErrorKind::DriverError, "Unknown profile",
+                                   NameErrorComponent::new(format!("Profile '{}' is not defined in {}", name, MANIFEST_NAME)))),
+        };
+
+        if let Some(config) = config {
+            if let Some(opt_level) = config.opt_level {
+                profile.opt_level = opt_level;
+            }
+            if let Some(lto) = config.lto {
+                profile.lto = lto;
+            }
+            if let Some(error_handler) = config.error_handler {
+                profile.error_handler = error_handler;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Name of the produced executable
+    pub fn output_name(&self) -> String {
+        self.manifest.project.name.split_whitespace().collect::<Vec<&str>>().join("_")
+    }
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let entries = fs::read_dir(dir).map_err(|err| {
+        error!(ErrorKind::DriverError, "Could not read source directory",
+               NameErrorComponent::new(format!("{}: {}", dir.display(), err)))
+    })?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, files)?;
+        } else if matches!(path.extension(), Some(ext) if ext == SOURCE_EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{Manifest, Project};
+
+    const MANIFEST: &str = r#"
+project:
+    name: "My project"
+    version: "1.0.0"
+
+dependencies:
+    Hello: "*"
+
+profiles:
+    debug:
+        opt-level: 0
+        lto: false
+        error-handler: true
+
+    release:
+        opt-level: 2
+
+    bench:
+        opt-level: 3
+"#;
+
+    #[test]
+    fn parse_manifest() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        assert_eq!(manifest.project.name, "My project");
+        assert_eq!(manifest.project.version, "1.0.0");
+        assert_eq!(manifest.dependencies.get("Hello").map(|v| v.as_str()), Some("*"));
+        assert_eq!(manifest.profiles.len(), 3);
+    }
+
+    #[test]
+    fn select_profiles() {
+        let project = Project { root: PathBuf::from("."), manifest: Manifest::parse(MANIFEST).unwrap() };
+
+        let release = project.profile("release").unwrap();
+        assert_eq!(release.opt_level, 2);
+        assert!(release.lto);
+
+        let bench = project.profile("bench").unwrap();
+        assert_eq!(bench.opt_level, 3);
+        assert!(!bench.lto);
+
+        assert!(project.profile("unknown").is_err());
+        assert_eq!(project.output_name(), "My_project");
+    }
+
+    #[test]
+    fn reject_manifest_without_project() {
+        assert!(Manifest::parse("profiles: {}").is_err());
+    }
+
+    #[test]
+    fn reject_invalid_opt_level() {
+        let manifest = "project: { name: a, version: \"1.0.0\" }\nprofiles:\n    fast:\n        opt-level: 4\n";
+        assert_eq!(Manifest::parse(manifest).unwrap_err().message(), "Invalid opt-level in project manifest");
+    }
+}
@@ -6,6 +6,7 @@ pub mod lexer {
         // Single-character tokens.
         Paren(char),
         Brace(char),
+        Bracket(char),
         Operator(String),
         Separator(char),
         // Identifiers
@@ -14,6 +15,8 @@ pub mod lexer {
         Number(String),
         // Strings
         String(String),
+        // Characters, with their quotes like strings
+        Char(String),
         // End of file.
         EOF,
     }    
@@ -26,12 +29,19 @@ pub mod lexer {
         pub column: usize,
     }
 
+    /// Operators longer than one character, every prefix of them is an operator too
+    const OPERATORS: [&str; 21] = [
+        "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "=>", "..", "..=",
+        "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=",
+    ];
+
     #[derive(Debug, Clone)]
     enum LexerState {
         Start,
         Identifier,
         Number,
         String,
+        Char,
         Comment,
         Operator,
         Error,
@@ -97,26 +107,30 @@ pub mod lexer {
                             ')' => add_token!(self, TokenType::Paren(')'), LexerState::Start),
                             '{' => add_token!(self, TokenType::Brace('{'), LexerState::Start),
                             '}' => add_token!(self, TokenType::Brace('}'), LexerState::Start),
+                            '[' => add_token!(self, TokenType::Bracket('['), LexerState::Start),
+                            ']' => add_token!(self, TokenType::Bracket(']'), LexerState::Start),
                             ':' => add_token!(self, TokenType::Separator(':'), LexerState::Start),
                             ',' => add_token!(self, TokenType::Separator(','), LexerState::Start),
-                            '.' => add_token!(self, TokenType::Separator('.'), LexerState::Start),
+                            '.' => add_token!(self, LexerState::Operator, c ;),
                             ';' => add_token!(self, TokenType::Separator(';'), LexerState::Start),
                             '=' => add_token!(self, LexerState::Operator, c ;),
                             '+' => add_token!(self, LexerState::Operator, c ;),
-                            '-' => add_token!(self, LexerState::Number, c ;),
+                            '-' => add_token!(self, LexerState::Operator, c ;),
                             '*' => add_token!(self, LexerState::Operator, c ;),
+                            '/' => add_token!(self, LexerState::Operator, c ;),
                             '%' => add_token!(self, LexerState::Operator, c ;),
                             '!' => add_token!(self, LexerState::Operator, c ;),
                             '<' => add_token!(self, LexerState::Operator, c ;),
                             '>' => add_token!(self, LexerState::Operator, c ;),
                             '&' => add_token!(self, LexerState::Operator, c ;),
                             '|' => add_token!(self, LexerState::Operator, c ;),
+                            '^' => add_token!(self, LexerState::Operator, c ;),
 
                             // More complex tokens
-                            '/' => add_token!(self, TokenType::Operator("/".to_string()), LexerState::Start),
                             '#' => add_token!(self, LexerState::Comment),
 
                             '"' => add_token!(self, LexerState::String, c ;),
+                            '\'' => add_token!(self, LexerState::Char, c ;),
 
                             ' ' | '\t' | '\n' | '\r' => (),
                             _ => add_token!(self, LexerState::Identifier, c ;),
@@ -131,24 +145,24 @@ pub mod lexer {
                         }
                         _ => {
                             add_token!(self, TokenType::Identifier(self.curr_token.clone()), LexerState::Start);
-                            self.feed(c);
                             self.curr_token.clear();
+                            self.feed(c);
                         }
                     }
                 }
                 LexerState::Number => {
                     // Number
                     match c {
+                        // Second dot starts a range operator, like `0..n`
+                        '.' if self.curr_token.ends_with('.') => {
+                            self.curr_token.pop();
+                            add_token!(self, TokenType::Number(self.curr_token.clone()), LexerState::Operator);
+                            self.curr_token = "..".to_string();
+                        }
                         '0' ..= '9' | '.' | 'f' | 'u' | 'i' => {
                             self.curr_token.push(c);
                         }
                         _ => {
-                            if self.curr_token.len() == 1 && self.curr_token.chars().next().unwrap() == '-' {
-                                add_token!(self, TokenType::Operator(self.curr_token.clone()), LexerState::Start);
-                                self.curr_token.clear();
-                                self.feed(c);
-                                return;
-                            }
                             add_token!(self, TokenType::Number(self.curr_token.clone()), LexerState::Start);
                             self.curr_token.clear();
                             self.feed(c);
@@ -158,9 +172,24 @@ pub mod lexer {
                 LexerState::String => {
                     // String
                     match c {
-                        '"' => {
+                        // Quote after an odd number of backslashes is escaped
+                        '"' if self.curr_token.chars().rev().take_while(|c| *c == '\\').count() % 2 == 0 => {
                             self.curr_token.push(c);
-                            add_token!(self, TokenType::String(self.curr_token.clone()), LexerState::Start); 
+                            add_token!(self, TokenType::String(self.curr_token.clone()), LexerState::Start);
+                            self.curr_token.clear();
+                        },
+                        _ => {
+                            self.curr_token.push(c);
+                        }
+                    }
+                }
+                LexerState::Char => {
+                    // Character, escapes are replaced by the parser
+                    match c {
+                        '\'' if self.curr_token.chars().rev().take_while(|c| *c == '\\').count() % 2 == 0 => {
+                            self.curr_token.push(c);
+                            add_token!(self, TokenType::Char(self.curr_token.clone()), LexerState::Start);
+                            self.curr_token.clear();
                         },
                         _ => {
                             self.curr_token.push(c);
@@ -175,23 +204,18 @@ pub mod lexer {
                     }
                 }
                 LexerState::Operator => {
-                    // Operator
-                    match c {
-                        '=' => { self.curr_token.push(c); }
-                        '+' => { self.curr_token.push(c); }
-                        '-' => { self.curr_token.push(c); }
-                        '*' => { self.curr_token.push(c); }
-                        '%' => { self.curr_token.push(c); }
-                        '!' => { self.curr_token.push(c); }
-                        '<' => { self.curr_token.push(c); }
-                        '>' => { self.curr_token.push(c); }
-                        '&' => { self.curr_token.push(c); }
-                        '|' => { self.curr_token.push(c); }
-                        _ => {
+                    // Operator, only extended while it stays a known operator, so `a&&!b` is `&&` and `!`
+                    if OPERATORS.contains(&format!("{}{}", self.curr_token, c).as_str()) {
+                        self.curr_token.push(c);
+                    } else {
+                        // Single dot is just a separator
+                        if self.curr_token == "." {
+                            add_token!(self, TokenType::Separator('.'), LexerState::Start);
+                        } else {
                             add_token!(self, TokenType::Operator(self.curr_token.clone()), LexerState::Start);
-                            self.feed(c);
-                            self.curr_token.clear();
                         }
+                        self.curr_token.clear();
+                        self.feed(c);
                     }
                 }
                 LexerState::Error => {
@@ -251,6 +275,34 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::String("\"abc\"".to_string()));
     }
 
+    #[test]
+    fn lex_escaped_string() {
+        let input = r#""a\"b\\" c;"#;
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[0].token_type, TokenType::String(r#""a\"b\\""#.to_string()));
+        assert_eq!(tokens[1].token_type, TokenType::Identifier("c".to_string()));
+    }
+
+    #[test]
+    fn lex_chars() {
+        let input = r"'a' '\'' '\\';";
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].token_type, TokenType::Char("'a'".to_string()));
+        assert_eq!(tokens[1].token_type, TokenType::Char(r"'\''".to_string()));
+        assert_eq!(tokens[2].token_type, TokenType::Char(r"'\\'".to_string()));
+    }
+
+    #[test]
+    fn lex_brackets() {
+        let input = "s[0];";
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(tokens[1].token_type, TokenType::Bracket('['));
+        assert_eq!(tokens[3].token_type, TokenType::Bracket(']'));
+    }
+
     #[test]
     fn lex_single_comment() {
         let input = "#abc";
@@ -274,6 +326,25 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::Operator("==".to_string()));
     }
 
+    #[test]
+    fn lex_compound_assignment() {
+        let input = "x /= 2;";
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[1].token_type, TokenType::Operator("/=".to_string()));
+    }
+
+    #[test]
+    fn lex_minus_as_operator() {
+        let input = "a-1 -=-2;";
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 8);
+        assert_eq!(tokens[1].token_type, TokenType::Operator("-".to_string()));
+        assert_eq!(tokens[2].token_type, TokenType::Number("1".to_string()));
+        assert_eq!(tokens[3].token_type, TokenType::Operator("-=".to_string()));
+        assert_eq!(tokens[4].token_type, TokenType::Operator("-".to_string()));
+    }
+
     #[test]
     fn lex_import_statement() {
         let input = "import hello::world { print, println };";
@@ -292,4 +363,29 @@ mod tests {
         assert_eq!(tokens[9].token_type, TokenType::Brace('}'), "Expected '}}' brace");
         assert_eq!(tokens[10].token_type, TokenType::Separator(';'), "Expected ';'");
     }
+
+    #[test]
+    fn lex_range() {
+        let input = "0..n 1.5..=2;";
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 8);
+        assert_eq!(tokens[0].token_type, TokenType::Number("0".to_string()));
+        assert_eq!(tokens[1].token_type, TokenType::Operator("..".to_string()));
+        assert_eq!(tokens[2].token_type, TokenType::Identifier("n".to_string()));
+        assert_eq!(tokens[3].token_type, TokenType::Number("1.5".to_string()));
+        assert_eq!(tokens[4].token_type, TokenType::Operator("..=".to_string()));
+        assert_eq!(tokens[5].token_type, TokenType::Number("2".to_string()));
+    }
+
+    #[test]
+    fn lex_operator_before_not() {
+        let operators = |input: &str| tokenize(input).into_iter().filter_map(|token| match token.token_type {
+            TokenType::Operator(op) => Some(op),
+            _ => None,
+        }).collect::<Vec<String>>();
+        assert_eq!(operators("a&&!b"), vec!["&&", "!"]);
+        assert_eq!(operators("x=!y"), vec!["=", "!"]);
+        assert_eq!(operators("a||!b != c"), vec!["||", "!", "!="]);
+        assert_eq!(operators("x<<=1; y>>2; z=>-1"), vec!["<<=", ">>", "=>", "-"]);
+    }
 }
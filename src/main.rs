@@ -1,33 +1,32 @@
-use std::{fs::File, io::Read};
+use std::{panic, process};
 
-use compiler::compiler::Compiler;
-use inkwell::context::Context;
+use clap::Parser;
+use driver::cli::Cli;
+use utils::error::CompilationFailed;
 
 mod lexer;
 mod parser;
 mod compiler;
+mod driver;
 mod utils;
 
 fn main() {
-    let mut f = File::open("test.arl").unwrap();
-
-    let mut content = String::new();
-    f.read_to_string(&mut content).unwrap();
-
-    let tokens = lexer::lexer::tokenize(content.as_str());
-
-    for t in tokens.iter() {
-        println!("{:?}", t);
+    let cli = Cli::parse();
+
+    // Code generation reports errors by unwinding, so catch them here to exit gracefully
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| driver::driver::run(cli)));
+    match result {
+        Ok(Ok(code)) => process::exit(code),
+        Ok(Err(err)) => {
+            err.print_err();
+            process::exit(1);
+        }
+        Err(payload) => {
+            if payload.is::<CompilationFailed>() {
+                process::exit(1);
+            }
+            // Anything else is a bug in the compiler itself
+            panic::resume_unwind(payload);
+        }
     }
-
-    let ast = parser::parser::parse(&tokens);
-
-    for a in ast.iter() {
-        println!("{}", a.to_string());
-    }
-
-    let context = Context::create();
-    let module = context.create_module("test");
-    let compiler = Compiler::new(&context, module);
-    compiler.compile(ast);
 }
@@ -0,0 +1,152 @@
+use std::cell::RefCell;
+
+use inkwell::{AddressSpace, builder::Builder, context::Context, module::Module, types::{BasicType, BasicTypeEnum, StructType}, values::{AnyValueEnum, IntValue, PointerValue, StructValue}};
+
+use crate::{compiler::checker::{TypeChecker, compatible}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, data_types::{DataType, ToBasic}, value_expression::ValueExpr, scope::ScopeManager};
+
+/// Array literal, like `[1, 2, 3]`, all elements have the same type
+pub struct ArrayLiteralExpr {
+    token: Token,
+    elements: Vec<Box<dyn ASTExpr>>,
+    // Type of the elements, it is known after the literal was checked
+    element_type: RefCell<DataType>,
+}
+
+impl Parseable for ArrayLiteralExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Bracket('[') {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing array literal",
+                              ErrorTokenComponent::new("Expected '['".to_string(), Some(token.clone()))));
+        }
+
+        // Should be followed by elements separated by commas
+        *pos += 1;
+        let mut elements = vec![];
+        while tokens[*pos].token_type != TokenType::Bracket(']') {
+            match ValueExpr::parse(tokens, pos) {
+                Ok(element) => elements.push(element),
+                Err(_) => error!(ErrorKind::ParserError, "Error while parsing array literal",
+                                 ErrorTokenComponent::new("Expected array element".to_string(), Some(tokens[*pos].clone()))).panic()
+            }
+            match tokens[*pos].token_type {
+                TokenType::Separator(',') => *pos += 1,
+                TokenType::Bracket(']') => {}
+                _ => error!(ErrorKind::ParserError, "Error while parsing array literal",
+                            ErrorTokenComponent::new("Expected ',' or ']'".to_string(), Some(tokens[*pos].clone()))).panic()
+            }
+        }
+        *pos += 1;
+
+        Ok(Box::new(ArrayLiteralExpr {
+            token,
+            elements,
+            element_type: RefCell::new(DataType::Unknown),
+        }))
+    }
+}
+
+impl ASTExpr for ArrayLiteralExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let element_type = self.element_type.borrow().clone();
+        if element_type == DataType::Unknown {
+            error!(ErrorKind::CompilerError, "Can not infer type of empty array",
+                   ErrorTokenComponent::new("Array has no elements".to_string(), Some(self.token.clone()))).panic()
+        }
+
+        // Elements are inserted one by one, so they do not have to be constants
+        let mut array = element_type.into_basic_type(context).array_type(self.elements.len() as u32).get_undef();
+        for (i, element) in self.elements.iter().enumerate() {
+            let value = match element.generate(context, module, builder, scope_manager) {
+                Some(value) => value,
+                None => error!(ErrorKind::CompilerError, "Expected value for array element",
+                               ErrorTokenComponent::new(format!("Element {} has no value", i), Some(self.token.clone()))).panic()
+            };
+            array = builder.build_insert_value(array, value.to_basic(), i as u32, "arrtmp").unwrap().into_array_value();
+        }
+        Some(AnyValueEnum::ArrayValue(array))
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::Array(Box::new(self.element_type.borrow().clone()), self.elements.len() as u32)
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let mut element_type = DataType::Unknown;
+        for (i, element) in self.elements.iter().enumerate() {
+            let found = element.check(checker);
+            if element_type == DataType::Unknown {
+                element_type = found;
+            } else if !compatible(&element_type, &found) {
+                checker.error(error!(ErrorKind::TypeError, "Array elements have different types",
+                                     ErrorTokenComponent::new(format!("Element {} has a different type than the first one", i), Some(self.token.clone()))
+                                     NameErrorComponent::new(format!("Expected: {}", element_type))
+                                     NameErrorComponent::new(format!("Found: {}", found))));
+            }
+        }
+        if self.elements.is_empty() {
+            checker.error(error!(ErrorKind::TypeError, "Can not infer type of empty array",
+                                 ErrorTokenComponent::new("Array has no elements".to_string(), Some(self.token.clone()))));
+        }
+        if matches!(element_type, DataType::Void | DataType::Never) {
+            checker.error(error!(ErrorKind::TypeError, "Array elements have to be values",
+                                 ErrorTokenComponent::new(format!("Elements of type {} can not be stored", element_type), Some(self.token.clone()))));
+            element_type = DataType::Unknown;
+        }
+        *self.element_type.borrow_mut() = element_type.clone();
+        DataType::Array(Box::new(element_type), self.elements.len() as u32)
+    }
+
+    fn to_string(&self) -> String {
+        format!("Array [{}]", self.elements.iter().map(|element| element.to_string()).collect::<Vec<String>>().join(", "))
+    }
+}
+
+/// Slices are a pointer to the first element and the number of elements
+pub fn slice_type<'a>(context: &'a Context, element: BasicTypeEnum<'a>) -> StructType<'a> {
+    context.struct_type(&[element.ptr_type(AddressSpace::Generic).into(), context.i64_type().into()], false)
+}
+
+pub fn build_slice<'a>(builder: &Builder<'a>, slice_type: StructType<'a>, data: PointerValue<'a>, len: IntValue<'a>) -> StructValue<'a> {
+    let slice = builder.build_insert_value(slice_type.get_undef(), data, 0, "slicetmp").unwrap();
+    builder.build_insert_value(slice, len, 1, "slicetmp").unwrap().into_struct_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::{checker::check_source, compiler::run_source};
+
+    fn parse_value(input: &str) -> String {
+        let tokens = tokenize(input);
+        let mut pos = 0;
+        ValueExpr::parse(&tokens, &mut pos).unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_array_literals() {
+        assert_eq!(parse_value("[1, 2 + x, 3];"), "Array [I32(1), ADD I32(2), VarCall x, I32(3)]");
+        assert_eq!(parse_value("[[1], [2,]][0];"), "Index Array [Array [I32(1)], Array [I32(2)]][I32(0)]");
+    }
+
+    #[test]
+    fn parse_slices() {
+        assert_eq!(parse_value("a[1..n - 1];"), "Slice VarCall a[I32(1)..SUBTRACT VarCall n, I32(1)]");
+        assert_eq!(parse_value("a[i..=j][0];"), "Index Slice VarCall a[VarCall i..=VarCall j][I32(0)]");
+    }
+
+    #[test]
+    fn check_arrays() {
+        assert!(check_source("fun f(s: i32[]): i32 { var a = [1, 2, 3]; var b: i32[] = a[0..2]; s[0] + b[1] + a[2] }").is_empty());
+        assert_eq!(check_source("fun f(): void { [1, 2u8]; }"), vec!["TypeError: Array elements have different types"]);
+        assert_eq!(check_source("fun f(): void { var a = [1, 2, 3]; var c: i32[2] = a; }"), vec!["TypeError: Variable types do not match"]);
+    }
+
+    #[test]
+    fn run_arrays() {
+        assert_eq!(run_source("fun main(): i32 { var a = [1, 2, 3, 4]; var s = a[1..=2]; var total = 0; for (i in 0..2) { total += s[i]; } total * 10 + sum(a[0..4]) } fun sum(x: i32[]): i32 { x[0] + x[3] }"), 55);
+        assert_eq!(run_source(r#"fun main(): i32 { var m: i32[2][2] = [[1, 2], [3, 4]]; m[1][0] + "abc"[1..3][0] as i32 }"#), 101);
+    }
+}
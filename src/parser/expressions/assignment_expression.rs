@@ -0,0 +1,304 @@
+use colored::*;
+use inkwell::{builder::Builder, context::Context, module::Module, values::AnyValueEnum};
+
+use crate::{compiler::checker::{TypeChecker, BindingKind, compatible}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, data_types::{DataType, ToAny, ToBasic}, math_expression::{MathOperation, build_math, check_math}, string_expression::build_concat, value_expression::ValueExpr, variable_expression::VariableCallExpr, dynamic_get_expression::DynamicGetExpr, scope::ScopeManager};
+
+pub struct AssignExpr {
+    token: Token,
+    name: String,
+    // Operation of compound assignment, like `+=`
+    operation: Option<MathOperation>,
+    value: Box<dyn ASTExpr>,
+}
+
+impl Parseable for AssignExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with a variable name
+        let token = tokens[*pos].clone();
+        let name = match token.token_type {
+            TokenType::Identifier(ref s) => s.clone(),
+            _ => return Err(error!(ErrorKind::ParserError, "Error while parsing assignment",
+                                   ErrorTokenComponent::new("Expected variable name".to_string(), Some(token.clone()))))
+        };
+
+        // Should be followed by "=" or a compound operator and a value
+        *pos += 1;
+        let operation = parse_operation(tokens, pos)?;
+        let value = parse_value(tokens, pos)?;
+
+        Ok(Box::new(AssignExpr {
+            token,
+            name,
+            operation,
+            value,
+        }))
+    }
+}
+
+impl ASTExpr for AssignExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        // Only variables can be assigned to
+        if scope_manager.scope.fn_args.contains_key(&self.name) {
+            error!(ErrorKind::CompilerError, "Can not assign to function argument",
+                   ErrorTokenComponent::new(format!("'{}' is an argument of the function", &self.name.green()), Some(self.token.clone()))).panic()
+        }
+        if scope_manager.scope.constants.contains_key(&self.name) {
+            error!(ErrorKind::CompilerError, "Can not assign to constant",
+                   ErrorTokenComponent::new(format!("'{}' is a constant", &self.name.green()), Some(self.token.clone()))).panic()
+        }
+        let variable = match scope_manager.scope.variables.get(&self.name) {
+            Some(variable) => variable.clone(),
+            None => error!(ErrorKind::CompilerError, "Variable does not exist",
+                           ErrorTokenComponent::new(format!("Variable '{}' does not exist", &self.name.green()), Some(self.token.clone()))).panic()
+        };
+
+        let mut value = match self.value.generate(context, module, builder, scope_manager) {
+            Some(value) => value,
+            None => error!(ErrorKind::CompilerError, "Expected value for assignment",
+                           ErrorTokenComponent::new(format!("Nothing is assigned to '{}'", &self.name.green()), Some(self.token.clone()))).panic()
+        };
+        let expected = variable.data_type.into_llvm_type(context);
+        if value.get_type() != expected {
+            error!(ErrorKind::CompilerError, "Assignment types do not match",
+                   ErrorTokenComponent::new(format!("Variable '{}' has a different type", &self.name.green()), Some(self.token.clone()))
+                   NameErrorComponent::new(format!("Expected: {}", variable.data_type))
+                   NameErrorComponent::new(format!("Found: {}", value.get_type()))).panic()
+        }
+
+        // Compound assignment works with the current value
+        if let Some(operation) = self.operation {
+            let current = builder.build_load(variable.pointer, &self.name);
+            value = if variable.data_type == DataType::String {
+                AnyValueEnum::StructValue(build_concat(context, builder, current.into_struct_value(), value.into_struct_value()))
+            } else {
+                build_math(builder, operation, current.to_any(), value, variable.data_type.is_unsigned()).unwrap()
+            };
+        }
+        builder.build_store(variable.pointer, value.to_basic());
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let found = self.value.check(checker);
+        let binding = match checker.lookup(&self.name) {
+            Some(binding) => binding,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Variable does not exist",
+                                     ErrorTokenComponent::new(format!("Variable '{}' does not exist", &self.name.green()), Some(self.token.clone()))));
+                return DataType::Void;
+            }
+        };
+        match binding.kind {
+            BindingKind::Argument => checker.error(error!(ErrorKind::TypeError, "Can not assign to function argument",
+                                                          ErrorTokenComponent::new(format!("'{}' is an argument of the function", &self.name.green()), Some(self.token.clone())))),
+            BindingKind::Constant => checker.error(error!(ErrorKind::TypeError, "Can not assign to constant",
+                                                          ErrorTokenComponent::new(format!("'{}' is a constant", &self.name.green()), Some(self.token.clone())))),
+            BindingKind::Variable => {}
+        }
+        if !compatible(&binding.data_type, &found) {
+            checker.error(error!(ErrorKind::TypeError, "Assignment types do not match",
+                                 ErrorTokenComponent::new(format!("Variable '{}' has a different type", &self.name.green()), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("Expected: {}", binding.data_type))
+                                 NameErrorComponent::new(format!("Found: {}", found))));
+        } else if let Some(operation) = self.operation {
+            check_math(checker, &self.token, operation, binding.data_type, found);
+        }
+        DataType::Void
+    }
+
+    fn to_string(&self) -> String {
+        match self.operation {
+            Some(operation) => format!("Assign {:?} {} = {}", operation, self.name, self.value.to_string()),
+            None => format!("Assign {} = {}", self.name, self.value.to_string()),
+        }
+    }
+}
+
+/// Assignment to a field of a class instance, like `this.value += 1`
+pub struct FieldAssignExpr {
+    token: Token,
+    // Variable that stores the instance
+    variable: String,
+    target: Box<dyn ASTExpr>,
+    operation: Option<MathOperation>,
+    value: Box<dyn ASTExpr>,
+}
+
+impl Parseable for FieldAssignExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with a variable name followed by field names
+        let token = tokens[*pos].clone();
+        let variable = match token.token_type {
+            TokenType::Identifier(ref s) => s.clone(),
+            _ => return Err(error!(ErrorKind::ParserError, "Error while parsing field assignment",
+                                   ErrorTokenComponent::new("Expected variable name".to_string(), Some(token.clone()))))
+        };
+        let mut target = VariableCallExpr::parse(tokens, pos)?;
+        let mut fields = 0;
+        while tokens[*pos].token_type == TokenType::Separator('.') {
+            let field = match tokens[*pos + 1].token_type {
+                TokenType::Identifier(ref field) => field.clone(),
+                _ => break,
+            };
+            target = Box::new(DynamicGetExpr::new(tokens[*pos].clone(), target, field, None));
+            fields += 1;
+            *pos += 2;
+        }
+        if fields == 0 {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing field assignment",
+                              ErrorTokenComponent::new("Expected '.' and field name".to_string(), Some(tokens[*pos].clone()))));
+        }
+
+        // Should be followed by "=" or a compound operator and a value
+        let operation = parse_operation(tokens, pos)?;
+        let value = parse_value(tokens, pos)?;
+
+        Ok(Box::new(FieldAssignExpr {
+            token,
+            variable,
+            target,
+            operation,
+            value,
+        }))
+    }
+}
+
+impl ASTExpr for FieldAssignExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let data_type = self.target.data_type(scope_manager);
+        let mut value = match self.value.generate(context, module, builder, scope_manager) {
+            Some(value) => value,
+            None => error!(ErrorKind::CompilerError, "Expected value for assignment",
+                           ErrorTokenComponent::new(format!("Nothing is assigned to a field of '{}'", &self.variable.green()), Some(self.token.clone()))).panic()
+        };
+        let pointer = match self.target.pointer(context, module, builder, scope_manager) {
+            Some(pointer) => pointer,
+            None => error!(ErrorKind::CompilerError, "Can not assign to field",
+                           ErrorTokenComponent::new(format!("'{}' is not stored in a variable", &self.variable.green()), Some(self.token.clone()))).panic()
+        };
+
+        // Compound assignment works with the current value
+        if let Some(operation) = self.operation {
+            let current = builder.build_load(pointer, "fieldtmp");
+            value = if data_type == DataType::String {
+                AnyValueEnum::StructValue(build_concat(context, builder, current.into_struct_value(), value.into_struct_value()))
+            } else {
+                build_math(builder, operation, current.to_any(), value, data_type.is_unsigned()).unwrap()
+            };
+        }
+        builder.build_store(pointer, value.to_basic());
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let found = self.value.check(checker);
+        // Fields of arguments can only be changed through `this`
+        if let Some(binding) = checker.lookup(&self.variable) {
+            match binding.kind {
+                BindingKind::Argument if !matches!(binding.data_type, DataType::Reference(_)) =>
+                    checker.error(error!(ErrorKind::TypeError, "Can not assign to function argument",
+                                         ErrorTokenComponent::new(format!("'{}' is an argument of the function", &self.variable.green()), Some(self.token.clone())))),
+                BindingKind::Constant => checker.error(error!(ErrorKind::TypeError, "Can not assign to constant",
+                                                              ErrorTokenComponent::new(format!("'{}' is a constant", &self.variable.green()), Some(self.token.clone())))),
+                _ => {}
+            }
+        }
+        let expected = self.target.check(checker);
+        if !compatible(&expected, &found) {
+            checker.error(error!(ErrorKind::TypeError, "Assignment types do not match",
+                                 ErrorTokenComponent::new(format!("Field of '{}' has a different type", &self.variable.green()), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("Expected: {}", expected))
+                                 NameErrorComponent::new(format!("Found: {}", found))));
+        } else if let Some(operation) = self.operation {
+            check_math(checker, &self.token, operation, expected, found);
+        }
+        DataType::Void
+    }
+
+    fn to_string(&self) -> String {
+        match self.operation {
+            Some(operation) => format!("Assign {:?} {} = {}", operation, self.target.to_string(), self.value.to_string()),
+            None => format!("Assign {} = {}", self.target.to_string(), self.value.to_string()),
+        }
+    }
+}
+
+/// "=" or a compound operator, like `+=`, `pos` is moved after it
+fn parse_operation(tokens: &Vec<Token>, pos: &mut usize) -> Result<Option<MathOperation>, Error> {
+    let operation = match tokens[*pos].token_type {
+        TokenType::Operator(ref op) if op == "=" => None,
+        TokenType::Operator(ref op) if op.ends_with('=') => match MathOperation::parse(&op[..op.len() - 1]) {
+            Some(operation) => Some(operation),
+            None => return Err(error!(ErrorKind::ParserError, "Error while parsing assignment",
+                                      ErrorTokenComponent::new("Expected assignment operator".to_string(), Some(tokens[*pos].clone()))))
+        },
+        _ => return Err(error!(ErrorKind::ParserError, "Error while parsing assignment",
+                               ErrorTokenComponent::new("Expected assignment operator".to_string(), Some(tokens[*pos].clone()))))
+    };
+    *pos += 1;
+    Ok(operation)
+}
+
+fn parse_value(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+    let start = *pos;
+    ValueExpr::parse(tokens, pos).map_err(|err| {
+        if *pos > start {
+            return err;
+        }
+        error!(ErrorKind::ParserError, "Error while parsing assignment",
+               ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::TokenType, test_token, parser::expressions::{Parseable, basic_expression::BasicExpr}};
+    use crate::compiler::compiler::run_source;
+
+    #[test]
+    fn parse_assignment() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("t1".to_string())),
+            test_token!(TokenType::Operator("=".to_string())),
+            test_token!(TokenType::Identifier("t2".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = BasicExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Assign t1 = VarCall t2");
+        assert_eq!(pos, 4);
+    }
+
+    #[test]
+    fn parse_compound_assignment() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("%=".to_string())),
+            test_token!(TokenType::Number("3".to_string())),
+            test_token!(TokenType::Operator("+".to_string())),
+            test_token!(TokenType::Number("1".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = super::AssignExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Assign MODULO x = ADD I32(3), I32(1)");
+    }
+
+    #[test]
+    fn parse_field_assignment() {
+        let tokens = crate::lexer::lexer::tokenize("this.size.w *= 2; c.add(1);");
+        let mut pos = 0;
+        assert_eq!(BasicExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Assign MULTIPLY Get Get VarCall this.size.w = I32(2)");
+        assert_eq!(BasicExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Get VarCall c.add(I32(1))");
+    }
+
+    #[test]
+    fn run_assignments() {
+        assert_eq!(run_source("fun main(): i32 { var x: i32 = 1; x = 5; x }"), 5);
+        assert_eq!(run_source("fun main(): i32 { var x: i32 = 10; x += 4; x -= 2; x *= 3; x /= 4; x %= 5; x }"), 4);
+        assert_eq!(run_source("fun main(): i32 { var s: i32 = 0; for (var i: i32 = 1; i <= 4; i += 1) { s += i; } s }"), 10);
+        assert_eq!(run_source("fun main(): i32 { var t1: i32 = 0; var t2: i32 = 1; var next: i32 = 1; for (i in 0..5) { t1 = t2; t2 = next; next = t1 + t2; } t2 }"), 8);
+    }
+}
@@ -1,25 +1,27 @@
 
 use crate::{try_parse, lexer::lexer::TokenType, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
 
-use super::{Parseable, block_expression::BlockExpr, value_expression::ValueExpr };
+use super::{Parseable, block_expression::BlockExpr, value_expression::ValueExpr, return_expression::ReturnExpr, if_expression::IfExpr, loop_expression::{LoopExpr, ForExpr, BreakExpr, ContinueExpr}, assignment_expression::{AssignExpr, FieldAssignExpr}, tuple_expression::DestructureExpr, match_expression::MatchExpr };
 
 // Parses any basic expression
 pub struct BasicExpr {}
 
 impl Parseable for BasicExpr {
     fn parse(tokens: &Vec<crate::lexer::lexer::Token>, pos: &mut usize) -> Result<Box<dyn super::ASTExpr>, Error> {
-        let temp = try_parse!(tokens, *pos, BlockExpr);
-        if temp.is_ok() {
-            *pos += 1;
+        // Block-like expressions do not need a semicolon
+        let start = *pos;
+        let temp = try_parse!(tokens, *pos, BlockExpr IfExpr MatchExpr LoopExpr ForExpr);
+        // Error after the keyword or brace is inside of the block-like expression
+        if temp.is_ok() || *pos > start {
             return temp;
         }
-        let temp = try_parse!(tokens, *pos, ValueExpr);
+        let temp = try_parse!(tokens, *pos, ReturnExpr BreakExpr ContinueExpr DestructureExpr FieldAssignExpr AssignExpr ValueExpr)?;
         // Should be followed by a semicolon
         if tokens[*pos].token_type != TokenType::Separator(';') {
             return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing basic expression",
                               ErrorTokenComponent::new("Expected ';'".to_string(), Some(tokens[*pos].clone()))));
         }
         *pos += 1;
-        return temp;
+        return Ok(temp);
     }
 }
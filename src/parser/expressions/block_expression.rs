@@ -1,9 +1,15 @@
-use crate::{lexer::lexer::TokenType, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
+use std::cell::RefCell;
 
-use super::{Parseable, ASTExpr, basic_expression::BasicExpr, function_expression::FunctionExpr, scope::ScopeManager};
+use crate::{compiler::checker::TypeChecker, lexer::lexer::TokenType, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
+
+use super::{Parseable, ASTExpr, basic_expression::BasicExpr, function_expression::FunctionExpr, value_expression::ValueExpr, return_expression::is_terminated, data_types::DataType, scope::ScopeManager};
 
 pub struct BlockExpr {
     statements: Vec<Box<dyn ASTExpr>>,
+    // Last expression without a semicolon, it is the value of the block
+    result: Option<Box<dyn ASTExpr>>,
+    // Type of the result, it is known only while the scope of the block exists
+    result_type: RefCell<DataType>,
 }
 
 impl Parseable for BlockExpr {
@@ -16,13 +22,40 @@ impl Parseable for BlockExpr {
 
         *pos += 1;
         let mut statements: Vec<Box<dyn ASTExpr>> = Vec::new();
+        let mut result: Option<Box<dyn ASTExpr>> = None;
         while tokens[*pos].token_type != TokenType::Brace('}') {
-            let expr = BasicExpr::parse(tokens, pos)?;
-            statements.push(expr);
+            let start = *pos;
+            match BasicExpr::parse(tokens, pos) {
+                // Block-like expression at the end is the result, like `{ if (x) { 1 } else { 2 } }`
+                Ok(expr) if tokens[*pos - 1].token_type != TokenType::Separator(';') && tokens[*pos].token_type == TokenType::Brace('}') => {
+                    result = Some(expr);
+                    break;
+                }
+                Ok(expr) => statements.push(expr),
+                // Could be the result of the block, it only misses the semicolon before the brace
+                Err(err) if tokens[*pos].token_type == TokenType::Brace('}') => {
+                    let failed = *pos;
+                    *pos = start;
+                    match ValueExpr::parse(tokens, pos) {
+                        Ok(expr) if tokens[*pos].token_type == TokenType::Brace('}') => {
+                            result = Some(expr);
+                            break;
+                        }
+                        _ => {
+                            *pos = failed;
+                            return Err(err);
+                        }
+                    }
+                }
+                Err(err) => return Err(err),
+            }
         }
+        *pos += 1;
 
         Ok(Box::new(BlockExpr {
             statements,
+            result,
+            result_type: RefCell::new(DataType::Void),
         }))
     }
 }
@@ -30,26 +63,85 @@ impl Parseable for BlockExpr {
 impl ASTExpr for BlockExpr {
 
     fn to_string(&self) -> String {
-        format!("{{\n\t {} \n}}", self.statements.iter().map(|s| s.to_string()).collect::<Vec<String>>().join("\n\t "))
+        let mut statements = self.statements.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        if let Some(ref result) = self.result {
+            statements.push(format!("Result {}", result.to_string()));
+        }
+        format!("{{\n\t {} \n}}", statements.join("\n\t "))
     }
 
     fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &inkwell::builder::Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
-        // let function = sm.function.as_ref().unwrap();
-        // let block_block = context.append_basic_block(**function, "code_block");
-        // builder.build_unconditional_branch(block_block );
-        // builder.position_at_end(block_block);
-
         scope_manager.create_scope();
         for statement in &self.statements {
+            // Code after a return is never executed
+            if is_terminated(builder) {
+                break;
+            }
             statement.generate(context, module, builder, scope_manager);
         }
+        let mut value = None;
+        if let Some(ref result) = self.result {
+            if !is_terminated(builder) {
+                value = result.generate(context, module, builder, scope_manager);
+                *self.result_type.borrow_mut() = result.data_type(scope_manager);
+            }
+        }
         scope_manager.exit_scope();
+        return value;
+    }
 
-        // let after_block = context.append_basic_block(**function, "after_block");
-        // builder.build_unconditional_branch(after_block);
-        // builder.position_at_end(after_block);
-        return None;
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        self.result_type.borrow().clone()
     }
 
-    
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        checker.enter_scope();
+        // Statement that never finishes, like a return, makes the rest of the block unreachable
+        let mut diverges = false;
+        for statement in &self.statements {
+            if statement.check(checker) == DataType::Never {
+                diverges = true;
+            }
+        }
+        let result_type = match self.result {
+            Some(ref result) => result.check(checker),
+            None => DataType::Void,
+        };
+        checker.exit_scope();
+        if diverges { DataType::Never } else { result_type }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::TokenType, test_token, parser::expressions::Parseable};
+
+    #[test]
+    fn parse_block_with_result() {
+        let tokens = vec![
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Separator(';')),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("+".to_string())),
+            test_token!(TokenType::Number("1".to_string())),
+            test_token!(TokenType::Brace('}')),
+        ];
+        let mut pos = 0;
+        let expr = super::BlockExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "{\n\t VarCall x\n\t Result ADD VarCall x, I32(1) \n}");
+        assert_eq!(pos, 7);
+    }
+
+    #[test]
+    fn parse_block_without_semicolon() {
+        let tokens = vec![
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Identifier("y".to_string())),
+            test_token!(TokenType::Brace('}')),
+        ];
+        let mut pos = 0;
+        assert!(super::BlockExpr::parse(&tokens, &mut pos).is_err());
+    }
 }
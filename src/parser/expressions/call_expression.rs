@@ -0,0 +1,200 @@
+use inkwell::{types::AnyType, values::{AnyValue, BasicMetadataValueEnum}};
+use colored::*;
+
+use crate::{compiler::checker::{TypeChecker, compatible}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{Parseable, ASTExpr, data_types::{DataType, ToBasic}, value_expression::ValueExpr, scope::ScopeManager};
+
+pub struct CallExpr {
+    token: Token,
+    name: String,
+    arguments: Vec<Box<dyn ASTExpr>>,
+}
+
+impl Parseable for CallExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with a function name
+        let token = tokens[*pos].clone();
+        let name = match token.token_type {
+            TokenType::Identifier(ref s) => s.clone(),
+            _ => return Err(error!(ErrorKind::ParserError, "Error while parsing function call",
+                                   ErrorTokenComponent::new("Expected function name".to_string(), Some(token.clone()))))
+        };
+
+        // Should be followed by a parenthesis
+        *pos += 1;
+        if tokens[*pos].token_type != TokenType::Paren('(') {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing function call",
+                              ErrorTokenComponent::new("Expected '('".to_string(), Some(tokens[*pos].clone()))));
+        }
+
+        // Should be followed by a list of arguments
+        let arguments = parse_arguments(tokens, pos);
+
+        Ok(Box::new(CallExpr {
+            token,
+            name,
+            arguments,
+        }))
+    }
+}
+
+impl ASTExpr for CallExpr {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &inkwell::builder::Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
+        // Find function
+        let function = match scope_manager.scope.functions.get(&self.name) {
+            Some(function) => function.value,
+            None => error!(ErrorKind::CompilerError, "Function does not exist",
+                           ErrorTokenComponent::new(format!("Function '{}' does not exist", &self.name.green()), Some(self.token.clone()))).panic()
+        };
+
+        // Check arguments against the signature
+        let params = function.get_type().get_param_types();
+        if params.len() != self.arguments.len() {
+            error!(ErrorKind::CompilerError, "Wrong number of arguments",
+                   ErrorTokenComponent::new(format!("Function '{}' takes {} arguments, but {} were given", &self.name.green(), params.len(), self.arguments.len()), Some(self.token.clone()))).panic()
+        }
+        let mut arguments: Vec<BasicMetadataValueEnum<'a>> = Vec::new();
+        for (i, (argument, param)) in self.arguments.iter().zip(params.iter()).enumerate() {
+            let value = match argument.generate(context, module, builder, scope_manager) {
+                Some(value) => value,
+                None => error!(ErrorKind::CompilerError, "Expected value for function argument",
+                               ErrorTokenComponent::new(format!("Argument {} of '{}' has no value", i + 1, &self.name.green()), Some(self.token.clone()))).panic()
+            };
+            if value.get_type() != param.as_any_type_enum() {
+                error!(ErrorKind::CompilerError, "Function argument types do not match",
+                       ErrorTokenComponent::new(format!("Argument {} of '{}' has wrong type", i + 1, &self.name.green()), Some(self.token.clone()))
+                       NameErrorComponent::new(format!("Expected: {}", param))
+                       NameErrorComponent::new(format!("Found: {}", value.get_type()))).panic()
+            }
+            arguments.push(value.to_basic().into());
+        }
+
+        // Void values can not be named
+        let name = if function.get_type().get_return_type().is_some() { "calltmp" } else { "" };
+        let call = builder.build_call(function, &arguments, name);
+        call.try_as_basic_value().left().map(|value| value.as_any_value_enum())
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        match scope_manager.scope.functions.get(&self.name) {
+            Some(function) => function.return_type.clone(),
+            None => DataType::Unknown,
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let arguments: Vec<DataType> = self.arguments.iter().map(|argument| argument.check(checker)).collect();
+        let function = match checker.function(&self.name) {
+            Some(function) => function,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Function does not exist",
+                                     ErrorTokenComponent::new(format!("Function '{}' does not exist", &self.name.green()), Some(self.token.clone()))));
+                return DataType::Unknown;
+            }
+        };
+
+        check_arguments(checker, &self.token, &self.name, &function.arguments, &arguments);
+        function.return_type
+    }
+
+    fn to_string(&self) -> String {
+        format!("Call {}({})", self.name, self.arguments.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(", "))
+    }
+}
+
+/// Values in parentheses separated by commas, `pos` has to be on the opening parenthesis
+pub fn parse_arguments(tokens: &Vec<Token>, pos: &mut usize) -> Vec<Box<dyn ASTExpr>> {
+    *pos += 1;
+    let mut arguments: Vec<Box<dyn ASTExpr>> = Vec::new();
+    while tokens[*pos].token_type != TokenType::Paren(')') {
+        match ValueExpr::parse(tokens, pos) {
+            Ok(argument) => arguments.push(argument),
+            Err(_) => error!(ErrorKind::ParserError, "Error while parsing function call arguments",
+                             ErrorTokenComponent::new("Expected argument".to_string(), Some(tokens[*pos].clone()))).panic()
+        }
+        match tokens[*pos].token_type {
+            TokenType::Separator(',') => *pos += 1,
+            TokenType::Paren(')') => break,
+            _ => error!(ErrorKind::ParserError, "Error while parsing function call arguments",
+                        ErrorTokenComponent::new("Expected ',' or ')'".to_string(), Some(tokens[*pos].clone()))).panic()
+        }
+    }
+    *pos += 1;
+    arguments
+}
+
+/// Checks types of the arguments against the signature
+pub fn check_arguments(checker: &mut TypeChecker, token: &Token, name: &str, expected: &[DataType], found: &[DataType]) {
+    if expected.len() != found.len() {
+        checker.error(error!(ErrorKind::TypeError, "Wrong number of arguments",
+                             ErrorTokenComponent::new(format!("Function '{}' takes {} arguments, but {} were given", name.green(), expected.len(), found.len()), Some(token.clone()))));
+    }
+    for (i, (found, expected)) in found.iter().zip(expected.iter()).enumerate() {
+        if !compatible(expected, found) {
+            checker.error(error!(ErrorKind::TypeError, "Function argument types do not match",
+                                 ErrorTokenComponent::new(format!("Argument {} of '{}' has wrong type", i + 1, name.green()), Some(token.clone()))
+                                 NameErrorComponent::new(format!("Expected: {}", expected))
+                                 NameErrorComponent::new(format!("Found: {}", found))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::TokenType, test_token, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::compiler::run_source;
+
+    #[test]
+    fn parse_call_without_args() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("test".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = super::CallExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Call test()");
+        assert_eq!(pos, 3);
+    }
+
+    #[test]
+    fn parse_call_with_args() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("add".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Number("1".to_string())),
+            test_token!(TokenType::Separator(',')),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("*".to_string())),
+            test_token!(TokenType::Number("2".to_string())),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Call add(I32(1), MULTIPLY VarCall x, I32(2))");
+    }
+
+    #[test]
+    fn parse_call_in_math() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("one".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Operator("+".to_string())),
+            test_token!(TokenType::Number("2".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "ADD Call one(), I32(2)");
+    }
+
+    #[test]
+    fn run_calls() {
+        assert_eq!(run_source("fun main(): i32 { add(40, 2) } fun add(a: i32, b: i32): i32 { a + b }"), 42);
+        assert_eq!(run_source("fun one(): i32 1; fun main(): i32 { one() + one() }"), 2);
+    }
+}
@@ -0,0 +1,124 @@
+use inkwell::{IntPredicate, builder::Builder, context::Context, module::Module, types::AnyTypeEnum, values::AnyValueEnum};
+
+use crate::{compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::Token, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, data_types::DataType, scope::ScopeManager, string_expression::build_int_to_string};
+
+pub struct CastExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+    data_type: DataType,
+}
+
+impl CastExpr {
+    pub fn new(token: Token, value: Box<dyn ASTExpr>, data_type: DataType) -> CastExpr {
+        CastExpr { token, value, data_type }
+    }
+}
+
+impl ASTExpr for CastExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let value = match self.value.generate(context, module, builder, scope_manager) {
+            Some(value) => value,
+            None => error!(ErrorKind::CompilerError, "Expected value for cast",
+                           ErrorTokenComponent::new("Nothing to cast".to_string(), Some(self.token.clone()))).panic()
+        };
+        // Unsigned values are extended with zeros, signed ones with their sign
+        let unsigned = self.value.data_type(scope_manager).is_unsigned();
+        if let (AnyValueEnum::IntValue(value), DataType::String) = (value, &self.data_type) {
+            return Some(AnyValueEnum::StructValue(build_int_to_string(context, module, builder, value, unsigned)));
+        }
+        let target = match self.data_type {
+            DataType::Named(_) | DataType::Void | DataType::Unknown => AnyTypeEnum::VoidType(context.void_type()),
+            _ => self.data_type.into_llvm_type(context),
+        };
+
+        match (value, target) {
+            (AnyValueEnum::IntValue(value), AnyTypeEnum::IntType(target)) => {
+                let from = value.get_type().get_bit_width();
+                let to = target.get_bit_width();
+                Some(AnyValueEnum::IntValue(if from == to {
+                    value
+                } else if to == 1 {
+                    // Integers are true when they are not zero
+                    builder.build_int_compare(IntPredicate::NE, value, value.get_type().const_zero(), "casttmp")
+                } else if from == 1 || (unsigned && from < to) {
+                    builder.build_int_z_extend(value, target, "casttmp")
+                } else if from < to {
+                    builder.build_int_s_extend(value, target, "casttmp")
+                } else {
+                    builder.build_int_truncate(value, target, "casttmp")
+                }))
+            }
+            (AnyValueEnum::IntValue(value), AnyTypeEnum::FloatType(target)) => {
+                Some(AnyValueEnum::FloatValue(if unsigned || value.get_type().get_bit_width() == 1 {
+                    builder.build_unsigned_int_to_float(value, target, "casttmp")
+                } else {
+                    builder.build_signed_int_to_float(value, target, "casttmp")
+                }))
+            }
+            (AnyValueEnum::FloatValue(value), AnyTypeEnum::IntType(target)) => {
+                Some(AnyValueEnum::IntValue(if self.data_type.is_unsigned() || target.get_bit_width() == 1 {
+                    builder.build_float_to_unsigned_int(value, target, "casttmp")
+                } else {
+                    builder.build_float_to_signed_int(value, target, "casttmp")
+                }))
+            }
+            (AnyValueEnum::FloatValue(value), AnyTypeEnum::FloatType(target)) => {
+                Some(AnyValueEnum::FloatValue(builder.build_float_cast(value, target, "casttmp")))
+            }
+            (value, _) => error!(ErrorKind::CompilerError, "Invalid cast",
+                                 ErrorTokenComponent::new(format!("Can not cast to {}", self.data_type), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("Found: {}", value.get_type()))).panic()
+        }
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        self.data_type.clone()
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        // Only numbers, bools and chars can be converted into each other, integers can also be written as strings
+        let castable = |data_type: &DataType| data_type.is_integer() || data_type.is_float() || matches!(data_type, DataType::Bool | DataType::Char);
+        let found = self.value.check(checker);
+        let valid = match self.data_type {
+            DataType::String => found == DataType::Unknown || found.is_integer(),
+            ref data_type => (found == DataType::Unknown || castable(&found)) && castable(data_type),
+        };
+        if !valid {
+            checker.error(error!(ErrorKind::TypeError, "Invalid cast",
+                                 ErrorTokenComponent::new(format!("Can not cast to {}", self.data_type), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("Found: {}", found))));
+        }
+        self.data_type.clone()
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        self.value.evaluate(checker)?.cast(&self.data_type)
+    }
+
+    fn to_string(&self) -> String {
+        format!("Cast {} as {:?}", self.value.to_string(), self.data_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::TokenType, test_token, parser::expressions::{Parseable, value_expression::ValueExpr}};
+
+    #[test]
+    fn parse_chained_casts() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Identifier("as".to_string())),
+            test_token!(TokenType::Identifier("i8".to_string())),
+            test_token!(TokenType::Identifier("as".to_string())),
+            test_token!(TokenType::Identifier("f32".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Cast Cast VarCall x as I8 as F32");
+        assert_eq!(pos, 5);
+    }
+}
@@ -0,0 +1,360 @@
+use std::{collections::{HashMap, HashSet}, rc::Rc};
+
+use inkwell::{builder::Builder, context::Context, module::{Linkage, Module}, types::BasicTypeEnum, values::AnyValueEnum};
+
+use crate::{compiler::checker::{TypeChecker, ClassSignature, Field, Method, Signature, compatible}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, data_types::{DataType, ToBasic}, function_expression::FunctionExpr, value_expression::ValueExpr, enum_expression::{check_type_name, check_type_body}, scope::{ScopeManager, Class}};
+
+/// Class declaration, like `class Counter { var value: i32 = 0; public fun get(this: &this): i32 { this.value } }`
+pub struct ClassExpr {
+    token: Token,
+    name: String,
+    fields: Vec<ClassField>,
+    methods: Vec<ClassMethod>,
+    exported: bool,
+}
+
+struct ClassField {
+    token: Token,
+    name: String,
+    data_type: DataType,
+    // Value every instance starts with, zero if there is none
+    value: Option<Box<dyn ASTExpr>>,
+    public: bool,
+}
+
+struct ClassMethod {
+    name: String,
+    public: bool,
+    is_static: bool,
+    function: FunctionExpr,
+}
+
+impl Parseable for ClassExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Could start with "export" to make the class visible to other files
+        let exported = consume(tokens, pos, "export");
+
+        // Should start with keyword "class"
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("class".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing class",
+                              ErrorTokenComponent::new("Expected 'class' keyword".to_string(), Some(token))));
+        }
+
+        // Should be followed by a name and a brace
+        *pos += 1;
+        let name = expect_name(tokens, pos, "Expected class name")?;
+        expect(tokens, pos, TokenType::Brace('{'), "Expected '{'")?;
+
+        // Members can start with "public" and "static"
+        let mut fields = vec![];
+        let mut methods = vec![];
+        while tokens[*pos].token_type != TokenType::Brace('}') {
+            let public = consume(tokens, pos, "public");
+            let is_static = consume(tokens, pos, "static");
+            let member_token = tokens[*pos].clone();
+            match member_token.token_type {
+                TokenType::Identifier(ref keyword) if keyword == "var" => {
+                    if is_static {
+                        return Err(error!(ErrorKind::ParserError, "Error while parsing class",
+                                          ErrorTokenComponent::new("Static fields are not supported".to_string(), Some(member_token.clone()))));
+                    }
+                    *pos += 1;
+                    let field = expect_name(tokens, pos, "Expected field name")?;
+                    expect(tokens, pos, TokenType::Separator(':'), "Expected ':' and field type")?;
+                    let data_type = match DataType::parse_type(tokens, pos) {
+                        Ok(data_type) => data_type,
+                        Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing class",
+                                                    ErrorTokenComponent::new("Expected field type".to_string(), Some(tokens[*pos].clone())))),
+                    };
+                    let mut value = None;
+                    if tokens[*pos].token_type == TokenType::Operator("=".to_string()) {
+                        *pos += 1;
+                        let start = *pos;
+                        match ValueExpr::parse(tokens, pos) {
+                            Ok(default) => value = Some(default),
+                            Err(err) if *pos > start => return Err(err),
+                            Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing class",
+                                                        ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone())))),
+                        }
+                    }
+                    expect(tokens, pos, TokenType::Separator(';'), "Expected ';'")?;
+                    fields.push(ClassField { token: member_token, name: field, data_type, value, public });
+                }
+                TokenType::Identifier(ref keyword) if keyword == "fun" => {
+                    // Method starts with "fun", so the error of the function is the one to report
+                    let function = FunctionExpr::parse_function(tokens, pos)?;
+                    let method = function.name().to_string();
+                    methods.push(ClassMethod { name: method, public, is_static, function: function.into_method(&name) });
+                }
+                _ => return Err(error!(ErrorKind::ParserError, "Error while parsing class",
+                                       ErrorTokenComponent::new("Expected field or method".to_string(), Some(member_token.clone())))),
+            }
+        }
+        *pos += 1;
+
+        Ok(Box::new(ClassExpr {
+            token,
+            name,
+            fields,
+            methods,
+            exported,
+        }))
+    }
+}
+
+impl ClassExpr {
+    /// Types of the members, arguments of methods do not include `this`
+    fn signature(&self) -> ClassSignature {
+        let fields = self.fields.iter().map(|field| Field {
+            name: field.name.clone(),
+            data_type: field.data_type.clone(),
+            public: field.public,
+        }).collect();
+        let methods = self.methods.iter().map(|method| {
+            let arguments = method.function.arguments();
+            let this = arguments.first().map_or(false, |arg| arg.0 == "this" && matches!(arg.1, DataType::Reference(_)));
+            let signature = Signature {
+                arguments: arguments.iter().skip(this as usize).map(|arg| arg.1.clone()).collect(),
+                return_type: method.function.return_type().clone(),
+            };
+            (method.name.clone(), Method { signature, public: method.public, is_static: method.is_static, this })
+        }).collect::<HashMap<String, Method>>();
+        ClassSignature { fields, methods }
+    }
+}
+
+impl ASTExpr for ClassExpr {
+    fn declare_types<'a, 'b>(&self, context: &'a Context, scope_manager: &'b mut ScopeManager<'a>) {
+        check_type_name(context, &self.name, &self.token).unwrap_or_else(|err| err.panic());
+        // Fields that can not be stored are reported by the checker, until then the body stays opaque
+        let struct_type = context.opaque_struct_type(&self.name);
+        let types = self.fields.iter().map(|field| field.data_type.clone()).collect::<Vec<DataType>>();
+        if types.iter().all(|data_type| !matches!(data_type, DataType::Void | DataType::Never) && scope_manager.scope.stores(data_type)) {
+            struct_type.set_body(&types.iter().map(|data_type| data_type.into_basic_type(context)).collect::<Vec<BasicTypeEnum<'a>>>(), false);
+        }
+        scope_manager.scope.classes.insert(self.name.clone(), Rc::new(Class { struct_type, signature: self.signature(), exported: self.exported }));
+    }
+
+    fn declare<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, scope_manager: &'b mut ScopeManager<'a>) {
+        if !scope_manager.scope.classes.contains_key(&self.name) {
+            self.declare_types(context, scope_manager);
+        }
+        let struct_type = scope_manager.scope.classes.get(&self.name).unwrap().struct_type;
+        let init = module.add_function(&init_name(&self.name), struct_type.fn_type(&[], false), None);
+        if !self.exported {
+            init.set_linkage(Linkage::Internal);
+        }
+        for method in self.methods.iter() {
+            method.function.declare(context, module, scope_manager);
+            // Files that import the class call its public methods
+            if let Some(function) = scope_manager.scope.functions.get(method.function.name()).filter(|_| self.exported && method.public) {
+                function.value.set_linkage(Linkage::External);
+            }
+        }
+    }
+
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        // Use the declaration if the class was declared before
+        let init = match module.get_function(&init_name(&self.name)) {
+            Some(init) => init,
+            None => {
+                self.declare(context, module, scope_manager);
+                module.get_function(&init_name(&self.name)).unwrap()
+            }
+        };
+
+        // Values of the fields are set by a function, so every instance starts the same
+        let struct_type = scope_manager.scope.classes.get(&self.name).unwrap().struct_type;
+        check_type_body(struct_type, &self.name, &self.token);
+        builder.position_at_end(context.append_basic_block(init, "entry"));
+        scope_manager.create_scope();
+        scope_manager.scope.function = Some(Rc::new(init));
+        let mut instance = struct_type.const_zero();
+        for (i, field) in self.fields.iter().enumerate() {
+            if let Some(ref value) = field.value {
+                let value = match value.generate(context, module, builder, scope_manager) {
+                    Some(value) => value.to_basic(),
+                    None => error!(ErrorKind::CompilerError, "Expected value for field",
+                                   ErrorTokenComponent::new(format!("Field {} has no value", field.name), Some(field.token.clone()))).panic()
+                };
+                instance = builder.build_insert_value(instance, value, i as u32, &field.name).unwrap().into_struct_value();
+            }
+        }
+        builder.build_return(Some(&instance));
+        scope_manager.exit_scope();
+
+        for method in self.methods.iter() {
+            method.function.generate(context, module, builder, scope_manager);
+        }
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        // Declared first, so methods can use each other
+        checker.declare_class(&self.name, self.signature());
+        let mut names = HashSet::new();
+        for field in self.fields.iter() {
+            if !names.insert(field.name.as_str()) {
+                checker.error(error!(ErrorKind::TypeError, "Member is defined more than once",
+                                     ErrorTokenComponent::new(format!("Member {} of {} already exists", field.name, self.name), Some(field.token.clone()))));
+            }
+            checker.check_type(&field.data_type, &field.token);
+            if matches!(field.data_type, DataType::Void | DataType::Never) {
+                checker.error(error!(ErrorKind::TypeError, "Fields have to be values",
+                                     ErrorTokenComponent::new(format!("Field {} of {} can not be stored", field.name, self.name), Some(field.token.clone()))
+                                     NameErrorComponent::new(format!("Found: {}", field.data_type))));
+            }
+            if let Some(ref value) = field.value {
+                let found = value.check(checker);
+                if !compatible(&field.data_type, &found) {
+                    checker.error(error!(ErrorKind::TypeError, "Field types do not match",
+                                         ErrorTokenComponent::new(format!("Field {} of {} has a different type", field.name, self.name), Some(field.token.clone()))
+                                         NameErrorComponent::new(format!("Expected: {}", field.data_type))
+                                         NameErrorComponent::new(format!("Found: {}", found))));
+                }
+            }
+        }
+
+        let signature = self.signature();
+        for method in self.methods.iter() {
+            let token = method.function.token();
+            if !names.insert(method.name.as_str()) {
+                checker.error(error!(ErrorKind::TypeError, "Member is defined more than once",
+                                     ErrorTokenComponent::new(format!("Member {} of {} already exists", method.name, self.name), Some(token.clone()))));
+            }
+            let declared = &signature.methods[&method.name];
+            if !method.is_static && !declared.this {
+                checker.error(error!(ErrorKind::TypeError, "Method needs 'this' as its first argument",
+                                     ErrorTokenComponent::new(format!("Add 'this: &this' or make {}::{} static", self.name, method.name), Some(token.clone()))));
+            }
+            // Static methods with `this` create the instance, like `new`
+            let return_type = &declared.signature.return_type;
+            if method.is_static && declared.this && *return_type != DataType::Void && *return_type != DataType::Named(self.name.clone()) {
+                checker.error(error!(ErrorKind::TypeError, "Constructor has to return void or the class",
+                                     ErrorTokenComponent::new(format!("{}::{} creates a new instance", self.name, method.name), Some(token.clone()))
+                                     NameErrorComponent::new(format!("Found: {}", return_type))));
+            }
+            // Destructor only gets the instance that is removed
+            if method.name == "drop" && (!declared.this || !declared.signature.arguments.is_empty() || *return_type != DataType::Void) {
+                checker.error(error!(ErrorKind::TypeError, "Destructor has to take only 'this' and return void",
+                                     ErrorTokenComponent::new(format!("{}::drop is the destructor of {}", self.name, self.name), Some(token.clone()))));
+            }
+        }
+
+        let saved = checker.enter_class(&self.name);
+        for method in self.methods.iter() {
+            method.function.check(checker);
+        }
+        checker.exit_class(saved);
+        DataType::Void
+    }
+
+    fn to_string(&self) -> String {
+        let fields = self.fields.iter().map(|field| {
+            let public = if field.public { "Public " } else { "" };
+            let value = field.value.as_ref().map(|value| format!(" = {}", value.to_string())).unwrap_or_default();
+            format!("{}Field {}: {:?}{}", public, field.name, field.data_type, value)
+        });
+        let methods = self.methods.iter().map(|method| {
+            let public = if method.public { "Public " } else { "" };
+            let is_static = if method.is_static { "Static " } else { "" };
+            format!("{}{}{}", public, is_static, method.function.to_string())
+        });
+        let export = if self.exported { "Export " } else { "" };
+        format!("{}Class {} {{ {} }}", export, self.name, fields.chain(methods).collect::<Vec<String>>().join(", "))
+    }
+}
+
+/// Function that returns an instance with the default values of the fields
+pub fn init_name(class: &str) -> String {
+    format!("{}.<init>", class)
+}
+
+fn consume(tokens: &Vec<Token>, pos: &mut usize, keyword: &str) -> bool {
+    let found = tokens[*pos].token_type == TokenType::Identifier(keyword.to_string());
+    if found {
+        *pos += 1;
+    }
+    found
+}
+
+fn expect_name(tokens: &Vec<Token>, pos: &mut usize, message: &str) -> Result<String, Error> {
+    match tokens[*pos].token_type {
+        TokenType::Identifier(ref name) => {
+            *pos += 1;
+            Ok(name.clone())
+        }
+        _ => Err(error!(ErrorKind::ParserError, "Error while parsing class",
+                        ErrorTokenComponent::new(message.to_string(), Some(tokens[*pos].clone())))),
+    }
+}
+
+fn expect(tokens: &Vec<Token>, pos: &mut usize, token_type: TokenType, message: &str) -> Result<(), Error> {
+    if tokens[*pos].token_type != token_type {
+        return Err(error!(ErrorKind::ParserError, "Error while parsing class",
+                          ErrorTokenComponent::new(message.to_string(), Some(tokens[*pos].clone()))));
+    }
+    *pos += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::Parseable};
+    use crate::compiler::{checker::check_source, compiler::run_source};
+
+    #[test]
+    fn parse_class() {
+        let tokens = tokenize("class Counter { public var value: i32 = 1; var step: u8; public static fun new(this: &this): void {} fun add(this: &this, n: i32): void {} }");
+        let mut pos = 0;
+        let expr = super::ClassExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Class Counter { Public Field value: I32 = I32(1), Field step: U8, \
+                                      Public Static Function Counter.new(this: Reference(Named(\"Counter\")), ) => Void {\n\t  \n}, \
+                                      Function Counter.add(this: Reference(Named(\"Counter\")), n: I32, ) => Void {\n\t  \n} }");
+        assert_eq!(pos, tokens.len() - 1);
+
+        let tokens = tokenize("export class Point { public var x: i32; }");
+        let mut pos = 0;
+        assert_eq!(super::ClassExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Export Class Point { Public Field x: I32 }");
+    }
+
+    const COUNTER: &str = "class Counter { public var value: i32 = 0; var step: i32 = 1; \
+                           public static fun new(this: &this, start: i32): void { this.value = start; } public static fun zero(): i32 { 0 } \
+                           public fun add(this: &this): void { this.value += this.step; } }";
+
+    #[test]
+    fn check_classes() {
+        let check = |source: &str| check_source(&format!("{} {}", COUNTER, source));
+        assert!(check("fun f(): i32 { var d = Counter::new(2); d.add(); d.value = 3; d.value + Counter::zero() }").is_empty());
+        assert_eq!(check_source("class A { var x: i32 = 1.5; }"), vec!["TypeError: Field types do not match"]);
+        assert_eq!(check_source("class A { var x: i32; var x: u8; }"), vec!["TypeError: Member is defined more than once"]);
+        assert_eq!(check_source("class A { var x: void; }"), vec!["TypeError: Fields have to be values"]);
+        assert_eq!(check_source("class A { public static fun new(this: &this): i32 { 1 } }"), vec!["TypeError: Constructor has to return void or the class"]);
+        assert_eq!(check_source("class A { fun get(): i32 { 1 } }"), vec!["TypeError: Method needs 'this' as its first argument"]);
+        assert!(check_source("class A { public static fun drop(this: &this): void {} }").is_empty());
+        assert_eq!(check_source("class A { fun drop(this: &this, x: i32): void {} }"), vec!["TypeError: Destructor has to take only 'this' and return void"]);
+        assert_eq!(check_source("class A { static fun drop(): i32 { 1 } }"), vec!["TypeError: Destructor has to take only 'this' and return void"]);
+        assert_eq!(check("fun f(): void { var d = Counter::new(2); d.value = 2.0; }"), vec!["TypeError: Assignment types do not match"]);
+        assert_eq!(check("fun f(): void { var d = Counter::new(2); d.step = 3; }"), vec!["TypeError: Member is private"]);
+        assert_eq!(check("fun f(c: Counter): void { c.value = 1; }"), vec!["TypeError: Can not assign to function argument"]);
+        assert_eq!(check("fun f(): void { Counter::add(); }"), vec!["TypeError: Method is not static"]);
+        assert_eq!(check("fun f(): void { var d = Counter::new(2); d.new(1); }"), vec!["TypeError: Method is static"]);
+        assert_eq!(check("fun f(): void { var d = Counter::new(2); d.size; }"), vec!["TypeError: Field does not exist"]);
+        assert_eq!(check("fun f(): void { var d = Counter::new(2); d.sub(); }"), vec!["TypeError: Method does not exist"]);
+        assert_eq!(check("fun f(n: i32): void { n.value; }"), vec!["TypeError: Value has no members"]);
+        assert_eq!(check("fun f(): void { Counter::new(1, 2); }"), vec!["TypeError: Wrong number of arguments"]);
+    }
+
+    #[test]
+    fn run_classes() {
+        let source = "class Counter { public var value: i32 = 10; var step: i32 = 1; \
+                      public static fun new(this: &this, step: i32): void { this.step = step; } \
+                      public static fun twice(n: i32): i32 { n * 2 } \
+                      public fun add(this: &this): i32 { this.value += this.step; this.value } } \
+                      fun main(): i32 { var c = Counter::new(5); c.add(); var d = c; d.add(); c.add() + d.value * 100 + Counter::twice(Counter::new(1).add()) * 10000 }";
+        assert_eq!(run_source(source), 222020);
+    }
+}
@@ -0,0 +1,184 @@
+use inkwell::{IntPredicate, FloatPredicate, values::AnyValueEnum};
+
+use crate::{error, compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::Token, utils::{error::{Error, ErrorKind}, error_components::{name_component::NameErrorComponent, token_component::ErrorTokenComponent}}};
+
+use super::{ASTExpr, data_types::DataType, scope::ScopeManager, string_expression::build_string_equal};
+
+#[derive(Debug, Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub enum CompareOperation {
+    EQUAL,
+    NOT_EQUAL,
+    LESS,
+    GREATER,
+    LESS_EQUAL,
+    GREATER_EQUAL,
+}
+
+pub struct CompareExpr {
+    token: Token,
+    lhs: Box<dyn ASTExpr>,
+    rhs: Box<dyn ASTExpr>,
+    operation: CompareOperation,
+}
+
+impl CompareOperation {
+    pub fn parse(op: &str) -> Option<CompareOperation> {
+        match op {
+            "==" => Some(CompareOperation::EQUAL),
+            "!=" => Some(CompareOperation::NOT_EQUAL),
+            "<" => Some(CompareOperation::LESS),
+            ">" => Some(CompareOperation::GREATER),
+            "<=" => Some(CompareOperation::LESS_EQUAL),
+            ">=" => Some(CompareOperation::GREATER_EQUAL),
+            _ => None,
+        }
+    }
+}
+
+impl CompareExpr {
+    pub fn new(token: Token, lhs: Box<dyn ASTExpr>, operation: CompareOperation, rhs: Box<dyn ASTExpr>) -> CompareExpr {
+        CompareExpr { token, lhs, rhs, operation }
+    }
+}
+
+impl ASTExpr for CompareExpr {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &inkwell::builder::Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let lhs = self.lhs.generate(context, module, builder, scope_manager);
+        if lhs.is_none() {
+            error!(ErrorKind::CompilerError, "Expected LHS for comparison").panic();
+        }
+        let rhs = self.rhs.generate(context, module, builder, scope_manager);
+        if rhs.is_none() {
+            error!(ErrorKind::CompilerError, "Expected RHS for comparison").panic();
+        }
+
+        let lhs = lhs.unwrap();
+        let rhs = rhs.unwrap();
+        if lhs.get_type() != rhs.get_type() {
+            error!(ErrorKind::CompilerError, "Comparison types do not match",
+                   NameErrorComponent::new(format!("LHS: {}", lhs.get_type()))
+                   NameErrorComponent::new(format!("RHS: {}", rhs.get_type()))).panic();
+        }
+        if lhs.is_int_value() {
+            let unsigned = self.lhs.data_type(scope_manager).is_unsigned();
+            let predicate = match self.operation {
+                CompareOperation::EQUAL => IntPredicate::EQ,
+                CompareOperation::NOT_EQUAL => IntPredicate::NE,
+                CompareOperation::LESS if unsigned => IntPredicate::ULT,
+                CompareOperation::LESS => IntPredicate::SLT,
+                CompareOperation::GREATER if unsigned => IntPredicate::UGT,
+                CompareOperation::GREATER => IntPredicate::SGT,
+                CompareOperation::LESS_EQUAL if unsigned => IntPredicate::ULE,
+                CompareOperation::LESS_EQUAL => IntPredicate::SLE,
+                CompareOperation::GREATER_EQUAL if unsigned => IntPredicate::UGE,
+                CompareOperation::GREATER_EQUAL => IntPredicate::SGE,
+            };
+            return Some(AnyValueEnum::IntValue(builder.build_int_compare(predicate, lhs.into_int_value(), rhs.into_int_value(), "icmptmp")));
+        } else if lhs.is_float_value() {
+            let predicate = match self.operation {
+                CompareOperation::EQUAL => FloatPredicate::OEQ,
+                CompareOperation::NOT_EQUAL => FloatPredicate::ONE,
+                CompareOperation::LESS => FloatPredicate::OLT,
+                CompareOperation::GREATER => FloatPredicate::OGT,
+                CompareOperation::LESS_EQUAL => FloatPredicate::OLE,
+                CompareOperation::GREATER_EQUAL => FloatPredicate::OGE,
+            };
+            return Some(AnyValueEnum::IntValue(builder.build_float_compare(predicate, lhs.into_float_value(), rhs.into_float_value(), "fcmptmp")));
+        } else if self.lhs.data_type(scope_manager) == DataType::String {
+            let equal = build_string_equal(context, module, builder, lhs.into_struct_value(), rhs.into_struct_value());
+            return Some(AnyValueEnum::IntValue(match self.operation {
+                CompareOperation::EQUAL => equal,
+                CompareOperation::NOT_EQUAL => builder.build_not(equal, "strne"),
+                _ => error!(ErrorKind::CompilerError, "Strings can only be compared for equality",
+                            ErrorTokenComponent::new(format!("Operation {:?} can not be used on str", self.operation), Some(self.token.clone()))).panic()
+            }));
+        }
+
+        error!(ErrorKind::CompilerError, "Values can not be compared",
+               NameErrorComponent::new(format!("Type: {}", lhs.get_type()))).panic()
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::Bool
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let lhs = self.lhs.check(checker);
+        let rhs = self.rhs.check(checker);
+        if lhs == DataType::Unknown || rhs == DataType::Unknown {
+            return DataType::Bool;
+        }
+        if lhs != rhs {
+            checker.error(error!(ErrorKind::TypeError, "Comparison types do not match",
+                                 ErrorTokenComponent::new(format!("Operands of {:?} have different types", self.operation), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("LHS: {}", lhs))
+                                 NameErrorComponent::new(format!("RHS: {}", rhs))));
+        } else if lhs == DataType::String {
+            if !matches!(self.operation, CompareOperation::EQUAL | CompareOperation::NOT_EQUAL) {
+                checker.error(error!(ErrorKind::TypeError, "Strings can only be compared for equality",
+                                     ErrorTokenComponent::new(format!("Operation {:?} can not be used on str", self.operation), Some(self.token.clone()))));
+            }
+        } else if lhs == DataType::Bool {
+            if !matches!(self.operation, CompareOperation::EQUAL | CompareOperation::NOT_EQUAL) {
+                checker.error(error!(ErrorKind::TypeError, "Bools can only be compared for equality",
+                                     ErrorTokenComponent::new(format!("Operation {:?} can not be used on bool", self.operation), Some(self.token.clone()))));
+            }
+        } else if !lhs.is_integer() && !lhs.is_float() && lhs != DataType::Char {
+            checker.error(error!(ErrorKind::TypeError, "Values can not be compared",
+                                 ErrorTokenComponent::new(format!("Operation {:?} can not be used on {}", self.operation, lhs), Some(self.token.clone()))));
+        }
+        DataType::Bool
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        Constant::compare(self.operation, &self.lhs.evaluate(checker)?, &self.rhs.evaluate(checker)?)
+    }
+
+    fn to_string(&self) -> String {
+        format!("{:?} {}, {}", self.operation, self.lhs.to_string(), self.rhs.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_token, lexer::lexer::TokenType, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::compiler::run_source;
+
+    #[test]
+    fn parse_less_i32() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("<".to_string())),
+            test_token!(TokenType::Number("3".to_string())),
+            test_token!(TokenType::Separator(';'))
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "LESS VarCall x, I32(3)");
+    }
+
+    #[test]
+    fn parse_comparison_of_math() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("+".to_string())),
+            test_token!(TokenType::Number("1".to_string())),
+            test_token!(TokenType::Operator(">=".to_string())),
+            test_token!(TokenType::Identifier("y".to_string())),
+            test_token!(TokenType::Operator("*".to_string())),
+            test_token!(TokenType::Number("2".to_string())),
+            test_token!(TokenType::Separator(';'))
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "GREATER_EQUAL ADD VarCall x, I32(1), MULTIPLY VarCall y, I32(2)");
+    }
+
+    #[test]
+    fn run_comparisons() {
+        assert_eq!(run_source("fun main(): i32 { if (2 + 3 == 5) { 1 } else { 0 } }"), 1);
+        assert_eq!(run_source("fun main(): i32 { if (1.5 >= 2.0) { 1 } else { 0 } }"), 0);
+        assert_eq!(run_source("fun max(a: i32, b: i32): i32 { if (a > b) { a } else { b } } fun main(): i32 { max(3, 7) }"), 7);
+    }
+}
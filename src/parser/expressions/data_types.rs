@@ -1,9 +1,13 @@
-use inkwell::{context::Context, types::{AnyTypeEnum, BasicTypeEnum, BasicMetadataTypeEnum, FunctionType}, values::{AnyValueEnum, BasicValueEnum}};
+use std::fmt;
 
-use crate::{lexer::lexer::{Token, TokenType}, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
+use inkwell::{AddressSpace, context::Context, types::{AnyTypeEnum, BasicType, BasicTypeEnum, BasicMetadataTypeEnum, FunctionType, IntType, StructType}, values::{AnyValueEnum, BasicValueEnum}};
 
+use crate::{lexer::lexer::{Token, TokenType}, utils::{error::Error, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
 
-#[derive(Debug, Clone)]
+use super::{array_expression::slice_type, string_expression::string_type, tuple_expression::tuple_type, range_expression::range_type};
+
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum DataType {
     I8,
     I16,
@@ -16,9 +20,24 @@ pub enum DataType {
     F32,
     F64,
     Bool,
+    // Unicode code point
+    Char,
     String,
+    // Fixed number of elements, stored by value
+    Array(Box<DataType>, u32),
+    // Pointer to elements and their number, like `i32[]`
+    Slice(Box<DataType>),
+    // Elements of different types, like `(i32, f64)`
+    Tuple(Vec<DataType>),
+    // Range of integers, like `range<i64>`
+    Range(Box<DataType>),
     Void,
-    Custom(Vec<DataType>, bool),
+    // Type of expressions that never produce a value, like `return`
+    Never,
+    // Type declared in the source, like an enum or a class
+    Named(String),
+    // Pointer to a value that is stored somewhere else, only used for `this` of methods
+    Reference(Box<DataType>),
     Unknown
 }
 impl DataType {
@@ -37,9 +56,11 @@ impl DataType {
                     "f32" => Ok(DataType::F32),
                     "f64" => Ok(DataType::F64),
                     "bool" => Ok(DataType::Bool),
-                    "string" => Ok(DataType::String),
+                    "char" => Ok(DataType::Char),
+                    "str" => Ok(DataType::String),
                     "void" => Ok(DataType::Void),
-                    _ => Ok(DataType::Custom(vec![], false)),
+                    // Enums and classes, the checker reports names that are not declared
+                    _ => Ok(DataType::Named(s.clone())),
                 }
             }
             _ => Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing data type",
@@ -47,29 +68,126 @@ impl DataType {
         }
     }
 
+    /// Parses the type with its array suffixes, like `i32[4][]` or `(i32, f64)`, `pos` is moved after the type
+    pub fn parse_type(tokens: &Vec<Token>, pos: &mut usize) -> Result<DataType, Error> {
+        let mut data_type = if tokens[*pos].token_type == TokenType::Paren('(') {
+            DataType::parse_tuple_type(tokens, pos)?
+        } else if tokens[*pos].token_type == TokenType::Identifier("range".to_string()) {
+            DataType::parse_range_type(tokens, pos)?
+        } else {
+            DataType::parse(&tokens[*pos])?
+        };
+        *pos += 1;
+        while tokens[*pos].token_type == TokenType::Bracket('[') {
+            *pos += 1;
+            data_type = match tokens[*pos].token_type {
+                TokenType::Bracket(']') => DataType::Slice(Box::new(data_type)),
+                TokenType::Number(ref s) => match s.parse::<u32>() {
+                    Ok(len) if tokens[*pos + 1].token_type == TokenType::Bracket(']') => {
+                        *pos += 1;
+                        DataType::Array(Box::new(data_type), len)
+                    }
+                    _ => return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing array type",
+                                           ErrorTokenComponent::new("Expected array length and ']'".to_string(), Some(tokens[*pos].clone())))),
+                },
+                _ => return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing array type",
+                                       ErrorTokenComponent::new("Expected array length or ']'".to_string(), Some(tokens[*pos].clone())))),
+            };
+            *pos += 1;
+        }
+        Ok(data_type)
+    }
+
+    /// Element types separated by commas, `pos` is left on the closing parenthesis
+    fn parse_tuple_type(tokens: &Vec<Token>, pos: &mut usize) -> Result<DataType, Error> {
+        let mut types = vec![];
+        *pos += 1;
+        loop {
+            types.push(DataType::parse_type(tokens, pos)?);
+            match tokens[*pos].token_type {
+                TokenType::Separator(',') if tokens[*pos + 1].token_type == TokenType::Paren(')') => {
+                    *pos += 1;
+                    break;
+                }
+                TokenType::Separator(',') => *pos += 1,
+                TokenType::Paren(')') => break,
+                _ => return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing tuple type",
+                                       ErrorTokenComponent::new("Expected ',' or ')'".to_string(), Some(tokens[*pos].clone())))),
+            }
+        }
+        Ok(DataType::Tuple(types))
+    }
+
+    /// Element type is written in angle brackets, `range` alone is a range of `i32`, `pos` is left on the last token
+    fn parse_range_type(tokens: &Vec<Token>, pos: &mut usize) -> Result<DataType, Error> {
+        if tokens[*pos + 1].token_type != TokenType::Operator("<".to_string()) {
+            return Ok(DataType::Range(Box::new(DataType::I32)));
+        }
+        *pos += 2;
+        let element = DataType::parse_type(tokens, pos)?;
+        if tokens[*pos].token_type != TokenType::Operator(">".to_string()) {
+            return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing range type",
+                              ErrorTokenComponent::new("Expected '>'".to_string(), Some(tokens[*pos].clone()))));
+        }
+        Ok(DataType::Range(Box::new(element)))
+    }
+
+    /// Signed type with the same width as the integer type
+    pub fn from_int_type(int_type: IntType) -> DataType {
+        match int_type.get_bit_width() {
+            1 => DataType::Bool,
+            8 => DataType::I8,
+            16 => DataType::I16,
+            32 => DataType::I32,
+            64 => DataType::I64,
+            _ => DataType::Unknown,
+        }
+    }
+
+    /// Names of the enums and classes that the type is made of
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            DataType::Named(name) => vec![name.as_str()],
+            DataType::Array(element, _) | DataType::Slice(element) | DataType::Range(element) | DataType::Reference(element) => element.names(),
+            DataType::Tuple(elements) => elements.iter().flat_map(|element| element.names()).collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn is_unsigned(&self) -> bool {
+        matches!(self, DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 | DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64)
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, DataType::F32 | DataType::F64)
+    }
+
     pub fn into_llvm_type<'a>(&self, context: &'a Context) -> AnyTypeEnum<'a> {
         match self {
             DataType::I8 => AnyTypeEnum::IntType(context.i8_type()),
             DataType::I16 => AnyTypeEnum::IntType(context.i16_type()),
             DataType::I32 => AnyTypeEnum::IntType(context.i32_type()),
             DataType::I64 => AnyTypeEnum::IntType(context.i64_type()),
-            DataType::U8 => todo!("u8"),
-            DataType::U16 => todo!("u16"),
-            DataType::U32 => todo!("u32"),
-            DataType::U64 => todo!("u64"),
+            DataType::U8 => AnyTypeEnum::IntType(context.i8_type()),
+            DataType::U16 => AnyTypeEnum::IntType(context.i16_type()),
+            DataType::U32 => AnyTypeEnum::IntType(context.i32_type()),
+            DataType::U64 => AnyTypeEnum::IntType(context.i64_type()),
             DataType::F32 => AnyTypeEnum::FloatType(context.f32_type()),
             DataType::F64 => AnyTypeEnum::FloatType(context.f64_type()),
             DataType::Bool => AnyTypeEnum::IntType(context.bool_type()),
-            DataType::String => AnyTypeEnum::PointerType(context.i8_type().ptr_type(inkwell::AddressSpace::Global)),
-            DataType::Void => AnyTypeEnum::VoidType(context.void_type()),
-            DataType::Custom(dt, v) => {
-                let mut types: Vec<BasicTypeEnum> = Vec::new();
-                for dt in dt {
-                    types.push(dt.into_basic_type(context));
-                }
-                let type_name = context.struct_type(&types, false);
-                AnyTypeEnum::StructType(type_name)
-            }
+            DataType::Char => AnyTypeEnum::IntType(context.i32_type()),
+            DataType::String => AnyTypeEnum::StructType(string_type(context)),
+            DataType::Array(element, len) => AnyTypeEnum::ArrayType(element.into_basic_type(context).array_type(*len)),
+            DataType::Slice(element) => AnyTypeEnum::StructType(slice_type(context, element.into_basic_type(context))),
+            DataType::Tuple(elements) => AnyTypeEnum::StructType(tuple_type(context, elements)),
+            DataType::Range(element) => AnyTypeEnum::StructType(range_type(context, element.into_basic_type(context))),
+            DataType::Named(name) => AnyTypeEnum::StructType(named_type(context, name)),
+            DataType::Reference(element) => AnyTypeEnum::PointerType(element.into_basic_type(context).ptr_type(AddressSpace::Generic)),
+            DataType::Void | DataType::Never => AnyTypeEnum::VoidType(context.void_type()),
             DataType::Unknown => AnyTypeEnum::VoidType(context.void_type()), // TODO: Replace this with a proper error message
         }
     }
@@ -80,22 +198,21 @@ impl DataType {
             DataType::I16 => BasicTypeEnum::IntType(context.i16_type()),
             DataType::I32 => BasicTypeEnum::IntType(context.i32_type()),
             DataType::I64 => BasicTypeEnum::IntType(context.i64_type()),
-            DataType::U8 => todo!("u8"),
-            DataType::U16 => todo!("u16"),
-            DataType::U32 => todo!("u32"),
-            DataType::U64 => todo!("u64"),
+            DataType::U8 => BasicTypeEnum::IntType(context.i8_type()),
+            DataType::U16 => BasicTypeEnum::IntType(context.i16_type()),
+            DataType::U32 => BasicTypeEnum::IntType(context.i32_type()),
+            DataType::U64 => BasicTypeEnum::IntType(context.i64_type()),
             DataType::F32 => BasicTypeEnum::FloatType(context.f32_type()),
             DataType::F64 => BasicTypeEnum::FloatType(context.f64_type()),
             DataType::Bool => BasicTypeEnum::IntType(context.bool_type()),
-            DataType::String => BasicTypeEnum::PointerType(context.i8_type().ptr_type(inkwell::AddressSpace::Global)),
-            DataType::Custom(dt, v) => {
-                let mut types: Vec<BasicTypeEnum> = Vec::new();
-                for dt in dt {
-                    types.push(dt.into_basic_type(context));
-                }
-                let type_name = context.struct_type(&types, false);
-                BasicTypeEnum::StructType(type_name)
-            }
+            DataType::Char => BasicTypeEnum::IntType(context.i32_type()),
+            DataType::String => BasicTypeEnum::StructType(string_type(context)),
+            DataType::Array(element, len) => BasicTypeEnum::ArrayType(element.into_basic_type(context).array_type(*len)),
+            DataType::Slice(element) => BasicTypeEnum::StructType(slice_type(context, element.into_basic_type(context))),
+            DataType::Tuple(elements) => BasicTypeEnum::StructType(tuple_type(context, elements)),
+            DataType::Range(element) => BasicTypeEnum::StructType(range_type(context, element.into_basic_type(context))),
+            DataType::Named(name) => BasicTypeEnum::StructType(named_type(context, name)),
+            DataType::Reference(element) => BasicTypeEnum::PointerType(element.into_basic_type(context).ptr_type(AddressSpace::Generic)),
             _ => panic!("Type {:?} cannot be converted into BasicType", self), // TODO: change this message to be more meaningful
         }
     }
@@ -106,22 +223,21 @@ impl DataType {
             DataType::I16 => BasicMetadataTypeEnum::IntType(context.i16_type()),
             DataType::I32 => BasicMetadataTypeEnum::IntType(context.i32_type()),
             DataType::I64 => BasicMetadataTypeEnum::IntType(context.i64_type()),
-            DataType::U8 => todo!("u8"),
-            DataType::U16 => todo!("u16"),
-            DataType::U32 => todo!("u32"),
-            DataType::U64 => todo!("u64"),
+            DataType::U8 => BasicMetadataTypeEnum::IntType(context.i8_type()),
+            DataType::U16 => BasicMetadataTypeEnum::IntType(context.i16_type()),
+            DataType::U32 => BasicMetadataTypeEnum::IntType(context.i32_type()),
+            DataType::U64 => BasicMetadataTypeEnum::IntType(context.i64_type()),
             DataType::F32 => BasicMetadataTypeEnum::FloatType(context.f32_type()),
             DataType::F64 => BasicMetadataTypeEnum::FloatType(context.f64_type()),
             DataType::Bool => BasicMetadataTypeEnum::IntType(context.bool_type()),
-            DataType::String => BasicMetadataTypeEnum::PointerType(context.i8_type().ptr_type(inkwell::AddressSpace::Global)),
-            DataType::Custom(dt, v) => {
-                let mut types: Vec<BasicTypeEnum> = Vec::new();
-                for dt in dt {
-                    types.push(dt.into_basic_type(context));
-                }
-                let type_name = context.struct_type(&types, false);
-                BasicMetadataTypeEnum::StructType(type_name)
-            }
+            DataType::Char => BasicMetadataTypeEnum::IntType(context.i32_type()),
+            DataType::String => BasicMetadataTypeEnum::StructType(string_type(context)),
+            DataType::Array(element, len) => BasicMetadataTypeEnum::ArrayType(element.into_basic_type(context).array_type(*len)),
+            DataType::Slice(element) => BasicMetadataTypeEnum::StructType(slice_type(context, element.into_basic_type(context))),
+            DataType::Tuple(elements) => BasicMetadataTypeEnum::StructType(tuple_type(context, elements)),
+            DataType::Range(element) => BasicMetadataTypeEnum::StructType(range_type(context, element.into_basic_type(context))),
+            DataType::Named(name) => BasicMetadataTypeEnum::StructType(named_type(context, name)),
+            DataType::Reference(element) => BasicMetadataTypeEnum::PointerType(element.into_basic_type(context).ptr_type(AddressSpace::Generic)),
             _ => panic!("Type {:?} cannot be converted into BasicType", self), // TODO: change this message to be more meaningful
         }
     }
@@ -137,6 +253,7 @@ impl DataType {
         match t {
             AnyTypeEnum::VoidType(t) => t.fn_type(types.as_slice(), isVarArgs),
             AnyTypeEnum::StructType(t) => t.fn_type(types.as_slice(), isVarArgs),
+            AnyTypeEnum::ArrayType(t) => t.fn_type(types.as_slice(), isVarArgs),
             AnyTypeEnum::IntType(t) => t.fn_type(types.as_slice(), isVarArgs),
             AnyTypeEnum::FloatType(t) => t.fn_type(types.as_slice(), isVarArgs),
             AnyTypeEnum::PointerType(t) => t.fn_type(types.as_slice(), isVarArgs),
@@ -146,34 +263,75 @@ impl DataType {
     }
 }
 
-pub trait ToBasic {
-    fn to_basic(&self) -> BasicValueEnum;
+/// Name of the type as it is written in the source, so unsigned types are not shown as LLVM integers
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataType::I8 => write!(f, "i8"),
+            DataType::I16 => write!(f, "i16"),
+            DataType::I32 => write!(f, "i32"),
+            DataType::I64 => write!(f, "i64"),
+            DataType::U8 => write!(f, "u8"),
+            DataType::U16 => write!(f, "u16"),
+            DataType::U32 => write!(f, "u32"),
+            DataType::U64 => write!(f, "u64"),
+            DataType::F32 => write!(f, "f32"),
+            DataType::F64 => write!(f, "f64"),
+            DataType::Bool => write!(f, "bool"),
+            DataType::Char => write!(f, "char"),
+            DataType::String => write!(f, "str"),
+            DataType::Array(element, len) => write!(f, "{}[{}]", element, len),
+            DataType::Slice(element) => write!(f, "{}[]", element),
+            DataType::Range(element) => write!(f, "range<{}>", element),
+            DataType::Tuple(elements) => write!(f, "({})", elements.iter().map(|element| element.to_string()).collect::<Vec<String>>().join(", ")),
+            DataType::Void => write!(f, "void"),
+            DataType::Never => write!(f, "never"),
+            DataType::Named(name) => write!(f, "{}", name),
+            DataType::Reference(element) => write!(f, "&{}", element),
+            DataType::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Named types are created before any function is declared
+fn named_type<'a>(context: &'a Context, name: &str) -> StructType<'a> {
+    match context.get_struct_type(name) {
+        Some(struct_type) => struct_type,
+        None => error!(crate::utils::error::ErrorKind::CompilerError, "Type does not exist",
+                       NameErrorComponent::new(format!("Type {} was not declared", name))).panic()
+    }
+}
+
+pub trait ToBasic<'ctx> {
+    fn to_basic(&self) -> BasicValueEnum<'ctx>;
 }
 
-impl<'ctx> ToBasic for AnyValueEnum<'ctx> {
-    fn to_basic(&self) -> BasicValueEnum {
+impl<'ctx> ToBasic<'ctx> for AnyValueEnum<'ctx> {
+    fn to_basic(&self) -> BasicValueEnum<'ctx> {
         match self {
             AnyValueEnum::IntValue(v) => BasicValueEnum::IntValue(*v),
             AnyValueEnum::FloatValue(v) => BasicValueEnum::FloatValue(*v),
             AnyValueEnum::PointerValue(v) => BasicValueEnum::PointerValue(*v),
             AnyValueEnum::StructValue(v) => BasicValueEnum::StructValue(*v),
+            AnyValueEnum::ArrayValue(v) => BasicValueEnum::ArrayValue(*v),
             AnyValueEnum::VectorValue(v) => BasicValueEnum::VectorValue(*v),
             _ => panic!("Cannot convert {:?} to BasicValue", self) // TODO: Make this more meaningful
         }
     }
 }
 
-pub trait ToAny {
-    fn to_any(&self) -> AnyValueEnum;
+pub trait ToAny<'ctx> {
+    fn to_any(&self) -> AnyValueEnum<'ctx>;
 }
 
-impl<'ctx> ToAny for BasicValueEnum<'ctx> {
-    fn to_any(&self) -> AnyValueEnum {
+impl<'ctx> ToAny<'ctx> for BasicValueEnum<'ctx> {
+    fn to_any(&self) -> AnyValueEnum<'ctx> {
         match self {
             BasicValueEnum::IntValue(v) => AnyValueEnum::IntValue(v.clone()),
             BasicValueEnum::FloatValue(v) => AnyValueEnum::FloatValue(v.clone()),
             BasicValueEnum::PointerValue(v) => AnyValueEnum::PointerValue(v.clone()),
             BasicValueEnum::StructValue(v) => AnyValueEnum::StructValue(v.clone()),
+            BasicValueEnum::ArrayValue(v) => AnyValueEnum::ArrayValue(v.clone()),
             BasicValueEnum::VectorValue(v) => AnyValueEnum::VectorValue(v.clone()),
             _ => panic!("Cannot convert {:?} to AnyValue", self) // TODO: Make this more meaningful
         }
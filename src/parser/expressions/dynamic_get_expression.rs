@@ -0,0 +1,197 @@
+use std::rc::Rc;
+
+use inkwell::{builder::Builder, context::Context, module::Module, values::{AnyValue, AnyValueEnum, BasicMetadataValueEnum, PointerValue}};
+
+use crate::{compiler::checker::TypeChecker, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, data_types::{DataType, ToBasic}, call_expression::{parse_arguments, check_arguments}, variable_definition_expression::build_entry_alloca, scope::{ScopeManager, Class}};
+
+/// Field or method of a class instance, like `counter.value` or `counter.add(1)`
+pub struct DynamicGetExpr {
+    token: Token,
+    target: Box<dyn ASTExpr>,
+    member: String,
+    // Arguments of a method call, fields do not have any
+    arguments: Option<Vec<Box<dyn ASTExpr>>>,
+}
+
+impl DynamicGetExpr {
+    pub fn new(token: Token, target: Box<dyn ASTExpr>, member: String, arguments: Option<Vec<Box<dyn ASTExpr>>>) -> DynamicGetExpr {
+        DynamicGetExpr { token, target, member, arguments }
+    }
+
+    /// Parses `.field` or `.method(args)` after the target, `pos` has to be on the dot
+    pub fn parse_member(tokens: &Vec<Token>, pos: &mut usize, target: Box<dyn ASTExpr>) -> Box<dyn ASTExpr> {
+        let token = tokens[*pos].clone();
+        *pos += 1;
+        let member = match tokens[*pos].token_type {
+            TokenType::Identifier(ref member) => member.clone(),
+            _ => error!(ErrorKind::ParserError, "Error while parsing dynamic get",
+                        ErrorTokenComponent::new("Expected member name after '.'".to_string(), Some(tokens[*pos].clone()))).panic()
+        };
+        *pos += 1;
+        let arguments = if tokens[*pos].token_type == TokenType::Paren('(') { Some(parse_arguments(tokens, pos)) } else { None };
+        Box::new(DynamicGetExpr::new(token, target, member, arguments))
+    }
+
+    fn class<'a>(&self, scope_manager: &ScopeManager<'a>) -> (String, Rc<Class<'a>>) {
+        let class = class_name(&self.target.data_type(scope_manager)).and_then(|name| {
+            scope_manager.scope.classes.get(&name).map(|class| (name, class.clone()))
+        });
+        match class {
+            Some(class) => class,
+            None => error!(ErrorKind::CompilerError, "Value has no members",
+                           ErrorTokenComponent::new("Only class instances have fields and methods".to_string(), Some(self.token.clone()))).panic()
+        }
+    }
+
+    /// Address of the instance, values that are not stored anywhere are copied into memory
+    fn target_pointer<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>, class: &Class<'a>) -> PointerValue<'a> {
+        if let Some(pointer) = self.target.pointer(context, module, builder, scope_manager) {
+            return pointer;
+        }
+        match self.target.generate(context, module, builder, scope_manager) {
+            Some(AnyValueEnum::PointerValue(pointer)) => pointer,
+            Some(AnyValueEnum::StructValue(value)) => {
+                let memory = build_entry_alloca(context, builder, class.struct_type.into(), "instancetmp");
+                builder.build_store(memory, value);
+                memory
+            }
+            _ => error!(ErrorKind::CompilerError, "Value has no members",
+                        ErrorTokenComponent::new("Only class instances have fields and methods".to_string(), Some(self.token.clone()))).panic()
+        }
+    }
+}
+
+impl ASTExpr for DynamicGetExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let arguments = match self.arguments {
+            Some(ref arguments) => arguments,
+            None => {
+                let pointer = self.pointer(context, module, builder, scope_manager).unwrap();
+                return Some(builder.build_load(pointer, &self.member).as_any_value_enum());
+            }
+        };
+
+        // Methods get the instance as their first argument
+        let (name, class) = self.class(scope_manager);
+        let function = match scope_manager.scope.functions.get(&format!("{}.{}", name, self.member)) {
+            Some(function) => function.value,
+            None => error!(ErrorKind::CompilerError, "Method does not exist",
+                           ErrorTokenComponent::new(format!("{} has no method {}", name, self.member), Some(self.token.clone()))).panic()
+        };
+        let mut values: Vec<BasicMetadataValueEnum<'a>> = vec![self.target_pointer(context, module, builder, scope_manager, &class).into()];
+        for (i, argument) in arguments.iter().enumerate() {
+            match argument.generate(context, module, builder, scope_manager) {
+                Some(value) => values.push(value.to_basic().into()),
+                None => error!(ErrorKind::CompilerError, "Expected value for function argument",
+                               ErrorTokenComponent::new(format!("Argument {} of '{}' has no value", i + 1, self.member), Some(self.token.clone()))).panic()
+            }
+        }
+        let call_name = if function.get_type().get_return_type().is_some() { "calltmp" } else { "" };
+        let call = builder.build_call(function, &values, call_name);
+        call.try_as_basic_value().left().map(|value| value.as_any_value_enum())
+    }
+
+    /// Fields are reached through the address of the instance
+    fn pointer<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<PointerValue<'a>> {
+        if self.arguments.is_some() {
+            return None;
+        }
+        let (name, class) = self.class(scope_manager);
+        let index = match class.signature.field(&self.member) {
+            Some((index, _)) => index,
+            None => error!(ErrorKind::CompilerError, "Field does not exist",
+                           ErrorTokenComponent::new(format!("{} has no field {}", name, self.member), Some(self.token.clone()))).panic()
+        };
+        let target = self.target_pointer(context, module, builder, scope_manager, &class);
+        Some(builder.build_struct_gep(target, index, &self.member).unwrap())
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        let class = match class_name(&self.target.data_type(scope_manager)).and_then(|name| scope_manager.scope.classes.get(&name)) {
+            Some(class) => class,
+            None => return DataType::Unknown,
+        };
+        let data_type = match self.arguments {
+            Some(_) => class.signature.methods.get(&self.member).map(|method| method.signature.return_type.clone()),
+            None => class.signature.field(&self.member).map(|(_, field)| field.data_type.clone()),
+        };
+        data_type.unwrap_or(DataType::Unknown)
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let target = self.target.check(checker);
+        let arguments = self.arguments.iter().flatten().map(|argument| argument.check(checker)).collect::<Vec<DataType>>();
+        let (name, class) = match class_name(&target).and_then(|name| checker.class(&name).map(|class| (name, class))) {
+            Some(class) => class,
+            None => {
+                if target != DataType::Unknown {
+                    checker.error(error!(ErrorKind::TypeError, "Value has no members",
+                                         ErrorTokenComponent::new("Only class instances have fields and methods".to_string(), Some(self.token.clone()))
+                                         NameErrorComponent::new(format!("Found: {}", target))));
+                }
+                return DataType::Unknown;
+            }
+        };
+
+        if self.arguments.is_none() {
+            return match class.field(&self.member) {
+                Some((_, field)) => {
+                    checker.check_visible(&name, &self.member, field.public, &self.token);
+                    field.data_type.clone()
+                }
+                None => {
+                    checker.error(error!(ErrorKind::TypeError, "Field does not exist",
+                                         ErrorTokenComponent::new(format!("{} has no field {}", name, self.member), Some(self.token.clone()))));
+                    DataType::Unknown
+                }
+            };
+        }
+        let method = match class.methods.get(&self.member) {
+            Some(method) => method,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Method does not exist",
+                                     ErrorTokenComponent::new(format!("{} has no method {}", name, self.member), Some(self.token.clone()))));
+                return DataType::Unknown;
+            }
+        };
+        checker.check_visible(&name, &self.member, method.public, &self.token);
+        if method.is_static {
+            checker.error(error!(ErrorKind::TypeError, "Method is static",
+                                 ErrorTokenComponent::new(format!("Use {}::{} to call it", name, self.member), Some(self.token.clone()))));
+        } else {
+            check_arguments(checker, &self.token, &format!("{}.{}", name, self.member), &method.signature.arguments, &arguments);
+        }
+        method.signature.return_type.clone()
+    }
+
+    fn to_string(&self) -> String {
+        match self.arguments {
+            Some(ref arguments) => format!("Get {}.{}({})", self.target.to_string(), self.member, arguments.iter().map(|argument| argument.to_string()).collect::<Vec<String>>().join(", ")),
+            None => format!("Get {}.{}", self.target.to_string(), self.member),
+        }
+    }
+}
+
+/// Instances can also be reached through `this`
+fn class_name(data_type: &DataType) -> Option<String> {
+    match data_type {
+        DataType::Named(name) => Some(name.clone()),
+        DataType::Reference(element) => class_name(element),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::{Parseable, value_expression::ValueExpr}};
+
+    #[test]
+    fn parse_dynamic_get() {
+        let tokens = tokenize("c.value + c.add(1, x).items[0] * t.0;");
+        let mut pos = 0;
+        assert_eq!(ValueExpr::parse(&tokens, &mut pos).unwrap().to_string(),
+                   "ADD Get VarCall c.value, MULTIPLY Index Get Get VarCall c.add(I32(1), VarCall x).items[I32(0)], Field VarCall t.0");
+    }
+}
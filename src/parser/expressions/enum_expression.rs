@@ -0,0 +1,262 @@
+use std::rc::Rc;
+
+use inkwell::{AddressSpace, builder::Builder, context::Context, module::Module, types::{BasicType, BasicTypeEnum, StructType}, values::{AnyValueEnum, IntValue, StructValue}};
+
+use crate::{compiler::checker::TypeChecker, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, data_types::DataType, variable_definition_expression::build_entry_alloca, scope::{ScopeManager, Enum}};
+
+/// Enum declaration, like `enum Shape { Empty, Circle(f64), Rect(f64, f64) }`
+pub struct EnumExpr {
+    token: Token,
+    name: String,
+    variants: Vec<(String, Vec<DataType>)>,
+    exported: bool,
+}
+
+impl Parseable for EnumExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Could start with "export" to make the enum visible to other files
+        let exported = tokens[*pos].token_type == TokenType::Identifier("export".to_string());
+        if exported {
+            *pos += 1;
+        }
+
+        // Should start with keyword "enum"
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("enum".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing enum",
+                              ErrorTokenComponent::new("Expected 'enum' keyword".to_string(), Some(token))));
+        }
+
+        // Should be followed by a name and a brace
+        *pos += 1;
+        let name = match tokens[*pos].token_type {
+            TokenType::Identifier(ref name) => name.clone(),
+            _ => return Err(error!(ErrorKind::ParserError, "Error while parsing enum",
+                                   ErrorTokenComponent::new("Expected enum name".to_string(), Some(tokens[*pos].clone())))),
+        };
+        *pos += 1;
+        expect(tokens, pos, TokenType::Brace('{'), "Expected '{'")?;
+
+        // Variants are separated by commas, each one can have field types in parentheses
+        let mut variants = vec![];
+        while tokens[*pos].token_type != TokenType::Brace('}') {
+            let variant = match tokens[*pos].token_type {
+                TokenType::Identifier(ref variant) => variant.clone(),
+                _ => return Err(error!(ErrorKind::ParserError, "Error while parsing enum",
+                                       ErrorTokenComponent::new("Expected variant name".to_string(), Some(tokens[*pos].clone())))),
+            };
+            *pos += 1;
+            let mut fields = vec![];
+            if tokens[*pos].token_type == TokenType::Paren('(') {
+                *pos += 1;
+                while tokens[*pos].token_type != TokenType::Paren(')') {
+                    match DataType::parse_type(tokens, pos) {
+                        Ok(field) => fields.push(field),
+                        Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing enum",
+                                                    ErrorTokenComponent::new("Expected field type".to_string(), Some(tokens[*pos].clone())))),
+                    }
+                    if tokens[*pos].token_type == TokenType::Separator(',') {
+                        *pos += 1;
+                    } else if tokens[*pos].token_type != TokenType::Paren(')') {
+                        return Err(error!(ErrorKind::ParserError, "Error while parsing enum",
+                                          ErrorTokenComponent::new("Expected ',' or ')'".to_string(), Some(tokens[*pos].clone()))));
+                    }
+                }
+                *pos += 1;
+            }
+            variants.push((variant, fields));
+            if tokens[*pos].token_type == TokenType::Separator(',') {
+                *pos += 1;
+            } else if tokens[*pos].token_type != TokenType::Brace('}') {
+                return Err(error!(ErrorKind::ParserError, "Error while parsing enum",
+                                  ErrorTokenComponent::new("Expected ',' or '}'".to_string(), Some(tokens[*pos].clone()))));
+            }
+        }
+        *pos += 1;
+
+        Ok(Box::new(EnumExpr {
+            token,
+            name,
+            variants,
+            exported,
+        }))
+    }
+}
+
+impl ASTExpr for EnumExpr {
+    fn declare_types<'a, 'b>(&self, context: &'a Context, scope_manager: &'b mut ScopeManager<'a>) {
+        check_type_name(context, &self.name, &self.token).unwrap_or_else(|err| err.panic());
+        let struct_type = context.opaque_struct_type(&self.name);
+        // Without the layout of every field the body stays opaque, the checker reports unknown types
+        if self.variants.iter().flat_map(|(_, fields)| fields.iter()).all(|field| scope_manager.scope.stores(field)) {
+            // Payload is made of the widest aligned integers, so every variant fits with its alignment
+            let (mut size, mut align) = (0, 1);
+            for (_, fields) in self.variants.iter() {
+                let variant = variant_type(context, fields).as_basic_type_enum();
+                size = size.max(scope_manager.target_data.get_abi_size(&variant));
+                align = align.max(scope_manager.target_data.get_abi_alignment(&variant) as u64);
+            }
+            let mut body: Vec<BasicTypeEnum<'a>> = vec![context.i8_type().into()];
+            if size > 0 {
+                let unit = context.custom_width_int_type(align as u32 * 8);
+                body.push(unit.array_type(((size + align - 1) / align) as u32).into());
+            }
+            struct_type.set_body(&body, false);
+        }
+        scope_manager.scope.enums.insert(self.name.clone(), Rc::new(Enum { struct_type, variants: self.variants.clone(), exported: self.exported }));
+    }
+
+    fn generate<'a, 'b>(&self, context: &'a Context, _module: &Module<'a>, _builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        // Use the declaration if the enum was declared before
+        if !scope_manager.scope.enums.contains_key(&self.name) {
+            self.declare_types(context, scope_manager);
+        }
+        check_type_body(scope_manager.scope.enums.get(&self.name).unwrap().struct_type, &self.name, &self.token);
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        if self.variants.len() > 256 {
+            checker.error(error!(ErrorKind::TypeError, "Enum has too many variants",
+                                 ErrorTokenComponent::new(format!("Enum {} has {} variants, but only 256 fit into the tag", self.name, self.variants.len()), Some(self.token.clone()))));
+        }
+        for (i, (variant, fields)) in self.variants.iter().enumerate() {
+            for field in fields.iter() {
+                checker.check_type(field, &self.token);
+            }
+            if self.variants[..i].iter().any(|(other, _)| other == variant) {
+                checker.error(error!(ErrorKind::TypeError, "Variant is defined more than once",
+                                     ErrorTokenComponent::new(format!("Variant {} of {} already exists", variant, self.name), Some(self.token.clone()))));
+            }
+            for field in fields.iter().filter(|field| matches!(field, DataType::Void | DataType::Never)) {
+                checker.error(error!(ErrorKind::TypeError, "Variant fields have to be values",
+                                     ErrorTokenComponent::new(format!("Field of {}::{} can not be stored", self.name, variant), Some(self.token.clone()))
+                                     NameErrorComponent::new(format!("Found: {}", field))));
+            }
+        }
+        checker.declare_enum(&self.name, self.variants.clone());
+        DataType::Void
+    }
+
+    fn to_string(&self) -> String {
+        let variants = self.variants.iter().map(|(variant, fields)| {
+            if fields.is_empty() {
+                variant.clone()
+            } else {
+                format!("{}({})", variant, fields.iter().map(|field| format!("{:?}", field)).collect::<Vec<String>>().join(", "))
+            }
+        }).collect::<Vec<String>>();
+        let export = if self.exported { "Export " } else { "" };
+        format!("{}Enum {} {{ {} }}", export, self.name, variants.join(", "))
+    }
+}
+
+/// Types that are stored in an enum or a class have to be declared before it
+pub fn check_type_body(struct_type: StructType, name: &str, token: &Token) {
+    if struct_type.is_opaque() {
+        error!(ErrorKind::CompilerError, "Type is used before it is declared",
+               ErrorTokenComponent::new(format!("Types stored in {} have to be declared before it", name), Some(token.clone()))).panic()
+    }
+}
+
+/// Named types are shared by all modules of the program, so their names can not repeat in any file
+pub fn check_type_name(context: &Context, name: &str, token: &Token) -> Result<(), Error> {
+    if context.get_struct_type(name).is_some() {
+        return Err(error!(ErrorKind::CompilerError, "Type is declared more than once",
+                          ErrorTokenComponent::new(format!("Type {} already exists, names of enums and classes have to be unique in the program", name), Some(token.clone()))));
+    }
+    Ok(())
+}
+
+/// Fields of a variant are stored as an anonymous struct in the payload
+pub fn variant_type<'a>(context: &'a Context, fields: &[DataType]) -> StructType<'a> {
+    let types = fields.iter().map(|field| field.into_basic_type(context)).collect::<Vec<BasicTypeEnum<'a>>>();
+    context.struct_type(&types, false)
+}
+
+/// Creates a value of the variant, fields are stored into the payload through a pointer of the variant type
+pub fn build_variant<'a>(context: &'a Context, builder: &Builder<'a>, declared: &Enum<'a>, variant: usize, fields: StructValue<'a>) -> StructValue<'a> {
+    let memory = build_entry_alloca(context, builder, declared.struct_type.into(), "enumtmp");
+    let tag = builder.build_struct_gep(memory, 0, "tag").unwrap();
+    builder.build_store(tag, context.i8_type().const_int(variant as u64, false));
+    if !declared.variants[variant].1.is_empty() {
+        let payload = builder.build_struct_gep(memory, 1, "payload").unwrap();
+        let payload = builder.build_pointer_cast(payload, fields.get_type().ptr_type(AddressSpace::Generic), "variantptr");
+        builder.build_store(payload, fields);
+    }
+    builder.build_load(memory, "enumval").into_struct_value()
+}
+
+/// Reads the fields of the variant from the value, the tag has to be checked before
+pub fn build_variant_fields<'a>(context: &'a Context, builder: &Builder<'a>, declared: &Enum<'a>, variant: usize, value: StructValue<'a>) -> StructValue<'a> {
+    let memory = build_entry_alloca(context, builder, declared.struct_type.into(), "enumtmp");
+    builder.build_store(memory, value);
+    let payload = builder.build_struct_gep(memory, 1, "payload").unwrap();
+    let fields_type = variant_type(context, &declared.variants[variant].1);
+    let payload = builder.build_pointer_cast(payload, fields_type.ptr_type(AddressSpace::Generic), "variantptr");
+    builder.build_load(payload, "variantval").into_struct_value()
+}
+
+pub fn build_tag<'a>(builder: &Builder<'a>, value: StructValue<'a>) -> IntValue<'a> {
+    builder.build_extract_value(value, 0, "tag").unwrap().into_int_value()
+}
+
+fn expect(tokens: &Vec<Token>, pos: &mut usize, token_type: TokenType, message: &str) -> Result<(), Error> {
+    if tokens[*pos].token_type != token_type {
+        return Err(error!(ErrorKind::ParserError, "Error while parsing enum",
+                          ErrorTokenComponent::new(message.to_string(), Some(tokens[*pos].clone()))));
+    }
+    *pos += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::Parseable};
+    use crate::compiler::{checker::check_source, compiler::run_source};
+
+    #[test]
+    fn parse_enums() {
+        let tokens = tokenize("enum Shape { Empty, Circle(f64), Rect(f64, (i32, u8)), }");
+        let mut pos = 0;
+        let expr = super::EnumExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Enum Shape { Empty, Circle(F64), Rect(F64, Tuple([I32, U8])) }");
+        assert_eq!(pos, tokens.len() - 1);
+
+        let tokens = tokenize("export enum Color { Red, Green }");
+        let mut pos = 0;
+        assert_eq!(super::EnumExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Export Enum Color { Red, Green }");
+    }
+
+    const SHAPE: &str = "enum Shape { Empty, Circle(f64), Rect(f64, f64) }";
+
+    #[test]
+    fn check_enums() {
+        let check = |source: &str| check_source(&format!("{} {}", SHAPE, source));
+        assert!(check("fun f(s: Shape): f64 { var c = Shape::Circle(1.0); match s { Empty => 0.0, Circle(r) => r, Rect(w, h) => w * h } }").is_empty());
+        assert_eq!(check_source("enum Bad { Nothing(void) }"), vec!["TypeError: Variant fields have to be values"]);
+        assert_eq!(check_source("enum Bad { Nothing, Nothing }"), vec!["TypeError: Variant is defined more than once"]);
+        assert_eq!(check("fun f(): void { var a = Shape::Circle(1); }"), vec!["TypeError: Variant field types do not match"]);
+        assert_eq!(check("fun f(): void { var a = Shape::Rect(1.0); }"), vec!["TypeError: Wrong number of variant fields"]);
+        assert_eq!(check("fun f(): void { var a = Shape::Square; }"), vec!["TypeError: Variant does not exist"]);
+        assert_eq!(check("fun f(): void { var a = Color::Red; }"), vec!["TypeError: Type does not exist"]);
+        assert_eq!(check("fun f(s: Shape): f64 { match s { Circle(r) | Empty => r, _ => 0.0 } }"), vec![
+            "TypeError: Patterns with bindings can not have alternatives",
+            "TypeError: Variable does not exist",
+        ]);
+        assert_eq!(check("fun f(s: Shape): f64 { match s { Rect(w, h: i32) => w, _ => 0.0 } }"), vec!["TypeError: Pattern type does not match the value"]);
+        assert_eq!(check("fun f(s: Shape): i32 { match s { 1 => 1, _ => 2 } }"), vec!["TypeError: Pattern type does not match the value"]);
+        assert_eq!(check("fun f(s: Shape): i32 { match s { Empty => 1, Circle(_) => 2 } }"), vec!["TypeError: Match is not exhaustive"]);
+    }
+
+    #[test]
+    fn run_enums() {
+        let source = "enum Color { Red, Green, Blue } enum Shape { Empty, Circle(i32), Rect(i32, u8), Painted(Color, i32) } \
+                      fun main(): i32 { area(Shape::Circle(2)) + area(Shape::Rect(3, 4u8)) * 100 + area(Shape::Empty) + paint(Shape::Painted(Color::Blue, 5)) * 10000 } \
+                      fun area(s: Shape): i32 { match s { Shape::Empty => 0, Circle(r) => r * r, Rect(w, h: u8) => w * h as i32, _ => 100 } } \
+                      fun paint(s: Shape): i32 { match s { Painted(c, n) => match c { Red => 1, Green => 2, Blue => 3 } * n, _ => 0 } }";
+        assert_eq!(run_source(source), 151204);
+    }
+}
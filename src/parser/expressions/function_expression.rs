@@ -1,26 +1,41 @@
 use std::collections::HashMap;
 use std::rc::Rc;
-use inkwell::{builder::Builder};
+use inkwell::{builder::Builder, module::Linkage};
 
-use crate::{lexer::lexer::TokenType, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
+use crate::{compiler::checker::{TypeChecker, BindingKind}, lexer::lexer::{Token, TokenType}, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
 
-use super::{ASTExpr, Parseable, VoidExpr, basic_expression::BasicExpr, data_types::DataType, scope::ScopeManager};
+use super::{ASTExpr, Parseable, VoidExpr, basic_expression::BasicExpr, data_types::DataType, return_expression::{build_return, check_return, is_terminated}, scope::{ScopeManager, Function, Value}};
 
 pub struct FunctionExpr {
     body: Box<dyn ASTExpr>,
     arguments: Vec<(String, DataType)>,
     return_type: DataType,
     name: String,
+    token: Token,
     is_vararg: bool,
+    exported: bool,
     // TODO: Add generics
 }
 
 impl Parseable for FunctionExpr {
     fn parse(tokens: &Vec<crate::lexer::lexer::Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        Ok(Box::new(FunctionExpr::parse_function(tokens, pos)?))
+    }
+}
+
+impl FunctionExpr {
+    /// Parses the function without boxing it, so classes can turn it into a method
+    pub fn parse_function(tokens: &Vec<Token>, pos: &mut usize) -> Result<FunctionExpr, Error> {
         let mut arguments: Vec<(String, DataType)> = Vec::new();
         let mut return_type: DataType = DataType::Void;
         let mut body: Box<dyn ASTExpr> = Box::new(VoidExpr {});
         let mut name: String = String::new();
+
+        // Could start with "export" to make the function visible to other files
+        let exported = tokens[*pos].token_type == TokenType::Identifier("export".to_string());
+        if exported {
+            *pos += 1;
+        }
         
         // Should start with keyword "fn"
         if tokens[*pos].token_type != TokenType::Identifier("fun".to_string()) {
@@ -30,6 +45,7 @@ impl Parseable for FunctionExpr {
 
         // Should be followed by a name
         *pos += 1;
+        let token = tokens[*pos].clone();
         match tokens[*pos].token_type {
             TokenType::Identifier(ref s) => { name = s.clone(); },
             _ => error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing function name",
@@ -57,8 +73,14 @@ impl Parseable for FunctionExpr {
                                ErrorTokenComponent::new("Expected ':'".to_string(), Some(tokens[*pos].clone()))).panic()
                     }
                     *pos += 1;
-                    let data_type = DataType::parse(&tokens[*pos])?;
-                    *pos += 1;
+                    // Methods take `this: &this` as the first argument
+                    let data_type = if s == "this" && arguments.is_empty() && tokens[*pos].token_type == TokenType::Operator("&".to_string())
+                                       && tokens[*pos + 1].token_type == TokenType::Identifier("this".to_string()) {
+                        *pos += 2;
+                        DataType::Reference(Box::new(DataType::Named("this".to_string())))
+                    } else {
+                        DataType::parse_type(tokens, pos)?
+                    };
                     arguments.push((s.clone(), data_type));
                 },
                 _ => error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing function arguments",
@@ -80,27 +102,58 @@ impl Parseable for FunctionExpr {
 
         // Should be followed by a return type
         *pos += 1;
-        return_type = DataType::parse(&tokens[*pos])?;
+        return_type = DataType::parse_type(tokens, pos)?;
 
         // Should be followed by a function body
-        *pos += 1;
-        let t = BasicExpr::parse(tokens, pos);
-        match t {
+        let start = *pos;
+        match BasicExpr::parse(tokens, pos) {
             Ok(expr) => body = expr,
-            Err(_) => error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing function body (TODO: Add line and column info)").panic()
+            // Error inside of the body is more precise
+            Err(err) if *pos > start => return Err(err),
+            Err(_) => return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing function",
+                                        ErrorTokenComponent::new("Expected function body".to_string(), Some(tokens[*pos].clone())))),
         }
 
         // Return function
-        Ok(Box::new(FunctionExpr {
+        Ok(FunctionExpr {
             body,
             arguments,
             return_type,
             name,
+            token,
             is_vararg: false, // Add option for varargs
-        }))
+            exported,
+        })
+    }
+
+    /// Makes the function a method of the class, it is named `Class.method` and `this` points to the class
+    pub fn into_method(mut self, class: &str) -> FunctionExpr {
+        self.name = format!("{}.{}", class, self.name);
+        self.exported = false;
+        if let Some(arg) = self.arguments.first_mut().filter(|arg| arg.0 == "this" && matches!(arg.1, DataType::Reference(_))) {
+            arg.1 = DataType::Reference(Box::new(DataType::Named(class.to_string())));
+        }
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn token(&self) -> &Token {
+        &self.token
+    }
+
+    pub fn arguments(&self) -> &[(String, DataType)] {
+        &self.arguments
+    }
+
+    pub fn return_type(&self) -> &DataType {
+        &self.return_type
     }
 }
 
+
 impl ASTExpr for FunctionExpr {
     fn to_string(&self) -> String {
         let mut arguments = String::new();
@@ -108,10 +161,15 @@ impl ASTExpr for FunctionExpr {
             arguments.push_str(&format!("{}: {:?}, ", arg.0, arg.1));
         }
 
-        format!("Function {}({}) => {:?} {}", self.name, arguments, self.return_type, self.body.to_string())
+        let export = if self.exported { "Export " } else { "" };
+        format!("{}Function {}({}) => {:?} {}", export, self.name, arguments, self.return_type, self.body.to_string())
     }
 
-    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
+    fn declare<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, scope_manager: &'b mut ScopeManager<'a>) {
+        // Types that do not exist are reported by the checker
+        if !self.arguments.iter().map(|arg| &arg.1).chain([&self.return_type]).all(|data_type| scope_manager.scope.declares(data_type)) {
+            return;
+        }
         // Create sorted vector from arguments
         let mut arguments: Vec<DataType> = Vec::new();
         for arg in self.arguments.iter() {
@@ -121,6 +179,21 @@ impl ASTExpr for FunctionExpr {
         let f_type = self.return_type.into_fn_type(context, arguments, self.is_vararg);
         // Create function
         let function = module.add_function(self.name.as_str(), f_type, None);
+        // Functions that are not exported are private to the file
+        if !self.exported && self.name != "main" {
+            function.set_linkage(Linkage::Internal);
+        }
+        // Register function, so it can be called
+        let arguments = self.arguments.iter().map(|arg| arg.1.clone()).collect();
+        scope_manager.scope.functions.insert(self.name.to_string(), Rc::new(Function { value: function, arguments, return_type: self.return_type.clone() }));
+    }
+
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
+        // Use the declaration if the function was declared before
+        if !scope_manager.scope.functions.contains_key(&self.name) {
+            self.declare(context, module, scope_manager);
+        }
+        let function = scope_manager.scope.functions.get(&self.name).unwrap().value;
 
         // Create basic block
         let entry_block = context.append_basic_block(function, "entry");
@@ -130,14 +203,60 @@ impl ASTExpr for FunctionExpr {
         scope_manager.scope.function = Some(Rc::new(function));
         // Insert function arguments into fn_args
         for (i, arg) in self.arguments.iter().enumerate() {
-            scope_manager.scope.fn_args.insert(arg.0.to_string(), Rc::new(function.get_nth_param(i as u32).unwrap()));
+            scope_manager.scope.fn_args.insert(arg.0.to_string(), Rc::new(Value { value: function.get_nth_param(i as u32).unwrap(), data_type: arg.1.clone() }));
         }
         // Generate function code
-        self.body.generate(context, module, builder, scope_manager);
+        let value = self.body.generate(context, module, builder, scope_manager);
+        // Return value of the body, unless every path already returned
+        if !is_terminated(builder) {
+            if function.get_type().get_return_type().is_none() {
+                builder.build_return(None);
+            } else if value.is_none() {
+                error!(crate::utils::error::ErrorKind::CompilerError, "Missing return value",
+                       ErrorTokenComponent::new(format!("Function '{}' can reach its end without returning a value", self.name), Some(self.token.clone()))).panic()
+            } else {
+                build_return(builder, function, value, &self.token);
+            }
+        }
         // Exit function scope
         scope_manager.exit_scope();
         return Some(inkwell::values::AnyValueEnum::FunctionValue(function));
     }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        // Functions with unknown types are not declared, so the checker does not know them yet
+        if checker.function(&self.name).is_none() {
+            checker.declare_function(&self.name, self.arguments.iter().map(|arg| arg.1.clone()).collect(), self.return_type.clone());
+        }
+        // Methods with the same name are reported as members by their class
+        if !checker.define_function(&self.name) && !checker.in_class() {
+            checker.error(error!(crate::utils::error::ErrorKind::TypeError, "Function is defined more than once",
+                                 ErrorTokenComponent::new(format!("Function '{}' already has a body", self.name), Some(self.token.clone()))));
+        }
+        for arg in self.arguments.iter() {
+            checker.check_type(&arg.1, &self.token);
+        }
+        checker.check_type(&self.return_type, &self.token);
+        if self.arguments.first().map_or(false, |arg| arg.1 == DataType::Reference(Box::new(DataType::Named("this".to_string())))) {
+            checker.error(error!(crate::utils::error::ErrorKind::TypeError, "'this' can only be used in methods",
+                                 ErrorTokenComponent::new(format!("Function '{}' is not a method of a class", self.name), Some(self.token.clone()))));
+        }
+        let saved = checker.enter_function(self.return_type.clone());
+        for arg in self.arguments.iter() {
+            checker.define(&arg.0, arg.1.clone(), BindingKind::Argument);
+        }
+        let body = self.body.check(checker);
+        checker.exit_function(saved);
+
+        // Value of the body is returned, void functions ignore it
+        match body {
+            _ if self.return_type == DataType::Void => {}
+            DataType::Void => checker.error(error!(crate::utils::error::ErrorKind::TypeError, "Missing return value",
+                                                   ErrorTokenComponent::new(format!("Function '{}' can reach its end without returning a value", self.name), Some(self.token.clone())))),
+            body => check_return(checker, &self.return_type, &body, &self.token),
+        }
+        DataType::Void
+    }
     
 }
 
@@ -189,4 +308,22 @@ mod tests {
         assert_eq!(expr.to_string(), "Function test(arg1: F32, arg2: I64, ) => U16 {\n\t  \n}");
     }
 
+    #[test]
+    fn parse_exported_function() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("export".to_string())),
+            test_token!(TokenType::Identifier("fun".to_string())),
+            test_token!(TokenType::Identifier("test".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Separator(':')),
+            test_token!(TokenType::Identifier("void".to_string())),
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Brace('}')),
+        ];
+        let mut pos = 0;
+        let expr = super::FunctionExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Export Function test() => Void {\n\t  \n}");
+    }
+
 }
@@ -0,0 +1,248 @@
+use std::cell::RefCell;
+
+use inkwell::{IntPredicate, builder::Builder, values::{AnyValue, AnyValueEnum, IntValue}};
+
+use crate::{compiler::checker::TypeChecker, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, basic_expression::BasicExpr, data_types::{DataType, ToBasic}, value_expression::ValueExpr, return_expression::is_terminated, scope::ScopeManager};
+
+pub struct IfExpr {
+    token: Token,
+    condition: Box<dyn ASTExpr>,
+    then_body: Box<dyn ASTExpr>,
+    else_body: Option<Box<dyn ASTExpr>>,
+    // Type merged from both arms by the checker
+    result_type: RefCell<DataType>,
+}
+
+impl Parseable for IfExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with keyword "if"
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("if".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing if expression",
+                              ErrorTokenComponent::new("Expected 'if' keyword".to_string(), Some(token))));
+        }
+
+        // Should be followed by a condition in parentheses
+        *pos += 1;
+        let condition = parse_condition(tokens, pos, "if")?;
+
+        // Should be followed by a body, errors inside of it are more precise
+        let start = *pos;
+        let then_body = match BasicExpr::parse(tokens, pos) {
+            Ok(body) => body,
+            Err(err) if *pos > start => return Err(err),
+            Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing if expression",
+                                        ErrorTokenComponent::new("Expected body".to_string(), Some(tokens[*pos].clone())))),
+        };
+
+        // Could be followed by "else" and another body
+        let mut else_body = None;
+        if tokens[*pos].token_type == TokenType::Identifier("else".to_string()) {
+            *pos += 1;
+            let start = *pos;
+            match BasicExpr::parse(tokens, pos) {
+                Ok(body) => else_body = Some(body),
+                Err(err) if *pos > start => return Err(err),
+                Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing else body",
+                                            ErrorTokenComponent::new("Expected body".to_string(), Some(tokens[*pos].clone())))),
+            }
+        }
+
+        Ok(Box::new(IfExpr {
+            token,
+            condition,
+            then_body,
+            else_body,
+            result_type: RefCell::new(DataType::Void),
+        }))
+    }
+}
+
+impl ASTExpr for IfExpr {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let condition = self.condition.generate(context, module, builder, scope_manager);
+        let condition = build_condition(builder, condition, &self.token);
+
+        let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+        let then_block = context.append_basic_block(function, "then");
+        let else_block = context.append_basic_block(function, "else");
+        let merge_block = context.append_basic_block(function, "merge");
+        builder.build_conditional_branch(condition, then_block, else_block);
+
+        // Generate both arms, each one can end in a different block
+        builder.position_at_end(then_block);
+        let then_value = self.then_body.generate(context, module, builder, scope_manager);
+        let then_end = builder.get_insert_block().unwrap();
+        let then_terminated = is_terminated(builder);
+        if !then_terminated {
+            builder.build_unconditional_branch(merge_block);
+        }
+
+        builder.position_at_end(else_block);
+        let else_value = match self.else_body {
+            Some(ref body) => body.generate(context, module, builder, scope_manager),
+            None => None,
+        };
+        let else_end = builder.get_insert_block().unwrap();
+        let else_terminated = is_terminated(builder);
+        if !else_terminated {
+            builder.build_unconditional_branch(merge_block);
+        }
+
+        builder.position_at_end(merge_block);
+        match (then_terminated, else_terminated) {
+            // Nothing continues after the if
+            (true, true) => {
+                builder.build_unreachable();
+                None
+            }
+            (false, true) => then_value,
+            (true, false) => else_value,
+            (false, false) => {
+                // Value is only known if both arms produce the same type
+                match (then_value, else_value) {
+                    (Some(then_value), Some(else_value)) if then_value.get_type() == else_value.get_type() => {
+                        let then_value = then_value.to_basic();
+                        let else_value = else_value.to_basic();
+                        let phi = builder.build_phi(then_value.get_type(), "iftmp");
+                        phi.add_incoming(&[(&then_value, then_end), (&else_value, else_end)]);
+                        Some(phi.as_any_value_enum())
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        // If where both arms never finish does not produce a value
+        match *self.result_type.borrow() {
+            DataType::Never => DataType::Void,
+            ref data_type => data_type.clone(),
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        checker.check_condition(self.condition.as_ref(), &self.token);
+        let then_type = self.then_body.check(checker);
+        let else_type = match self.else_body {
+            Some(ref body) => body.check(checker),
+            None => return DataType::Void,
+        };
+        // Arm that never finishes does not decide the type
+        let result_type = match (then_type, else_type) {
+            (DataType::Never, data_type) | (data_type, DataType::Never) => data_type,
+            (then_type, else_type) if then_type == else_type => then_type,
+            _ => DataType::Void,
+        };
+        *self.result_type.borrow_mut() = result_type.clone();
+        result_type
+    }
+
+    fn to_string(&self) -> String {
+        match self.else_body {
+            Some(ref else_body) => format!("If {} {} Else {}", self.condition.to_string(), self.then_body.to_string(), else_body.to_string()),
+            None => format!("If {} {}", self.condition.to_string(), self.then_body.to_string()),
+        }
+    }
+}
+
+/// Parses a value in parentheses, like the condition of `if (x)`
+pub fn parse_condition(tokens: &Vec<Token>, pos: &mut usize, keyword: &str) -> Result<Box<dyn ASTExpr>, Error> {
+    if tokens[*pos].token_type != TokenType::Paren('(') {
+        return Err(error!(ErrorKind::ParserError, "Error while parsing condition",
+                          ErrorTokenComponent::new(format!("Expected '(' after '{}'", keyword), Some(tokens[*pos].clone()))));
+    }
+    *pos += 1;
+    let start = *pos;
+    let condition = match ValueExpr::parse(tokens, pos) {
+        Ok(condition) => condition,
+        Err(err) if *pos > start => return Err(err),
+        Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing condition",
+                                    ErrorTokenComponent::new("Expected condition".to_string(), Some(tokens[*pos].clone())))),
+    };
+    if tokens[*pos].token_type != TokenType::Paren(')') {
+        return Err(error!(ErrorKind::ParserError, "Error while parsing condition",
+                          ErrorTokenComponent::new("Expected ')'".to_string(), Some(tokens[*pos].clone()))));
+    }
+    *pos += 1;
+    Ok(condition)
+}
+
+/// Converts the value into a bool, integers are true when they are not zero
+pub fn build_condition<'a>(builder: &Builder<'a>, value: Option<AnyValueEnum<'a>>, token: &Token) -> IntValue<'a> {
+    match value {
+        Some(AnyValueEnum::IntValue(value)) if value.get_type().get_bit_width() == 1 => value,
+        Some(AnyValueEnum::IntValue(value)) => builder.build_int_compare(IntPredicate::NE, value, value.get_type().const_zero(), "cond"),
+        Some(value) => error!(ErrorKind::CompilerError, "Condition has to be a bool",
+                              ErrorTokenComponent::new("Invalid condition".to_string(), Some(token.clone()))
+                              NameErrorComponent::new(format!("Found: {}", value.get_type()))).panic(),
+        None => error!(ErrorKind::CompilerError, "Condition has to be a bool",
+                       ErrorTokenComponent::new("Condition does not have a value".to_string(), Some(token.clone()))).panic(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::TokenType, test_token, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::compiler::run_source;
+
+    #[test]
+    fn parse_if_without_else() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("if".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Identifier("y".to_string())),
+            test_token!(TokenType::Separator(';')),
+            test_token!(TokenType::Brace('}')),
+            test_token!(TokenType::EOF),
+        ];
+        let mut pos = 0;
+        let expr = super::IfExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "If VarCall x {\n\t VarCall y \n}");
+        assert_eq!(pos, 8);
+    }
+
+    #[test]
+    fn parse_if_else_value() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("if".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Number("1".to_string())),
+            test_token!(TokenType::Brace('}')),
+            test_token!(TokenType::Identifier("else".to_string())),
+            test_token!(TokenType::Identifier("if".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Identifier("y".to_string())),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Number("2".to_string())),
+            test_token!(TokenType::Brace('}')),
+            test_token!(TokenType::Identifier("else".to_string())),
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Number("3".to_string())),
+            test_token!(TokenType::Brace('}')),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "If VarCall x {\n\t Result I32(1) \n} Else If VarCall y {\n\t Result I32(2) \n} Else {\n\t Result I32(3) \n}");
+    }
+
+    #[test]
+    fn run_if_else() {
+        assert_eq!(run_source("fun main(): i32 { if (1) { 2 } else { 3 } }"), 2);
+        assert_eq!(run_source("fun main(): i32 { var x: i32 = if (0) { 2 } else if (0) { 3 } else { 4 }; x }"), 4);
+        assert_eq!(run_source("fun pick(x: i32): i32 { if (x) { return 10; } else { return 20; } } fun main(): i32 { pick(0) + pick(5) }"), 30);
+        assert_eq!(run_source("fun main(): i32 { if (0) { return 1; } 2 }"), 2);
+        assert_eq!(run_source("fun main(): i32 { var x = if (0) { return 1; } else { 250u8 }; (x / 5u8) as i32 }"), 50);
+    }
+}
@@ -1,18 +1,22 @@
+use std::rc::Rc;
+
 use inkwell::builder::Builder;
 
-use crate::{lexer::lexer::{Token, TokenType}, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
+use crate::{compiler::units, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
 
-use super::{ASTExpr, Parseable, scope::ScopeManager};
+use super::{ASTExpr, Parseable, class_expression::init_name, scope::{ScopeManager, Function, Enum, Class}};
 
 #[derive(Debug)]
 pub struct ImportExpr {
+    token: Token,
     path: Vec<String>,
     imports: Vec<String>,
 }
 
 impl ImportExpr {
-    fn new(path: Vec<String>, imports: Vec<String>) -> Self {
+    fn new(token: Token, path: Vec<String>, imports: Vec<String>) -> Self {
         Self {
+            token,
             path,
             imports,
         }
@@ -31,6 +35,7 @@ impl Parseable for ImportExpr {
         }
     
         // Should be followed by a path
+        let start = *pos;
         *pos += 1;
         while tokens[*pos].token_type != TokenType::Separator(';') && tokens[*pos].token_type != TokenType::Brace('{') {
             // Path
@@ -48,7 +53,7 @@ impl Parseable for ImportExpr {
                            ErrorTokenComponent::new("Expected ';'".to_string(), Some(tokens[*pos].clone()))).panic()
                 }
                 *pos += 1;
-                return Ok(Box::new(ImportExpr::new(path, imports)));
+                return Ok(Box::new(ImportExpr::new(tokens[start].clone(), path, imports)));
             }
     
             // Double colon
@@ -92,7 +97,7 @@ impl Parseable for ImportExpr {
         }
         *pos += 1;
     
-        Ok(Box::new(ImportExpr::new(path, imports)))
+        Ok(Box::new(ImportExpr::new(tokens[start].clone(), path, imports)))
     }
 }
 
@@ -102,7 +107,78 @@ impl ASTExpr for ImportExpr {
     }
 
     fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
-        todo!()
+        None
+    }
+
+    /// Imports are resolved before the file is checked, so imported functions are known to the type checker
+    fn declare<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, scope_manager: &'b mut ScopeManager<'a>) {
+        // Find the file, rest of the path names a symbol inside of it
+        let (file, used) = match scope_manager.units.resolve(&self.path) {
+            Some(resolved) => resolved,
+            None => error!(ErrorKind::CompilerError, "Could not resolve import",
+                           ErrorTokenComponent::new(format!("No file found for '{}'", self.path.join("::")), Some(self.token.clone()))).panic()
+        };
+        let names = match self.path[used..] {
+            [] => self.imports.clone(),
+            [ref name] if self.imports.is_empty() => vec![name.clone()],
+            _ => error!(ErrorKind::CompilerError, "Could not resolve import",
+                        ErrorTokenComponent::new(format!("'{}' is not a module of {}", self.path[used..].join("::"), self.path[..used].join("::")), Some(self.token.clone()))).panic()
+        };
+
+        // Compile the imported file first, so its exports are known
+        if let Some(chain) = scope_manager.units.cycle(&file) {
+            let chain: Vec<String> = chain.iter().map(|f| scope_manager.units.display(f)).collect();
+            error!(ErrorKind::CompilerError, "Import cycle detected",
+                   ErrorTokenComponent::new(format!("'{}' is already being imported", self.path[..used].join("::")), Some(self.token.clone()))
+                   NameErrorComponent::new(format!("Cycle: {}", chain.join(" -> ")))).panic()
+        }
+        if !scope_manager.units.is_compiled(&file) {
+            if let Err(err) = units::compile_file(context, scope_manager, &file) {
+                err.panic()
+            }
+        }
+
+        // Without names everything that is exported is imported, classes bring their methods with them
+        let exports = scope_manager.units.exports(&file).unwrap();
+        let mut imported = Vec::new();
+        let mut enums = Vec::new();
+        let mut classes = Vec::new();
+        if names.is_empty() {
+            imported.extend(exports.functions.iter().map(|(name, signature)| (name.clone(), signature.clone())));
+            enums.extend(exports.enums.iter().map(|(name, declared)| (name.clone(), declared.clone())));
+            classes.extend(exports.classes.iter().map(|(name, declared)| (name.clone(), declared.clone())));
+        }
+        for name in names.iter() {
+            if let Some(signature) = exports.functions.get(name) {
+                imported.push((name.clone(), signature.clone()));
+            } else if let Some(declared) = exports.enums.get(name) {
+                enums.push((name.clone(), declared.clone()));
+            } else if let Some(declared) = exports.classes.get(name) {
+                classes.push((name.clone(), declared.clone()));
+                let prefix = format!("{}.", name);
+                imported.extend(exports.functions.iter().filter(|(method, _)| method.starts_with(&prefix)).map(|(method, signature)| (method.clone(), signature.clone())));
+            } else {
+                error!(ErrorKind::CompilerError, "Could not resolve import",
+                       ErrorTokenComponent::new(format!("'{}' is not exported from {}", name, self.path[..used].join("::")), Some(self.token.clone()))).panic()
+            }
+        }
+
+        // Imported types are not exported again by this file
+        for (name, declared) in enums {
+            scope_manager.scope.enums.insert(name, Rc::new(Enum { exported: false, ..(*declared).clone() }));
+        }
+        for (name, declared) in classes {
+            if module.get_function(&init_name(&name)).is_none() {
+                module.add_function(&init_name(&name), declared.struct_type.fn_type(&[], false), None);
+            }
+            scope_manager.scope.classes.insert(name, Rc::new(Class { exported: false, ..(*declared).clone() }));
+        }
+        for (name, signature) in imported {
+            let value = module.get_function(&name).unwrap_or_else(|| {
+                module.add_function(&name, signature.return_type.into_fn_type(context, signature.arguments.clone(), false), None)
+            });
+            scope_manager.scope.functions.insert(name, Rc::new(Function { value, arguments: signature.arguments, return_type: signature.return_type }));
+        }
     }
     
 }
@@ -0,0 +1,323 @@
+use std::cell::RefCell;
+
+use inkwell::{AddressSpace, IntPredicate, builder::Builder, context::Context, module::{Linkage, Module}, values::{AnyValueEnum, BasicMetadataValueEnum, IntValue, PointerValue}};
+
+use crate::{compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::Token, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, data_types::{DataType, ToAny}, scope::ScopeManager, array_expression::{build_slice, slice_type}, string_expression::string_type, variable_definition_expression::build_entry_alloca, tuple_expression::{build_tuple_field, check_tuple_field}};
+
+/// Element of a value, like `a[i]`, strings are indexed by bytes, tuples only by constants
+pub struct IndexExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+    index: Box<dyn ASTExpr>,
+    // Field of a tuple, it is known after the expression was checked
+    tuple_field: RefCell<Option<u32>>,
+}
+
+impl IndexExpr {
+    pub fn new(token: Token, value: Box<dyn ASTExpr>, index: Box<dyn ASTExpr>) -> IndexExpr {
+        IndexExpr { token, value, index, tuple_field: RefCell::new(None) }
+    }
+}
+
+/// Part of a value, like `a[start..end]`, slices point into the memory of the value
+pub struct SliceExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+    start: Box<dyn ASTExpr>,
+    end: Box<dyn ASTExpr>,
+    inclusive: bool,
+}
+
+impl SliceExpr {
+    pub fn new(token: Token, value: Box<dyn ASTExpr>, start: Box<dyn ASTExpr>, end: Box<dyn ASTExpr>, inclusive: bool) -> SliceExpr {
+        SliceExpr { token, value, start, end, inclusive }
+    }
+}
+
+impl ASTExpr for IndexExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        if let Some(field) = *self.tuple_field.borrow() {
+            return match self.value.generate(context, module, builder, scope_manager) {
+                Some(AnyValueEnum::StructValue(tuple)) => Some(build_tuple_field(builder, tuple, field, &self.token).to_any()),
+                _ => error!(ErrorKind::CompilerError, "Value is not a tuple",
+                            ErrorTokenComponent::new(format!("Field {} does not exist", field), Some(self.token.clone()))).panic()
+            };
+        }
+        let (data, len) = build_elements(context, module, builder, scope_manager, &*self.value, &self.token);
+        let index = build_index(context, module, builder, scope_manager, &*self.index, &self.token);
+        if scope_manager.error_handler {
+            let valid = builder.build_int_compare(IntPredicate::ULT, index, len, "inbounds");
+            build_runtime_check(context, module, builder, valid, &self.token, "Index %lld is out of bounds for length %lld", &[index.into(), len.into()]);
+        }
+        let pointer = unsafe { builder.build_in_bounds_gep(data, &[index], "elemptr") };
+        Some(builder.build_load(pointer, "elem").to_any())
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        match self.value.data_type(scope_manager) {
+            DataType::String => DataType::U8,
+            DataType::Array(element, _) | DataType::Slice(element) => *element,
+            DataType::Tuple(mut types) => match *self.tuple_field.borrow() {
+                Some(field) if (field as usize) < types.len() => types.swap_remove(field as usize),
+                _ => DataType::Unknown,
+            },
+            _ => DataType::Unknown,
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let value = self.value.check(checker);
+        if let DataType::Tuple(_) = value {
+            return self.check_tuple(checker, value);
+        }
+        check_index(checker, &self.token, &*self.index);
+        // Constant indices of arrays are checked at compile time
+        if let (DataType::Array(_, len), Some(Constant::Int(index, _))) = (&value, self.index.evaluate(checker)) {
+            if index < 0 || index >= *len as i128 {
+                checker.error(error!(ErrorKind::TypeError, "Index out of bounds",
+                                     ErrorTokenComponent::new(format!("Index {} is out of bounds for length {}", index, len), Some(self.token.clone()))));
+            }
+        }
+        match value {
+            DataType::String => DataType::U8,
+            DataType::Array(element, _) | DataType::Slice(element) => *element,
+            DataType::Unknown => DataType::Unknown,
+            value => {
+                checker.error(error!(ErrorKind::TypeError, "Value can not be indexed",
+                                     ErrorTokenComponent::new(format!("Type {} has no elements", value), Some(self.token.clone()))));
+                DataType::Unknown
+            }
+        }
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        match (self.value.evaluate(checker)?, self.index.evaluate(checker)?) {
+            (Constant::Str(string), Constant::Int(index, _)) => {
+                let byte = *string.as_bytes().get(usize::try_from(index).ok()?)?;
+                Some(Constant::int(byte as i128, DataType::U8))
+            }
+            _ => None,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        format!("Index {}[{}]", self.value.to_string(), self.index.to_string())
+    }
+}
+
+impl IndexExpr {
+    /// Elements of a tuple have different types, so the index has to be known at compile time
+    fn check_tuple(&self, checker: &mut TypeChecker, value: DataType) -> DataType {
+        check_index(checker, &self.token, &*self.index);
+        match self.index.evaluate(checker) {
+            Some(Constant::Int(index, _)) => {
+                let element = check_tuple_field(checker, &self.token, value, index);
+                if element != DataType::Unknown {
+                    *self.tuple_field.borrow_mut() = Some(index as u32);
+                }
+                element
+            }
+            _ => {
+                checker.error(error!(ErrorKind::TypeError, "Tuple index has to be a constant",
+                                     ErrorTokenComponent::new("Index is not known at compile time".to_string(), Some(self.token.clone()))));
+                DataType::Unknown
+            }
+        }
+    }
+}
+
+impl ASTExpr for SliceExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let (data, len) = build_elements(context, module, builder, scope_manager, &*self.value, &self.token);
+        let start = build_index(context, module, builder, scope_manager, &*self.start, &self.token);
+        let mut end = build_index(context, module, builder, scope_manager, &*self.end, &self.token);
+        if self.inclusive {
+            end = builder.build_int_add(end, context.i64_type().const_int(1, false), "sliceend");
+        }
+        if scope_manager.error_handler {
+            let ordered = builder.build_int_compare(IntPredicate::ULE, start, end, "sliceordered");
+            let inside = builder.build_int_compare(IntPredicate::ULE, end, len, "sliceinside");
+            let valid = builder.build_and(ordered, inside, "inbounds");
+            build_runtime_check(context, module, builder, valid, &self.token, "Slice %lld..%lld is out of bounds for length %lld", &[start.into(), end.into(), len.into()]);
+        }
+
+        let pointer = unsafe { builder.build_in_bounds_gep(data, &[start], "sliceptr") };
+        let slice_len = builder.build_int_sub(end, start, "slicelen");
+        let slice_type = match self.value.data_type(scope_manager) {
+            DataType::Array(element, _) | DataType::Slice(element) => slice_type(context, element.into_basic_type(context)),
+            _ => string_type(context),
+        };
+        Some(AnyValueEnum::StructValue(build_slice(builder, slice_type, pointer, slice_len)))
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        match self.value.data_type(scope_manager) {
+            DataType::String => DataType::String,
+            DataType::Array(element, _) | DataType::Slice(element) => DataType::Slice(element),
+            _ => DataType::Unknown,
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let value = self.value.check(checker);
+        check_index(checker, &self.token, &*self.start);
+        check_index(checker, &self.token, &*self.end);
+        match value {
+            DataType::String => DataType::String,
+            DataType::Array(element, _) | DataType::Slice(element) => DataType::Slice(element),
+            DataType::Unknown => DataType::Unknown,
+            value => {
+                checker.error(error!(ErrorKind::TypeError, "Value can not be sliced",
+                                     ErrorTokenComponent::new(format!("Type {} has no elements", value), Some(self.token.clone()))));
+                DataType::Unknown
+            }
+        }
+    }
+
+    fn to_string(&self) -> String {
+        let range = if self.inclusive { "..=" } else { ".." };
+        format!("Slice {}[{}{}{}]", self.value.to_string(), self.start.to_string(), range, self.end.to_string())
+    }
+}
+
+fn check_index(checker: &mut TypeChecker, token: &Token, index: &dyn ASTExpr) {
+    let found = index.check(checker);
+    if found != DataType::Unknown && !found.is_integer() {
+        checker.error(error!(ErrorKind::TypeError, "Index has to be an integer",
+                             ErrorTokenComponent::new("Invalid index".to_string(), Some(token.clone()))
+                             NameErrorComponent::new(format!("Found: {}", found))));
+    }
+}
+
+/// Pointer to the first element of the value and the number of elements,
+/// arrays that are not stored anywhere are copied into memory, so their elements can be addressed
+fn build_elements<'a>(context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &mut ScopeManager<'a>, value: &dyn ASTExpr, token: &Token) -> (PointerValue<'a>, IntValue<'a>) {
+    let data_type = value.data_type(scope_manager);
+    let zero = context.i64_type().const_zero();
+    if let DataType::Array(_, len) = data_type {
+        if let Some(memory) = value.pointer(context, module, builder, scope_manager) {
+            let data = unsafe { builder.build_in_bounds_gep(memory, &[zero, zero], "data") };
+            return (data, context.i64_type().const_int(len as u64, false));
+        }
+    }
+    let generated = value.generate(context, module, builder, scope_manager);
+    match (generated, data_type) {
+        (Some(AnyValueEnum::StructValue(slice)), DataType::String | DataType::Slice(_)) => {
+            let data = builder.build_extract_value(slice, 0, "data").unwrap().into_pointer_value();
+            let len = builder.build_extract_value(slice, 1, "len").unwrap().into_int_value();
+            (data, len)
+        }
+        (Some(AnyValueEnum::ArrayValue(array)), DataType::Array(_, len)) => {
+            let memory = build_entry_alloca(context, builder, array.get_type().into(), "arrmem");
+            builder.build_store(memory, array);
+            let data = unsafe { builder.build_in_bounds_gep(memory, &[zero, zero], "data") };
+            (data, context.i64_type().const_int(len as u64, false))
+        }
+        (_, data_type) => error!(ErrorKind::CompilerError, "Value can not be indexed",
+                                 ErrorTokenComponent::new(format!("Type {} has no elements", data_type), Some(token.clone()))).panic()
+    }
+}
+
+/// Generates the index as a 64 bit integer, GEP treats indices as signed, so unsigned ones are extended with zeros
+fn build_index<'a>(context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &mut ScopeManager<'a>, index: &dyn ASTExpr, token: &Token) -> IntValue<'a> {
+    let unsigned = index.data_type(scope_manager).is_unsigned();
+    let value = match index.generate(context, module, builder, scope_manager) {
+        Some(AnyValueEnum::IntValue(value)) => value,
+        _ => error!(ErrorKind::CompilerError, "Index has to be an integer",
+                    ErrorTokenComponent::new("Invalid index".to_string(), Some(token.clone()))).panic()
+    };
+    let i64_type = context.i64_type();
+    if value.get_type().get_bit_width() == 64 {
+        value
+    } else if unsigned {
+        builder.build_int_z_extend(value, i64_type, "idxtmp")
+    } else {
+        builder.build_int_s_extend(value, i64_type, "idxtmp")
+    }
+}
+
+/// Aborts the program with the location of the token when `valid` is false,
+/// the message is a printf format for the arguments
+pub fn build_runtime_check<'a>(context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, valid: IntValue<'a>, token: &Token, message: &str, arguments: &[BasicMetadataValueEnum<'a>]) {
+    let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+    let fail_block = context.append_basic_block(function, "checkfail");
+    let ok_block = context.append_basic_block(function, "checkok");
+    builder.build_conditional_branch(valid, ok_block, fail_block);
+
+    // Message is written to stderr
+    builder.position_at_end(fail_block);
+    let file_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let fprintf = match module.get_function("fprintf") {
+        Some(fprintf) => fprintf,
+        None => module.add_function("fprintf", context.i32_type().fn_type(&[file_type.into(), file_type.into()], true), Some(Linkage::External)),
+    };
+    let format = format!("Error: {} at line {} and column {}\n", message, token.line, token.column);
+    let format = builder.build_global_string_ptr(&format, "errfmt").as_pointer_value();
+    let mut call_arguments: Vec<BasicMetadataValueEnum<'a>> = vec![build_stderr(context, module, builder).into(), format.into()];
+    call_arguments.extend_from_slice(arguments);
+    builder.build_call(fprintf, &call_arguments, "");
+    let abort = match module.get_function("abort") {
+        Some(abort) => abort,
+        None => module.add_function("abort", context.void_type().fn_type(&[], false), Some(Linkage::External)),
+    };
+    builder.build_call(abort, &[], "");
+    builder.build_unreachable();
+
+    builder.position_at_end(ok_block);
+}
+
+/// `FILE` of stderr, the C libraries of the host name it differently
+fn build_stderr<'a>(context: &'a Context, module: &Module<'a>, builder: &Builder<'a>) -> PointerValue<'a> {
+    let file_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    if cfg!(windows) {
+        let iob = match module.get_function("__acrt_iob_func") {
+            Some(iob) => iob,
+            None => module.add_function("__acrt_iob_func", file_type.fn_type(&[context.i32_type().into()], false), Some(Linkage::External)),
+        };
+        let call = builder.build_call(iob, &[context.i32_type().const_int(2, false).into()], "stderr");
+        return call.try_as_basic_value().left().unwrap().into_pointer_value();
+    }
+    let name = if cfg!(target_os = "macos") { "__stderrp" } else { "stderr" };
+    let stderr = match module.get_global(name) {
+        Some(stderr) => stderr,
+        None => {
+            let stderr = module.add_global(file_type, None, name);
+            stderr.set_linkage(Linkage::External);
+            stderr
+        }
+    };
+    builder.build_load(stderr.as_pointer_value(), "stderr").into_pointer_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+
+    use crate::compiler::{checker::check_source, compiler::Compiler, profile::Profile};
+
+    #[test]
+    fn check_indexing() {
+        assert!(check_source("fun f(s: str, a: i32[3]): u8 { var i = 2; s[a[i] as u8] }").is_empty());
+        assert_eq!(check_source("fun f(): i32 { var a = [1, 2, 3]; a[3] }"), vec!["TypeError: Index out of bounds"]);
+        assert_eq!(check_source("fun f(s: str): u8 { s[1.5] }"), vec!["TypeError: Index has to be an integer"]);
+        assert_eq!(check_source("fun f(x: i32): void { x[0]; }"), vec!["TypeError: Value can not be indexed"]);
+        assert_eq!(check_source("fun f(x: i32): void { x[0..1]; }"), vec!["TypeError: Value can not be sliced"]);
+    }
+
+    #[test]
+    fn run_bounds_checks_follow_profile() {
+        let source = "fun main(): i32 { var a = [1, 2, 3]; var i = 1; a[i] }";
+        for (profile, checked) in [(Profile::debug(), true), (Profile::release(), false)] {
+            let tokens = crate::lexer::lexer::tokenize(source);
+            let context = Context::create();
+            let compiler = Compiler::new(&context, context.create_module("test"), profile);
+            compiler.compile(crate::parser::parser::parse(&tokens).unwrap()).unwrap();
+            let ir = compiler.to_llvm_ir();
+            assert_eq!(ir.contains("fprintf"), checked);
+            // Variables are indexed in place, without a copy of the array
+            assert!(!ir.contains("arrmem"));
+        }
+    }
+}
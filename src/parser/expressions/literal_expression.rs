@@ -2,14 +2,14 @@ use std::sync::Arc;
 
 use inkwell::values::AnyValueEnum;
 
-use crate::{lexer::lexer::{TokenType, Token}, try_parse, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
+use crate::{compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::{TokenType, Token}, try_parse, utils::{error::Error, error_components::token_component::ErrorTokenComponent}, error};
 
-use super::{ASTExpr, Parseable, scope::ScopeManager};
+use super::{ASTExpr, Parseable, data_types::DataType, scope::ScopeManager, string_expression::{StringLiteralExpr, unescape}, array_expression::ArrayLiteralExpr};
 
 pub struct LiteralExpr {}
 impl Parseable for LiteralExpr {
     fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
-        try_parse!(tokens, *pos, IntegerLiteralExpr)
+        try_parse!(tokens, *pos, IntegerLiteralExpr StringLiteralExpr CharLiteralExpr BoolLiteralExpr ArrayLiteralExpr)
     }
 }
 
@@ -27,15 +27,36 @@ enum NumberValue {
     F64(f64),
 }
 
+impl NumberValue {
+    fn data_type(&self) -> DataType {
+        match self {
+            NumberValue::I8(_) => DataType::I8,
+            NumberValue::I16(_) => DataType::I16,
+            NumberValue::I32(_) => DataType::I32,
+            NumberValue::I64(_) => DataType::I64,
+            NumberValue::U8(_) => DataType::U8,
+            NumberValue::U16(_) => DataType::U16,
+            NumberValue::U32(_) => DataType::U32,
+            NumberValue::U64(_) => DataType::U64,
+            NumberValue::F32(_) => DataType::F32,
+            NumberValue::F64(_) => DataType::F64,
+        }
+    }
+}
+
 pub struct IntegerLiteralExpr {
     value: NumberValue,
 }
 
 impl Parseable for IntegerLiteralExpr {
     fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
-        match &tokens[*pos].token_type {
+        // Could be a negative number, like `-5`
+        let negative = tokens[*pos].token_type == TokenType::Operator("-".to_string()) && matches!(tokens[*pos + 1].token_type, TokenType::Number(_));
+        let number = if negative { &tokens[*pos + 1] } else { &tokens[*pos] };
+        match &number.token_type {
             TokenType::Number(value) => {
-                *pos += 1;
+                *pos += if negative { 2 } else { 1 };
+                let value = &if negative { format!("-{}", value) } else { value.clone() };
 
                 if value.contains('f') || value.contains('u') || value.contains('i') {
                     let s: String;
@@ -83,6 +104,30 @@ impl ASTExpr for IntegerLiteralExpr {
         format!("{:?}", self.value)
     }
 
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        self.value.data_type()
+    }
+
+    fn check(&self, _checker: &mut TypeChecker) -> DataType {
+        self.value.data_type()
+    }
+
+    fn evaluate(&self, _checker: &TypeChecker) -> Option<Constant> {
+        let data_type = self.value.data_type();
+        Some(match self.value {
+            NumberValue::I8(value) => Constant::int(value as i128, data_type),
+            NumberValue::I16(value) => Constant::int(value as i128, data_type),
+            NumberValue::I32(value) => Constant::int(value as i128, data_type),
+            NumberValue::I64(value) => Constant::int(value as i128, data_type),
+            NumberValue::U8(value) => Constant::int(value as i128, data_type),
+            NumberValue::U16(value) => Constant::int(value as i128, data_type),
+            NumberValue::U32(value) => Constant::int(value as i128, data_type),
+            NumberValue::U64(value) => Constant::int(value as i128, data_type),
+            NumberValue::F32(value) => Constant::float(value as f64, data_type),
+            NumberValue::F64(value) => Constant::float(value, data_type),
+        })
+    }
+
     fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &inkwell::builder::Builder, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
         match self.value {
             NumberValue::I8(value) => Some(AnyValueEnum::IntValue(context.i8_type().const_int(value as u64, true))),
@@ -99,9 +144,95 @@ impl ASTExpr for IntegerLiteralExpr {
     }
 }
 
+pub struct BoolLiteralExpr {
+    value: bool,
+}
+
+impl Parseable for BoolLiteralExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let value = match tokens[*pos].token_type {
+            TokenType::Identifier(ref s) if s == "true" => true,
+            TokenType::Identifier(ref s) if s == "false" => false,
+            _ => return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing bool literal",
+                                   ErrorTokenComponent::new("Expected 'true' or 'false'".to_string(), Some(tokens[*pos].clone()))))
+        };
+        *pos += 1;
+        Ok(Box::new(BoolLiteralExpr { value }))
+    }
+}
+
+impl ASTExpr for BoolLiteralExpr {
+    fn to_string(&self) -> String {
+        format!("Bool({})", self.value)
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::Bool
+    }
+
+    fn check(&self, _checker: &mut TypeChecker) -> DataType {
+        DataType::Bool
+    }
+
+    fn evaluate(&self, _checker: &TypeChecker) -> Option<Constant> {
+        Some(Constant::Bool(self.value))
+    }
+
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, _module: &inkwell::module::Module<'a>, _builder: &inkwell::builder::Builder, _scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        Some(AnyValueEnum::IntValue(context.bool_type().const_int(self.value as u64, false)))
+    }
+}
+
+pub struct CharLiteralExpr {
+    value: char,
+}
+
+impl Parseable for CharLiteralExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let token = tokens[*pos].clone();
+        let literal = match token.token_type {
+            TokenType::Char(ref literal) => literal,
+            _ => return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing char literal",
+                                   ErrorTokenComponent::new("Expected char literal".to_string(), Some(token.clone()))))
+        };
+
+        // Token still contains the quotes, there has to be exactly one character between them
+        *pos += 1;
+        let mut chars = unescape(&literal[1..literal.len() - 1], &token)?.chars().collect::<Vec<char>>();
+        if chars.len() != 1 {
+            return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing char literal",
+                              ErrorTokenComponent::new("Expected a single character".to_string(), Some(token.clone()))));
+        }
+        Ok(Box::new(CharLiteralExpr { value: chars.pop().unwrap() }))
+    }
+}
+
+impl ASTExpr for CharLiteralExpr {
+    fn to_string(&self) -> String {
+        format!("Char({:?})", self.value)
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::Char
+    }
+
+    fn check(&self, _checker: &mut TypeChecker) -> DataType {
+        DataType::Char
+    }
+
+    fn evaluate(&self, _checker: &TypeChecker) -> Option<Constant> {
+        Some(Constant::int(self.value as i128, DataType::Char))
+    }
+
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, _module: &inkwell::module::Module<'a>, _builder: &inkwell::builder::Builder, _scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        Some(AnyValueEnum::IntValue(context.i32_type().const_int(self.value as u64, false)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{lexer::lexer::{TokenType}, parser::expressions::{literal_expression::IntegerLiteralExpr, Parseable}, test_token};
+    use crate::{lexer::lexer::{TokenType}, parser::expressions::{literal_expression::{IntegerLiteralExpr, LiteralExpr}, Parseable}, test_token};
+    use crate::compiler::{checker::check_source, compiler::run_source};
 
     #[test]
     fn parse_i32_number() {
@@ -119,4 +250,50 @@ mod tests {
         assert_eq!(expr.to_string(), "F64(123.456)");
     }
 
+    #[test]
+    fn parse_negative_number() {
+        let tokens = vec![
+            test_token!(TokenType::Operator("-".to_string())),
+            test_token!(TokenType::Number("128i8".to_string())),
+        ];
+        let mut pos = 0;
+        let expr = IntegerLiteralExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "I8(-128)");
+        assert_eq!(pos, 2);
+    }
+
+    #[test]
+    fn parse_bool_and_char() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("true".to_string())),
+            test_token!(TokenType::Char(r"'\n'".to_string())),
+            test_token!(TokenType::Char(r"'\u{e9}'".to_string())),
+            test_token!(TokenType::Identifier("falsey".to_string())),
+        ];
+        let mut pos = 0;
+        assert_eq!(LiteralExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Bool(true)");
+        assert_eq!(LiteralExpr::parse(&tokens, &mut pos).unwrap().to_string(), r"Char('\n')");
+        assert_eq!(LiteralExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Char('\u{e9}')");
+        assert!(LiteralExpr::parse(&tokens, &mut pos).is_err());
+        assert_eq!(pos, 3);
+    }
+
+    #[test]
+    fn check_bools_and_chars() {
+        assert!(check_source("fun f(c: char): bool { var d = c + 1 as char; d - 'a' > 'z' && c as u8 == 'x' as u8 }").is_empty());
+        assert_eq!(check_source("fun f(c: char): char { c % 'a' }"), vec!["TypeError: Values can not be used in math operation"]);
+        assert_eq!(check_source("fun f(c: char): char { c + 1 }"), vec!["TypeError: Binary operation types do not match"]);
+        assert_eq!(check_source("fun f(b: bool): bool { b > false }"), vec!["TypeError: Bools can only be compared for equality"]);
+    }
+
+    #[test]
+    fn run_bools_and_chars() {
+        assert_eq!(run_source(r"fun main(): i32 { var c = 'a'; c = c + 2 as char; var big = c >= 'c' && true; c as i32 + big as i32 + ('\u{1F600}' as i32 - 128512) }"), 100);
+        assert_eq!(run_source("fun main(): i32 { const yes = !false; if (yes) { 1 } else { 2 } }"), 1);
+    }
+
+    #[test]
+    fn run_char_arithmetic() {
+        assert_eq!(run_source("fun main(): i32 { var c = 'z' - 'a' + 'A'; (c as i32) + ('d' * 2 as char / 'b') as i32 }"), 92);
+    }
 }
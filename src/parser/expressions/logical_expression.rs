@@ -0,0 +1,164 @@
+use inkwell::{builder::Builder, values::{AnyValue, AnyValueEnum}};
+
+use crate::{compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::Token};
+
+use super::{ASTExpr, if_expression::build_condition, data_types::DataType, scope::ScopeManager};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogicalOperation {
+    AND,
+    OR,
+}
+
+pub struct LogicalExpr {
+    token: Token,
+    lhs: Box<dyn ASTExpr>,
+    rhs: Box<dyn ASTExpr>,
+    operation: LogicalOperation,
+}
+
+impl LogicalExpr {
+    pub fn new(token: Token, lhs: Box<dyn ASTExpr>, operation: LogicalOperation, rhs: Box<dyn ASTExpr>) -> LogicalExpr {
+        LogicalExpr { token, lhs, rhs, operation }
+    }
+}
+
+impl ASTExpr for LogicalExpr {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let lhs = self.lhs.generate(context, module, builder, scope_manager);
+        let lhs = build_condition(builder, lhs, &self.token);
+        let lhs_end = builder.get_insert_block().unwrap();
+
+        // RHS is only evaluated if LHS does not decide the result
+        let function = lhs_end.get_parent().unwrap();
+        let rhs_block = context.append_basic_block(function, "logic_rhs");
+        let merge_block = context.append_basic_block(function, "logic_merge");
+        let short_circuit = match self.operation {
+            LogicalOperation::AND => {
+                builder.build_conditional_branch(lhs, rhs_block, merge_block);
+                context.bool_type().const_int(0, false)
+            }
+            LogicalOperation::OR => {
+                builder.build_conditional_branch(lhs, merge_block, rhs_block);
+                context.bool_type().const_int(1, false)
+            }
+        };
+
+        builder.position_at_end(rhs_block);
+        let rhs = self.rhs.generate(context, module, builder, scope_manager);
+        let rhs = build_condition(builder, rhs, &self.token);
+        let rhs_end = builder.get_insert_block().unwrap();
+        builder.build_unconditional_branch(merge_block);
+
+        builder.position_at_end(merge_block);
+        let phi = builder.build_phi(context.bool_type(), "logictmp");
+        phi.add_incoming(&[(&short_circuit, lhs_end), (&rhs, rhs_end)]);
+        Some(phi.as_any_value_enum())
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::Bool
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        checker.check_condition(self.lhs.as_ref(), &self.token);
+        checker.check_condition(self.rhs.as_ref(), &self.token);
+        DataType::Bool
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        // Same as at runtime, RHS is not evaluated if LHS decides the result
+        let lhs = self.lhs.evaluate(checker)?.is_true()?;
+        match (self.operation, lhs) {
+            (LogicalOperation::AND, false) | (LogicalOperation::OR, true) => Some(Constant::Bool(lhs)),
+            _ => Some(Constant::Bool(self.rhs.evaluate(checker)?.is_true()?)),
+        }
+    }
+
+    fn to_string(&self) -> String {
+        format!("{:?} {}, {}", self.operation, self.lhs.to_string(), self.rhs.to_string())
+    }
+}
+
+pub struct NotExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+}
+
+impl NotExpr {
+    pub fn new(token: Token, value: Box<dyn ASTExpr>) -> NotExpr {
+        NotExpr { token, value }
+    }
+}
+
+impl ASTExpr for NotExpr {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let value = self.value.generate(context, module, builder, scope_manager);
+        let value = build_condition(builder, value, &self.token);
+        Some(AnyValueEnum::IntValue(builder.build_not(value, "nottmp")))
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::Bool
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        checker.check_condition(self.value.as_ref(), &self.token);
+        DataType::Bool
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        Some(Constant::Bool(!self.value.evaluate(checker)?.is_true()?))
+    }
+
+    fn to_string(&self) -> String {
+        format!("NOT {}", self.value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_token, lexer::lexer::TokenType, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::compiler::run_source;
+
+    #[test]
+    fn parse_and_binds_tighter_than_or() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("a".to_string())),
+            test_token!(TokenType::Operator("||".to_string())),
+            test_token!(TokenType::Identifier("b".to_string())),
+            test_token!(TokenType::Operator("&&".to_string())),
+            test_token!(TokenType::Identifier("c".to_string())),
+            test_token!(TokenType::Operator("||".to_string())),
+            test_token!(TokenType::Identifier("d".to_string())),
+            test_token!(TokenType::Separator(';'))
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "OR OR VarCall a, AND VarCall b, VarCall c, VarCall d");
+    }
+
+    #[test]
+    fn parse_logic_of_comparisons() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator(">".to_string())),
+            test_token!(TokenType::Number("1".to_string())),
+            test_token!(TokenType::Operator("&&".to_string())),
+            test_token!(TokenType::Operator("!".to_string())),
+            test_token!(TokenType::Identifier("y".to_string())),
+            test_token!(TokenType::Separator(';'))
+        ];
+        let mut pos = 0;
+        let expr = ValueExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "AND GREATER VarCall x, I32(1), NOT VarCall y");
+    }
+
+    #[test]
+    fn run_short_circuit() {
+        // Division by zero would trap if the RHS was evaluated
+        assert_eq!(run_source("fun main(): i32 { var x: i32 = 0; if (x != 0 && 10 / x > 1) { 1 } else { 2 } }"), 2);
+        assert_eq!(run_source("fun main(): i32 { if (1 < 2 || 1 / 0 > 1) { 1 } else { 2 } }"), 1);
+        assert_eq!(run_source("fun main(): i32 { if (!0) { 1 } else { 2 } }"), 1);
+    }
+}
@@ -0,0 +1,538 @@
+use std::rc::Rc;
+
+use inkwell::{IntPredicate, basic_block::BasicBlock, builder::Builder, context::Context, module::Module, values::AnyValueEnum};
+
+use crate::{try_parse, compiler::checker::{TypeChecker, BindingKind}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, assignment_expression::AssignExpr, basic_expression::BasicExpr, if_expression::{parse_condition, build_condition}, return_expression::is_terminated, value_expression::ValueExpr, range_expression::range_parts, variable_definition_expression::build_entry_alloca, data_types::DataType, scope::{ScopeManager, Variable}};
+
+pub struct LoopExpr {
+    token: Token,
+    condition: Option<Box<dyn ASTExpr>>,
+    body: Box<dyn ASTExpr>,
+}
+
+impl Parseable for LoopExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with keyword "loop"
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("loop".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing loop",
+                              ErrorTokenComponent::new("Expected 'loop' keyword".to_string(), Some(token))));
+        }
+
+        // Could be followed by a condition
+        *pos += 1;
+        let condition = if tokens[*pos].token_type == TokenType::Paren('(') {
+            Some(parse_condition(tokens, pos, "loop")?)
+        } else {
+            None
+        };
+
+        // Should be followed by a body
+        let body = parse_body(tokens, pos)?;
+
+        Ok(Box::new(LoopExpr {
+            token,
+            condition,
+            body,
+        }))
+    }
+}
+
+impl ASTExpr for LoopExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let blocks = begin_loop(context, builder);
+        match self.condition {
+            Some(ref condition) => {
+                let condition = condition.generate(context, module, builder, scope_manager);
+                let condition = build_condition(builder, condition, &self.token);
+                builder.build_conditional_branch(condition, blocks.body, blocks.exit);
+            }
+            None => { builder.build_unconditional_branch(blocks.body); }
+        }
+        let broken = generate_body(context, module, builder, scope_manager, &blocks, self.body.as_ref());
+        end_loop(builder, &blocks, self.condition.is_none() && !broken);
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        if let Some(ref condition) = self.condition {
+            checker.check_condition(condition.as_ref(), &self.token);
+        }
+        let broken = check_body(checker, self.body.as_ref());
+        loop_type(self.condition.is_none() && !broken)
+    }
+
+    fn to_string(&self) -> String {
+        match self.condition {
+            Some(ref condition) => format!("Loop {} {}", condition.to_string(), self.body.to_string()),
+            None => format!("Loop {}", self.body.to_string()),
+        }
+    }
+}
+
+pub struct ForExpr {
+    token: Token,
+    init: Option<Box<dyn ASTExpr>>,
+    condition: Option<Box<dyn ASTExpr>>,
+    step: Option<Box<dyn ASTExpr>>,
+    body: Box<dyn ASTExpr>,
+}
+
+pub struct ForRangeExpr {
+    token: Token,
+    name: String,
+    range: Box<dyn ASTExpr>,
+    body: Box<dyn ASTExpr>,
+}
+
+impl Parseable for ForExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with keyword "for"
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("for".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing for loop",
+                              ErrorTokenComponent::new("Expected 'for' keyword".to_string(), Some(token))));
+        }
+
+        // Should be followed by a parenthesis
+        *pos += 1;
+        expect(tokens, pos, TokenType::Paren('('), "Expected '(' after 'for'")?;
+
+        // Range loop, like `for (i in 0..n)` or `for (i in r)`
+        if matches!(tokens[*pos].token_type, TokenType::Identifier(_)) && tokens[*pos + 1].token_type == TokenType::Identifier("in".to_string()) {
+            return ForRangeExpr::parse_range(tokens, pos, token);
+        }
+
+        // Otherwise it is `for (init; condition; step)`, every part can be left out
+        let init = parse_part(tokens, pos, TokenType::Separator(';'))?;
+        expect(tokens, pos, TokenType::Separator(';'), "Expected ';' after loop initialization")?;
+        let condition = parse_part(tokens, pos, TokenType::Separator(';'))?;
+        expect(tokens, pos, TokenType::Separator(';'), "Expected ';' after loop condition")?;
+        let step = parse_part(tokens, pos, TokenType::Paren(')'))?;
+        expect(tokens, pos, TokenType::Paren(')'), "Expected ')'")?;
+
+        // Should be followed by a body
+        let body = parse_body(tokens, pos)?;
+
+        Ok(Box::new(ForExpr {
+            token,
+            init,
+            condition,
+            step,
+            body,
+        }))
+    }
+}
+
+impl ASTExpr for ForExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        // Variables from the initialization only exist in the loop
+        scope_manager.create_scope();
+        if let Some(ref init) = self.init {
+            init.generate(context, module, builder, scope_manager);
+        }
+
+        let blocks = begin_loop(context, builder);
+        match self.condition {
+            Some(ref condition) => {
+                let condition = condition.generate(context, module, builder, scope_manager);
+                let condition = build_condition(builder, condition, &self.token);
+                builder.build_conditional_branch(condition, blocks.body, blocks.exit);
+            }
+            None => { builder.build_unconditional_branch(blocks.body); }
+        }
+        let broken = generate_body(context, module, builder, scope_manager, &blocks, self.body.as_ref());
+        if let Some(ref step) = self.step {
+            step.generate(context, module, builder, scope_manager);
+        }
+        end_loop(builder, &blocks, self.condition.is_none() && !broken);
+
+        scope_manager.exit_scope();
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        checker.enter_scope();
+        if let Some(ref init) = self.init {
+            init.check(checker);
+        }
+        if let Some(ref condition) = self.condition {
+            checker.check_condition(condition.as_ref(), &self.token);
+        }
+        let broken = check_body(checker, self.body.as_ref());
+        if let Some(ref step) = self.step {
+            step.check(checker);
+        }
+        checker.exit_scope();
+        loop_type(self.condition.is_none() && !broken)
+    }
+
+    fn to_string(&self) -> String {
+        let part = |part: &Option<Box<dyn ASTExpr>>| part.as_ref().map_or("NOP".to_string(), |p| p.to_string());
+        format!("For {}; {}; {} {}", part(&self.init), part(&self.condition), part(&self.step), self.body.to_string())
+    }
+}
+
+impl ForRangeExpr {
+    fn parse_range(tokens: &Vec<Token>, pos: &mut usize, token: Token) -> Result<Box<dyn ASTExpr>, Error> {
+        // Name of the variable and "in" were already checked
+        let name = match tokens[*pos].token_type {
+            TokenType::Identifier(ref name) => name.clone(),
+            _ => unreachable!(),
+        };
+        *pos += 2;
+
+        // Should be followed by a range, like `0..n`, `0..=n` or a range variable
+        let start = *pos;
+        let range = match ValueExpr::parse(tokens, pos) {
+            Ok(range) => range,
+            Err(err) if *pos > start => return Err(err),
+            Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing for loop",
+                                        ErrorTokenComponent::new("Expected range".to_string(), Some(tokens[*pos].clone())))),
+        };
+        expect(tokens, pos, TokenType::Paren(')'), "Expected ')'")?;
+
+        // Should be followed by a body
+        let body = parse_body(tokens, pos)?;
+
+        Ok(Box::new(ForRangeExpr {
+            token,
+            name,
+            range,
+            body,
+        }))
+    }
+}
+
+impl ASTExpr for ForRangeExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        // Range is evaluated once, before the loop
+        let (start, end, inclusive) = match self.range.generate(context, module, builder, scope_manager) {
+            Some(AnyValueEnum::StructValue(range)) => range_parts(builder, range),
+            _ => error!(ErrorKind::CompilerError, "For loop needs a range",
+                        ErrorTokenComponent::new("Value is not a range".to_string(), Some(self.token.clone()))).panic(),
+        };
+
+        // Loop variable only exists in the loop
+        scope_manager.create_scope();
+        let variable = build_entry_alloca(context, builder, start.get_type().into(), &self.name);
+        builder.build_store(variable, start);
+        scope_manager.scope.fn_args.remove(&self.name);
+        scope_manager.scope.constants.remove(&self.name);
+        let data_type = match self.range.data_type(scope_manager) {
+            DataType::Range(element) if *element != DataType::Unknown => *element,
+            _ => DataType::from_int_type(start.get_type()),
+        };
+        let unsigned = data_type.is_unsigned();
+        scope_manager.scope.variables.insert(self.name.to_string(), Rc::new(Variable { pointer: variable, data_type }));
+
+        let blocks = begin_loop(context, builder);
+        let current = builder.build_load(variable, &self.name).into_int_value();
+        // End is only reached when the range includes it
+        let predicate = if unsigned { IntPredicate::ULT } else { IntPredicate::SLT };
+        let below = builder.build_int_compare(predicate, current, end, "rangebelow");
+        let at_end = builder.build_int_compare(IntPredicate::EQ, current, end, "rangeatend");
+        let at_end = builder.build_and(inclusive, at_end, "rangeincl");
+        let condition = builder.build_or(below, at_end, "rangecond");
+        builder.build_conditional_branch(condition, blocks.body, blocks.exit);
+
+        generate_body(context, module, builder, scope_manager, &blocks, self.body.as_ref());
+        // Inclusive range stops at its end, incrementing could wrap past the type's maximum
+        let current = builder.build_load(variable, &self.name).into_int_value();
+        let at_end = builder.build_int_compare(IntPredicate::EQ, current, end, "rangeatend");
+        let at_end = builder.build_and(inclusive, at_end, "rangelast");
+        let function = blocks.latch.get_parent().unwrap();
+        let step = context.append_basic_block(function, "range_step");
+        builder.build_conditional_branch(at_end, blocks.exit, step);
+        builder.position_at_end(step);
+        let next = builder.build_int_add(current, start.get_type().const_int(1, false), "rangenext");
+        builder.build_store(variable, next);
+        end_loop(builder, &blocks, false);
+
+        scope_manager.exit_scope();
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let data_type = match self.range.check(checker) {
+            DataType::Range(element) => *element,
+            DataType::Unknown => DataType::Unknown,
+            found => {
+                checker.error(error!(ErrorKind::TypeError, "For loop needs a range",
+                                     ErrorTokenComponent::new("Value is not a range".to_string(), Some(self.token.clone()))
+                                     NameErrorComponent::new(format!("Found: {}", found))));
+                DataType::Unknown
+            }
+        };
+
+        // Loop variable only exists in the loop
+        checker.enter_scope();
+        checker.define(&self.name, data_type, BindingKind::Variable);
+        check_body(checker, self.body.as_ref());
+        checker.exit_scope();
+        DataType::Void
+    }
+
+    fn to_string(&self) -> String {
+        format!("For {} in {} {}", self.name, self.range.to_string(), self.body.to_string())
+    }
+}
+
+pub struct BreakExpr {
+    token: Token,
+}
+
+pub struct ContinueExpr {
+    token: Token,
+}
+
+impl Parseable for BreakExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("break".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing break",
+                              ErrorTokenComponent::new("Expected 'break' keyword".to_string(), Some(token))));
+        }
+        *pos += 1;
+        Ok(Box::new(BreakExpr { token }))
+    }
+}
+
+impl ASTExpr for BreakExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        match scope_manager.loops.last_mut() {
+            Some(current) => {
+                current.broken = true;
+                builder.build_unconditional_branch(current.break_block);
+            }
+            None => error!(ErrorKind::CompilerError, "Break outside of a loop",
+                           ErrorTokenComponent::new("'break' can only be used inside a loop".to_string(), Some(self.token.clone()))).panic()
+        }
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        if !checker.break_loop() {
+            checker.error(error!(ErrorKind::TypeError, "Break outside of a loop",
+                                 ErrorTokenComponent::new("'break' can only be used inside a loop".to_string(), Some(self.token.clone()))));
+        }
+        DataType::Never
+    }
+
+    fn to_string(&self) -> String {
+        "Break".to_string()
+    }
+}
+
+impl Parseable for ContinueExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("continue".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing continue",
+                              ErrorTokenComponent::new("Expected 'continue' keyword".to_string(), Some(token))));
+        }
+        *pos += 1;
+        Ok(Box::new(ContinueExpr { token }))
+    }
+}
+
+impl ASTExpr for ContinueExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        match scope_manager.loops.last() {
+            Some(current) => { builder.build_unconditional_branch(current.continue_block); }
+            None => error!(ErrorKind::CompilerError, "Continue outside of a loop",
+                           ErrorTokenComponent::new("'continue' can only be used inside a loop".to_string(), Some(self.token.clone()))).panic()
+        }
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        if !checker.in_loop() {
+            checker.error(error!(ErrorKind::TypeError, "Continue outside of a loop",
+                                 ErrorTokenComponent::new("'continue' can only be used inside a loop".to_string(), Some(self.token.clone()))));
+        }
+        DataType::Never
+    }
+
+    fn to_string(&self) -> String {
+        "Continue".to_string()
+    }
+}
+
+/// Blocks of a loop, header checks the condition and latch prepares the next iteration
+struct LoopBlocks<'a> {
+    header: BasicBlock<'a>,
+    body: BasicBlock<'a>,
+    latch: BasicBlock<'a>,
+    exit: BasicBlock<'a>,
+}
+
+/// Creates blocks of the loop and positions the builder in the header
+fn begin_loop<'a>(context: &'a Context, builder: &Builder<'a>) -> LoopBlocks<'a> {
+    let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+    let blocks = LoopBlocks {
+        header: context.append_basic_block(function, "loop_header"),
+        body: context.append_basic_block(function, "loop_body"),
+        latch: context.append_basic_block(function, "loop_latch"),
+        exit: context.append_basic_block(function, "loop_exit"),
+    };
+    builder.build_unconditional_branch(blocks.header);
+    builder.position_at_end(blocks.header);
+    blocks
+}
+
+/// Generates the body and positions the builder in the latch, returns whether the body contains a `break`
+fn generate_body<'a, 'b>(context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>, blocks: &LoopBlocks<'a>, body: &dyn ASTExpr) -> bool {
+    builder.position_at_end(blocks.body);
+    scope_manager.enter_loop(blocks.latch, blocks.exit);
+    scope_manager.create_scope();
+    body.generate(context, module, builder, scope_manager);
+    scope_manager.exit_scope();
+    let current = scope_manager.exit_loop();
+    if !is_terminated(builder) {
+        builder.build_unconditional_branch(blocks.latch);
+    }
+    builder.position_at_end(blocks.latch);
+    current.broken
+}
+
+/// Jumps back to the header and continues after the loop
+fn end_loop(builder: &Builder, blocks: &LoopBlocks, infinite: bool) {
+    builder.build_unconditional_branch(blocks.header);
+    builder.position_at_end(blocks.exit);
+    // Nothing continues after a loop that is never exited
+    if infinite {
+        builder.build_unreachable();
+    }
+}
+
+/// Checks the body of a loop in its own scope, returns whether the body contains a `break`
+fn check_body(checker: &mut TypeChecker, body: &dyn ASTExpr) -> bool {
+    checker.enter_loop();
+    checker.enter_scope();
+    body.check(checker);
+    checker.exit_scope();
+    checker.exit_loop()
+}
+
+/// Loop without a condition and a `break` never finishes
+fn loop_type(infinite: bool) -> DataType {
+    if infinite { DataType::Never } else { DataType::Void }
+}
+
+/// Errors inside of the body are kept, they are more precise
+fn parse_body(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+    let start = *pos;
+    match BasicExpr::parse(tokens, pos) {
+        Ok(body) => Ok(body),
+        Err(err) if *pos > start => Err(err),
+        Err(_) => Err(error!(ErrorKind::ParserError, "Error while parsing loop body",
+                             ErrorTokenComponent::new("Expected body".to_string(), Some(tokens[*pos].clone())))),
+    }
+}
+
+fn parse_part(tokens: &Vec<Token>, pos: &mut usize, end: TokenType) -> Result<Option<Box<dyn ASTExpr>>, Error> {
+    if tokens[*pos].token_type == end {
+        return Ok(None);
+    }
+    let start = *pos;
+    match try_parse!(tokens, *pos, AssignExpr ValueExpr) {
+        Ok(part) => Ok(Some(part)),
+        Err(err) if *pos > start => Err(err),
+        Err(_) => Err(error!(ErrorKind::ParserError, "Error while parsing for loop",
+                             ErrorTokenComponent::new("Expected expression".to_string(), Some(tokens[*pos].clone())))),
+    }
+}
+
+fn expect(tokens: &Vec<Token>, pos: &mut usize, token_type: TokenType, message: &str) -> Result<(), Error> {
+    if tokens[*pos].token_type != token_type {
+        return Err(error!(ErrorKind::ParserError, "Error while parsing for loop",
+                          ErrorTokenComponent::new(message.to_string(), Some(tokens[*pos].clone()))));
+    }
+    *pos += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::TokenType, test_token, parser::expressions::{Parseable, basic_expression::BasicExpr}};
+    use crate::compiler::compiler::run_source;
+
+    #[test]
+    fn parse_conditional_loop() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("loop".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Identifier("break".to_string())),
+            test_token!(TokenType::Separator(';')),
+            test_token!(TokenType::Brace('}')),
+            test_token!(TokenType::EOF),
+        ];
+        let mut pos = 0;
+        let expr = BasicExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Loop VarCall x {\n\t Break \n}");
+        assert_eq!(pos, 8);
+    }
+
+    #[test]
+    fn parse_range_for() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("for".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Identifier("i".to_string())),
+            test_token!(TokenType::Identifier("in".to_string())),
+            test_token!(TokenType::Number("0".to_string())),
+            test_token!(TokenType::Operator("..=".to_string())),
+            test_token!(TokenType::Identifier("n".to_string())),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Identifier("continue".to_string())),
+            test_token!(TokenType::Separator(';')),
+            test_token!(TokenType::EOF),
+        ];
+        let mut pos = 0;
+        let expr = super::ForExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "For i in I32(0)..=VarCall n Continue");
+    }
+
+    #[test]
+    fn parse_c_style_for() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("for".to_string())),
+            test_token!(TokenType::Paren('(')),
+            test_token!(TokenType::Identifier("var".to_string())),
+            test_token!(TokenType::Identifier("i".to_string())),
+            test_token!(TokenType::Separator(':')),
+            test_token!(TokenType::Identifier("i32".to_string())),
+            test_token!(TokenType::Operator("=".to_string())),
+            test_token!(TokenType::Number("0".to_string())),
+            test_token!(TokenType::Separator(';')),
+            test_token!(TokenType::Identifier("i".to_string())),
+            test_token!(TokenType::Operator("<".to_string())),
+            test_token!(TokenType::Number("3".to_string())),
+            test_token!(TokenType::Separator(';')),
+            test_token!(TokenType::Paren(')')),
+            test_token!(TokenType::Brace('{')),
+            test_token!(TokenType::Brace('}')),
+            test_token!(TokenType::EOF),
+        ];
+        let mut pos = 0;
+        let expr = super::ForExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "For Var I32 i = I32(0); LESS VarCall i, I32(3); NOP {\n\t  \n}");
+    }
+
+    #[test]
+    fn run_loops() {
+        assert_eq!(run_source("fun main(): i32 { loop { return 4; } }"), 4);
+        assert_eq!(run_source("fun main(): i32 { loop (0) { return 1; } 2 }"), 2);
+        assert_eq!(run_source("fun main(): i32 { for (i in 0..5) { if (i == 3) { return i; } } 0 }"), 3);
+        assert_eq!(run_source("fun main(): i32 { for (i in 0..=10) { if (i < 10) { continue; } return i; } 0 }"), 10);
+        assert_eq!(run_source("fun main(): i32 { for (;;) { break; } 5 }"), 5);
+        assert_eq!(run_source("fun main(): i32 { var n = 0; for (i in 0u8..=255u8) { n += 1; } n }"), 256);
+        assert_eq!(run_source("fun main(): i32 { var n = 0; for (i in 2147483646..=2147483647) { n += 1; } n }"), 2);
+    }
+}
@@ -0,0 +1,602 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use inkwell::{IntPredicate, basic_block::BasicBlock, builder::Builder, context::Context, module::Module, values::{AnyValue, AnyValueEnum, BasicValueEnum, IntValue, StructValue}};
+
+use crate::{try_parse, compiler::{checker::{TypeChecker, BindingKind, compatible}, constant::{Constant, bounds}}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, data_types::{DataType, ToBasic}, return_expression::{ReturnExpr, is_terminated}, loop_expression::{BreakExpr, ContinueExpr}, value_expression::{ValueExpr, parse_operators, precedence}, enum_expression::{build_tag, build_variant_fields}, tuple_expression::build_tuple_field, variable_definition_expression::build_entry_alloca, scope::{ScopeManager, Variable, Enum}};
+
+/// Compares the value with patterns of every arm and evaluates the first arm that matches,
+/// like `match x { 0..=2 => 0, 5 | 10..=15 => 1, _ => 2 }`
+pub struct MatchExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+    arms: Vec<MatchArm>,
+    // Values matched by every arm and fields of enum variants bound by them, they are known after the expression was checked
+    cases: RefCell<Vec<Vec<Case>>>,
+    bindings: RefCell<Vec<Vec<FieldBinding>>>,
+}
+
+pub struct MatchArm {
+    token: Token,
+    patterns: Vec<Pattern>,
+    body: Box<dyn ASTExpr>,
+}
+
+/// Patterns are constants or enum variants, so they can be compared at compile time
+pub enum Pattern {
+    Wildcard,
+    Value(Box<dyn ASTExpr>),
+    Range(Box<dyn ASTExpr>, Box<dyn ASTExpr>, bool),
+    // Single name is a variant when an enum is matched, otherwise a constant
+    Name(String, Box<dyn ASTExpr>),
+    // Variant with an optional enum name and names for its fields, like `Shape::Rect(w, h: f64)`
+    Variant(Option<String>, String, Option<Vec<(String, Option<DataType>)>>),
+}
+
+/// Field of the matched variant that is stored in a variable of the arm
+struct FieldBinding {
+    variant: usize,
+    field: u32,
+    name: String,
+    data_type: DataType,
+}
+
+/// Evaluated pattern, ranges include both ends
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    Any,
+    Between(i128, i128),
+}
+
+impl Parseable for MatchExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with keyword "match"
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("match".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing match expression",
+                              ErrorTokenComponent::new("Expected 'match' keyword".to_string(), Some(token))));
+        }
+
+        // Should be followed by the matched value, braces belong to the arms
+        *pos += 1;
+        let start = *pos;
+        let value = match parse_operators(tokens, pos, precedence::LOGICAL_OR) {
+            Ok(value) => value,
+            Err(err) if *pos > start => return Err(err),
+            Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing match expression",
+                                        ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone())))),
+        };
+        if tokens[*pos].token_type != TokenType::Brace('{') {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing match expression",
+                              ErrorTokenComponent::new("Expected '{'".to_string(), Some(tokens[*pos].clone()))));
+        }
+
+        // Arms are separated by commas, arms with a block do not need one
+        *pos += 1;
+        let mut arms = vec![];
+        while tokens[*pos].token_type != TokenType::Brace('}') {
+            arms.push(MatchArm::parse(tokens, pos)?);
+            match tokens[*pos].token_type {
+                TokenType::Separator(',') => *pos += 1,
+                TokenType::Brace('}') => {}
+                _ if tokens[*pos - 1].token_type == TokenType::Brace('}') => {}
+                _ => return Err(error!(ErrorKind::ParserError, "Error while parsing match expression",
+                                       ErrorTokenComponent::new("Expected ',' or '}'".to_string(), Some(tokens[*pos].clone())))),
+            }
+        }
+        *pos += 1;
+
+        Ok(Box::new(MatchExpr {
+            token,
+            value,
+            arms,
+            cases: RefCell::new(vec![]),
+            bindings: RefCell::new(vec![]),
+        }))
+    }
+}
+
+impl MatchArm {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<MatchArm, Error> {
+        // Alternatives are separated by '|'
+        let token = tokens[*pos].clone();
+        let mut patterns = vec![Pattern::parse(tokens, pos)];
+        while tokens[*pos].token_type == TokenType::Operator("|".to_string()) {
+            *pos += 1;
+            patterns.push(Pattern::parse(tokens, pos));
+        }
+
+        // Should be followed by "=>" and a body
+        if tokens[*pos].token_type != TokenType::Operator("=>".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing match arm",
+                              ErrorTokenComponent::new("Expected '=>'".to_string(), Some(tokens[*pos].clone()))));
+        }
+        *pos += 1;
+        let start = *pos;
+        let body = match try_parse!(tokens, *pos, ReturnExpr BreakExpr ContinueExpr ValueExpr) {
+            Ok(body) => body,
+            Err(err) if *pos > start => return Err(err),
+            Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing match arm",
+                                        ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone())))),
+        };
+        Ok(MatchArm { token, patterns, body })
+    }
+}
+
+impl Pattern {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Pattern {
+        if tokens[*pos].token_type == TokenType::Identifier("_".to_string()) {
+            *pos += 1;
+            return Pattern::Wildcard;
+        }
+        if let Some(pattern) = Pattern::parse_variant(tokens, pos) {
+            return pattern;
+        }
+
+        // Operators that bind looser than '|' would take the next alternative
+        let start = Pattern::parse_value(tokens, pos);
+        let inclusive = match tokens[*pos].token_type {
+            TokenType::Operator(ref op) if op == ".." => false,
+            TokenType::Operator(ref op) if op == "..=" => true,
+            _ => return Pattern::Value(start),
+        };
+        *pos += 1;
+        Pattern::Range(start, Pattern::parse_value(tokens, pos), inclusive)
+    }
+
+    /// Parses `Enum::Variant`, `Variant(fields)` or a single name, returns None for other patterns
+    fn parse_variant(tokens: &Vec<Token>, pos: &mut usize) -> Option<Pattern> {
+        let name = match tokens[*pos].token_type {
+            TokenType::Identifier(ref name) if name != "true" && name != "false" => name.clone(),
+            _ => return None,
+        };
+        let (owner, variant) = match tokens[*pos + 1].token_type {
+            TokenType::Separator(':') if tokens[*pos + 2].token_type == TokenType::Separator(':') => {
+                *pos += 3;
+                match tokens[*pos].token_type {
+                    TokenType::Identifier(ref variant) => (Some(name), variant.clone()),
+                    _ => error!(ErrorKind::ParserError, "Error while parsing pattern",
+                                ErrorTokenComponent::new("Expected variant name after '::'".to_string(), Some(tokens[*pos].clone()))).panic()
+                }
+            }
+            TokenType::Paren('(') => (None, name),
+            TokenType::Operator(ref op) if op == "=>" || op == "|" => {
+                let value = Pattern::parse_value(tokens, pos);
+                return Some(Pattern::Name(name, value));
+            }
+            _ => return None,
+        };
+
+        // Could be followed by names of the fields, each one can have a type
+        *pos += 1;
+        if tokens[*pos].token_type != TokenType::Paren('(') {
+            return Some(Pattern::Variant(owner, variant, None));
+        }
+        *pos += 1;
+        let mut fields = vec![];
+        while tokens[*pos].token_type != TokenType::Paren(')') {
+            let field = match tokens[*pos].token_type {
+                TokenType::Identifier(ref field) => field.clone(),
+                _ => error!(ErrorKind::ParserError, "Error while parsing pattern",
+                            ErrorTokenComponent::new("Expected field name".to_string(), Some(tokens[*pos].clone()))).panic()
+            };
+            *pos += 1;
+            let mut data_type = None;
+            if tokens[*pos].token_type == TokenType::Separator(':') {
+                *pos += 1;
+                match DataType::parse_type(tokens, pos) {
+                    Ok(field_type) => data_type = Some(field_type),
+                    Err(_) => error!(ErrorKind::ParserError, "Error while parsing pattern",
+                                     ErrorTokenComponent::new("Expected field type".to_string(), Some(tokens[*pos].clone()))).panic()
+                }
+            }
+            fields.push((field, data_type));
+            if tokens[*pos].token_type == TokenType::Separator(',') {
+                *pos += 1;
+            } else if tokens[*pos].token_type != TokenType::Paren(')') {
+                error!(ErrorKind::ParserError, "Error while parsing pattern",
+                       ErrorTokenComponent::new("Expected ',' or ')'".to_string(), Some(tokens[*pos].clone()))).panic()
+            }
+        }
+        *pos += 1;
+        Some(Pattern::Variant(owner, variant, Some(fields)))
+    }
+
+    fn parse_value(tokens: &Vec<Token>, pos: &mut usize) -> Box<dyn ASTExpr> {
+        match parse_operators(tokens, pos, precedence::BIT_XOR) {
+            Ok(value) => value,
+            Err(_) => error!(ErrorKind::ParserError, "Error while parsing pattern",
+                             ErrorTokenComponent::new("Expected constant, range or '_'".to_string(), Some(tokens[*pos].clone()))).panic()
+        }
+    }
+
+    /// Values matched by the pattern, variants of enums are matched by their tag, errors are reported to the checker
+    fn check(&self, checker: &mut TypeChecker, token: &Token, data_type: &DataType, variants: &Option<Vec<(String, Vec<DataType>)>>) -> Option<Case> {
+        match self {
+            Pattern::Wildcard => Some(Case::Any),
+            Pattern::Name(name, _) if variants.is_some() => Pattern::check_variant(checker, token, data_type, variants, None, name),
+            Pattern::Name(_, value) => {
+                let value = check_constant(checker, token, data_type, value.as_ref())?;
+                Some(Case::Between(value, value))
+            }
+            Pattern::Variant(owner, name, _) => Pattern::check_variant(checker, token, data_type, variants, owner.as_ref(), name),
+            Pattern::Value(value) => {
+                let value = check_constant(checker, token, data_type, value.as_ref())?;
+                Some(Case::Between(value, value))
+            }
+            Pattern::Range(start, end, inclusive) => {
+                let start = check_constant(checker, token, data_type, start.as_ref());
+                let end = check_constant(checker, token, data_type, end.as_ref());
+                let (start, end) = (start?, end?);
+                Some(Case::Between(start, if *inclusive { end } else { end - 1 }))
+            }
+        }
+    }
+
+    fn check_variant(checker: &mut TypeChecker, token: &Token, data_type: &DataType, variants: &Option<Vec<(String, Vec<DataType>)>>, owner: Option<&String>, name: &str) -> Option<Case> {
+        let variants = match (variants, data_type) {
+            (Some(variants), DataType::Named(enum_name)) if owner.map_or(true, |owner| owner == enum_name) => variants,
+            _ => {
+                checker.error(error!(ErrorKind::TypeError, "Pattern type does not match the value",
+                                     ErrorTokenComponent::new("Invalid pattern".to_string(), Some(token.clone()))
+                                     NameErrorComponent::new(format!("Expected: {}", data_type))
+                                     NameErrorComponent::new(format!("Found: variant {}", name))));
+                return None;
+            }
+        };
+        match variants.iter().position(|(variant, _)| variant == name) {
+            Some(variant) => Some(Case::Between(variant as i128, variant as i128)),
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Variant does not exist",
+                                     ErrorTokenComponent::new(format!("{} has no variant {}", data_type, name), Some(token.clone()))));
+                None
+            }
+        }
+    }
+
+    fn to_string(&self) -> String {
+        match self {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Name(name, _) => name.clone(),
+            Pattern::Variant(owner, name, fields) => {
+                let owner = owner.as_ref().map(|owner| format!("{}::", owner)).unwrap_or_default();
+                let fields = fields.as_ref().map(|fields| {
+                    let fields = fields.iter().map(|(field, data_type)| match data_type {
+                        Some(data_type) => format!("{}: {:?}", field, data_type),
+                        None => field.clone(),
+                    }).collect::<Vec<String>>();
+                    format!("({})", fields.join(", "))
+                }).unwrap_or_default();
+                format!("{}{}{}", owner, name, fields)
+            }
+            Pattern::Value(value) => value.to_string(),
+            Pattern::Range(start, end, inclusive) => format!("{}{}{}", start.to_string(), if *inclusive { "..=" } else { ".." }, end.to_string()),
+        }
+    }
+}
+
+impl ASTExpr for MatchExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let data_type = self.value.data_type(scope_manager);
+        let declared = match data_type {
+            DataType::Named(ref name) => scope_manager.scope.enums.get(name).cloned(),
+            _ => None,
+        };
+        // Enums are matched by their tag
+        let (value, variant_value) = match self.value.generate(context, module, builder, scope_manager) {
+            Some(AnyValueEnum::IntValue(value)) => (value, None),
+            Some(AnyValueEnum::StructValue(value)) if declared.is_some() => (build_tag(builder, value), Some(value)),
+            _ => error!(ErrorKind::CompilerError, "Value can not be matched",
+                        ErrorTokenComponent::new("Only integers, chars, bools and enums can be matched".to_string(), Some(self.token.clone()))).panic()
+        };
+        let unsigned = data_type.is_unsigned() || declared.is_some();
+
+        let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+        let arm_blocks = self.arms.iter().map(|_| context.append_basic_block(function, "arm")).collect::<Vec<BasicBlock<'a>>>();
+        let ranges_block = context.append_basic_block(function, "matchranges");
+        let merge_block = context.append_basic_block(function, "matchmerge");
+        let cases = self.cases.borrow();
+        build_dispatch(context, builder, value, unsigned, &cases, &arm_blocks, ranges_block);
+
+        // Generate every arm, each one can end in a different block
+        let bindings = self.bindings.borrow();
+        let mut incoming: Vec<(Option<AnyValueEnum<'a>>, BasicBlock<'a>)> = vec![];
+        for ((arm, block), arm_bindings) in self.arms.iter().zip(arm_blocks).zip(bindings.iter()) {
+            builder.position_at_end(block);
+            scope_manager.create_scope();
+            if let (Some(declared), Some(variant_value)) = (&declared, variant_value) {
+                bind_fields(context, builder, scope_manager, declared, variant_value, arm_bindings);
+            }
+            let arm_value = arm.body.generate(context, module, builder, scope_manager);
+            scope_manager.exit_scope();
+            if !is_terminated(builder) {
+                incoming.push((arm_value, builder.get_insert_block().unwrap()));
+                builder.build_unconditional_branch(merge_block);
+            }
+        }
+
+        builder.position_at_end(merge_block);
+        if incoming.is_empty() {
+            // Nothing continues after the match
+            builder.build_unreachable();
+            return None;
+        }
+
+        // Value is only known if every arm produces the same type
+        let values = incoming.iter().map(|(value, _)| value.map(|value| value.to_basic())).collect::<Option<Vec<BasicValueEnum<'a>>>>()?;
+        let first_type = values[0].get_type();
+        if values.iter().any(|value| value.get_type() != first_type) {
+            return None;
+        }
+        let phi = builder.build_phi(first_type, "matchtmp");
+        for (value, (_, block)) in values.iter().zip(incoming.iter()) {
+            phi.add_incoming(&[(value, *block)]);
+        }
+        Some(phi.as_any_value_enum())
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        self.arms.iter()
+            .map(|arm| arm.body.data_type(scope_manager))
+            .find(|data_type| !matches!(data_type, DataType::Never | DataType::Unknown))
+            .unwrap_or(DataType::Unknown)
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let data_type = self.value.check(checker);
+        let variants = match data_type {
+            DataType::Named(ref name) => checker.enum_variants(name),
+            _ => None,
+        };
+        // Variants are numbered in the order of their declaration
+        let domain = match variants {
+            Some(ref variants) => Some((0, variants.len() as i128 - 1)),
+            None => bounds(&data_type),
+        };
+        let matchable = domain.is_some() || data_type == DataType::Unknown;
+        if !matchable {
+            checker.error(error!(ErrorKind::TypeError, "Value can not be matched",
+                                 ErrorTokenComponent::new("Only integers, chars, bools and enums can be matched".to_string(), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("Found: {}", data_type))));
+        }
+
+        let mut cases = vec![];
+        let mut bindings = vec![];
+        let mut result = DataType::Never;
+        for arm in self.arms.iter() {
+            let arm_cases = arm.patterns.iter().filter_map(|pattern| {
+                if matchable { pattern.check(checker, &arm.token, &data_type, &variants) } else { None }
+            }).collect::<Vec<Case>>();
+            let arm_bindings = match variants {
+                Some(ref variants) => check_bindings(checker, arm, variants, &arm_cases),
+                None => vec![],
+            };
+
+            // Arm that never finishes does not decide the type
+            checker.enter_scope();
+            for binding in arm_bindings.iter() {
+                checker.define(&binding.name, binding.data_type.clone(), BindingKind::Variable);
+            }
+            cases.push(arm_cases);
+            bindings.push(arm_bindings);
+            let found = arm.body.check(checker);
+            checker.exit_scope();
+            if result == DataType::Never {
+                result = found;
+            } else if !compatible(&result, &found) {
+                checker.error(error!(ErrorKind::TypeError, "Match arms have different types",
+                                     ErrorTokenComponent::new("Arm has a different type than the previous ones".to_string(), Some(arm.token.clone()))
+                                     NameErrorComponent::new(format!("Expected: {}", result))
+                                     NameErrorComponent::new(format!("Found: {}", found))));
+            }
+        }
+
+        if let Some(domain) = domain {
+            if !is_exhaustive(&cases, domain) {
+                checker.error(error!(ErrorKind::TypeError, "Match is not exhaustive",
+                                     ErrorTokenComponent::new("Add a '_' arm to match the remaining values".to_string(), Some(self.token.clone()))));
+            }
+        }
+        *self.cases.borrow_mut() = cases;
+        *self.bindings.borrow_mut() = bindings;
+        result
+    }
+
+    fn to_string(&self) -> String {
+        let arms = self.arms.iter().map(|arm| {
+            let patterns = arm.patterns.iter().map(|pattern| pattern.to_string()).collect::<Vec<String>>().join(" | ");
+            format!("{} => {}", patterns, arm.body.to_string())
+        }).collect::<Vec<String>>();
+        format!("Match {} {{ {} }}", self.value.to_string(), arms.join(", "))
+    }
+}
+
+/// Fields of the variant that are named by the pattern of the arm
+fn check_bindings(checker: &mut TypeChecker, arm: &MatchArm, variants: &[(String, Vec<DataType>)], cases: &[Case]) -> Vec<FieldBinding> {
+    let names = match arm.patterns.iter().find_map(|pattern| match pattern {
+        Pattern::Variant(_, _, Some(names)) => Some(names),
+        _ => None,
+    }) {
+        Some(names) => names,
+        None => return vec![],
+    };
+    // Every alternative would need the same fields
+    if arm.patterns.len() > 1 {
+        checker.error(error!(ErrorKind::TypeError, "Patterns with bindings can not have alternatives",
+                             ErrorTokenComponent::new("Use a separate arm for every variant".to_string(), Some(arm.token.clone()))));
+        return vec![];
+    }
+    let variant = match cases.first() {
+        Some(Case::Between(variant, _)) => *variant as usize,
+        _ => return vec![],
+    };
+
+    let (variant_name, fields) = &variants[variant];
+    if names.len() != fields.len() {
+        checker.error(error!(ErrorKind::TypeError, "Wrong number of variant fields",
+                             ErrorTokenComponent::new(format!("{} has {} fields, but {} were given", variant_name, fields.len(), names.len()), Some(arm.token.clone()))));
+        return vec![];
+    }
+    let mut bindings = vec![];
+    for (i, ((name, expected), field)) in names.iter().zip(fields.iter()).enumerate() {
+        if let Some(expected) = expected {
+            if expected != field {
+                checker.error(error!(ErrorKind::TypeError, "Pattern type does not match the value",
+                                     ErrorTokenComponent::new(format!("Field {} of {} has a different type", i, variant_name), Some(arm.token.clone()))
+                                     NameErrorComponent::new(format!("Expected: {}", field))
+                                     NameErrorComponent::new(format!("Found: {}", expected))));
+            }
+        }
+        if name != "_" {
+            bindings.push(FieldBinding { variant, field: i as u32, name: name.clone(), data_type: field.clone() });
+        }
+    }
+    bindings
+}
+
+/// Stores the bound fields of the variant in variables of the arm
+fn bind_fields<'a>(context: &'a Context, builder: &Builder<'a>, scope_manager: &mut ScopeManager<'a>, declared: &Enum<'a>, value: StructValue<'a>, bindings: &[FieldBinding]) {
+    let variant = match bindings.first() {
+        Some(binding) => binding.variant,
+        None => return,
+    };
+    let fields = build_variant_fields(context, builder, declared, variant, value);
+    for binding in bindings.iter() {
+        let field = build_tuple_field(builder, fields, binding.field, &Token { token_type: TokenType::EOF, line: 0, column: 0 });
+        let alloca = build_entry_alloca(context, builder, field.get_type(), &binding.name);
+        builder.build_store(alloca, field);
+        scope_manager.scope.fn_args.remove(&binding.name);
+        scope_manager.scope.constants.remove(&binding.name);
+        scope_manager.scope.variables.insert(binding.name.clone(), Rc::new(Variable { pointer: alloca, data_type: binding.data_type.clone() }));
+    }
+}
+
+/// Constant value of the pattern as an integer
+fn check_constant(checker: &mut TypeChecker, token: &Token, data_type: &DataType, value: &dyn ASTExpr) -> Option<i128> {
+    let found = value.check(checker);
+    if !compatible(data_type, &found) {
+        checker.error(error!(ErrorKind::TypeError, "Pattern type does not match the value",
+                             ErrorTokenComponent::new("Invalid pattern".to_string(), Some(token.clone()))
+                             NameErrorComponent::new(format!("Expected: {}", data_type))
+                             NameErrorComponent::new(format!("Found: {}", found))));
+        return None;
+    }
+    match value.evaluate(checker) {
+        Some(Constant::Int(value, _)) => Some(value),
+        Some(Constant::Bool(value)) => Some(value as i128),
+        _ => {
+            checker.error(error!(ErrorKind::TypeError, "Pattern has to be a constant",
+                                 ErrorTokenComponent::new("Value is not known at compile time".to_string(), Some(token.clone()))));
+            None
+        }
+    }
+}
+
+/// Whether the cases together contain every value between the bounds
+fn is_exhaustive(cases: &[Vec<Case>], bounds: (i128, i128)) -> bool {
+    let mut ranges = vec![];
+    for case in cases.iter().flatten() {
+        match *case {
+            Case::Any => return true,
+            Case::Between(start, end) if start <= end => ranges.push((start, end)),
+            Case::Between(..) => {}
+        }
+    }
+    ranges.sort();
+    let mut next = bounds.0;
+    for (start, end) in ranges {
+        if start > next {
+            return false;
+        }
+        next = next.max(end + 1);
+    }
+    next > bounds.1
+}
+
+/// Single values are handled by a switch, ranges are compared in order after it,
+/// a value that is matched by an earlier arm is not added to the switch, so the first arm always wins
+fn build_dispatch<'a>(context: &'a Context, builder: &Builder<'a>, value: IntValue<'a>, unsigned: bool, cases: &[Vec<Case>], arm_blocks: &[BasicBlock<'a>], ranges_block: BasicBlock<'a>) {
+    let int_type = value.get_type();
+    let mut switch_cases = vec![];
+    let mut used = HashSet::new();
+    for (i, arm_cases) in cases.iter().enumerate() {
+        for case in arm_cases.iter() {
+            if let Case::Between(start, end) = *case {
+                let earlier = cases[..i].iter().flatten().any(|case| match *case {
+                    Case::Any => true,
+                    Case::Between(s, e) => s < e && s <= start && start <= e,
+                });
+                if start == end && !earlier && used.insert(start) {
+                    switch_cases.push((int_type.const_int(start as u64, false), arm_blocks[i]));
+                }
+            }
+        }
+    }
+    builder.build_switch(value, ranges_block, &switch_cases);
+
+    // Ranges and the wildcard are checked in the order of the arms
+    builder.position_at_end(ranges_block);
+    let function = ranges_block.get_parent().unwrap();
+    let (greater_equal, less_equal) = if unsigned { (IntPredicate::UGE, IntPredicate::ULE) } else { (IntPredicate::SGE, IntPredicate::SLE) };
+    for (i, arm_cases) in cases.iter().enumerate() {
+        for case in arm_cases.iter() {
+            match *case {
+                Case::Any => {
+                    builder.build_unconditional_branch(arm_blocks[i]);
+                    return;
+                }
+                Case::Between(start, end) if start < end => {
+                    let above = builder.build_int_compare(greater_equal, value, int_type.const_int(start as u64, false), "matchabove");
+                    let below = builder.build_int_compare(less_equal, value, int_type.const_int(end as u64, false), "matchbelow");
+                    let inside = builder.build_and(above, below, "matchinside");
+                    let next_block = context.append_basic_block(function, "matchnext");
+                    builder.build_conditional_branch(inside, arm_blocks[i], next_block);
+                    builder.position_at_end(next_block);
+                }
+                Case::Between(..) => {}
+            }
+        }
+    }
+
+    // Checker made sure that every value is matched
+    builder.build_unreachable();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::{checker::check_source, compiler::run_source};
+
+    fn parse_value(input: &str) -> String {
+        let tokens = tokenize(input);
+        let mut pos = 0;
+        ValueExpr::parse(&tokens, &mut pos).unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_match() {
+        assert_eq!(parse_value("match x { 0..=2 => 0, 5 | 10..=15 => 1, _ => 2 };"),
+                   "Match VarCall x { I32(0)..=I32(2) => I32(0), I32(5) | I32(10)..=I32(15) => I32(1), _ => I32(2) }");
+        assert_eq!(parse_value("match c { 'a' | 'b' => { y } '0'..'9' => return 1, _ => x | 1 };"),
+                   "Match VarCall c { Char('a') | Char('b') => {\n\t Result VarCall y \n}, Char('0')..Char('9') => Return I32(1), _ => BIT_OR VarCall x, I32(1) }");
+    }
+
+    #[test]
+    fn check_match() {
+        assert!(check_source("fun f(x: u8, b: bool): i32 { var a = match b { true => 1, false => 2 }; a + match x { 0u8..=100u8 => 1, 101u8..=255u8 => 2 } }").is_empty());
+        assert_eq!(check_source("fun f(x: u8): i32 { match x { 0u8..100u8 => 1, 200u8 => 2 } }"), vec!["TypeError: Match is not exhaustive"]);
+        assert_eq!(check_source("fun f(x: u8): i32 { match x { 1 => 1, _ => 2 } }"), vec!["TypeError: Pattern type does not match the value"]);
+        assert_eq!(check_source("fun f(n: i32): i32 { match n { n => 1, _ => 2 } }"), vec!["TypeError: Pattern has to be a constant"]);
+        assert_eq!(check_source("fun f(n: i32): void { match n { 0 => 1, _ => 2.5 } }"), vec!["TypeError: Match arms have different types"]);
+        assert_eq!(check_source("fun f(): void { match 1.5 { _ => 1 } }"), vec!["TypeError: Value can not be matched"]);
+    }
+
+    #[test]
+    fn run_match() {
+        let source = "fun main(): i32 { classify(1) + classify(5) * 10 + classify(12) * 100 + classify(-3) * 1000 + grade('b') * 10000 } \
+                      fun classify(x: i32): i32 { match x { 0..=2 => 1, 5 | 10..=15 => 2, _ => 3 } } \
+                      fun grade(c: char): i32 { match c { 'a' => 1, 'b' | 'c' => 2, _ => { return 9; } } }";
+        assert_eq!(run_source(source), 23221);
+    }
+}
@@ -1,64 +1,58 @@
 
-use inkwell::values::{AnyValueEnum, IntValue};
+use inkwell::{builder::Builder, values::AnyValueEnum};
 
-use crate::{error, utils::{error::{Error, ErrorKind}, error_components::name_component::NameErrorComponent}, lexer::lexer::TokenType};
+use crate::{error, compiler::{checker::TypeChecker, constant::Constant}, utils::{error::{Error, ErrorKind}, error_components::{name_component::NameErrorComponent, token_component::ErrorTokenComponent}}, lexer::lexer::Token};
 
-use super::{ASTExpr, Parseable, value_expression};
+use super::{ASTExpr, data_types::DataType, scope::ScopeManager, string_expression::build_concat};
 
-#[derive(Debug)]
-enum MathOperation {
+#[derive(Debug, Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub enum MathOperation {
     ADD,
     SUBTRACT,
     MULTIPLY,
-    DIVIDE
+    DIVIDE,
+    MODULO,
+    BIT_AND,
+    BIT_OR,
+    BIT_XOR,
+    SHIFT_LEFT,
+    SHIFT_RIGHT,
+}
+
+impl MathOperation {
+    pub fn parse(op: &str) -> Option<MathOperation> {
+        match op {
+            "+" => Some(MathOperation::ADD),
+            "-" => Some(MathOperation::SUBTRACT),
+            "*" => Some(MathOperation::MULTIPLY),
+            "/" => Some(MathOperation::DIVIDE),
+            "%" => Some(MathOperation::MODULO),
+            "&" => Some(MathOperation::BIT_AND),
+            "|" => Some(MathOperation::BIT_OR),
+            "^" => Some(MathOperation::BIT_XOR),
+            "<<" => Some(MathOperation::SHIFT_LEFT),
+            ">>" => Some(MathOperation::SHIFT_RIGHT),
+            _ => None,
+        }
+    }
 }
 
 pub struct MathExpr {
+    token: Token,
     lhs: Box<dyn ASTExpr>,
     rhs: Box<dyn ASTExpr>,
     operation: MathOperation,
 }
 
-impl Parseable for MathExpr {
-    fn parse(tokens: &Vec<crate::lexer::lexer::Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, crate::utils::error::Error> {
-        // Get LHS expression
-        let lhs = value_expression::NoMathValueExpr::parse(tokens, pos);
-        if lhs.is_err() {
-            return Err(error!(ErrorKind::ParserError, "Expected LHS expression"));
-        }
-        // Get operation
-        let op = match &tokens[*pos].token_type {
-            TokenType::Operator(ref op) => {
-                match op.chars().next().unwrap() {
-                    '+' => MathOperation::ADD,
-                    '-' => MathOperation::SUBTRACT,
-                    '*' => MathOperation::MULTIPLY,
-                    '/' => MathOperation::DIVIDE,
-                    _ => { return Err(error!(ErrorKind::ParserError, "Expected math operator")) }
-                }
-            }
-            _ => { return Err(error!(ErrorKind::ParserError, "Expected math operator")) } 
-        };
-        *pos += 1;
-
-        // Get RHS expression
-        let rhs = value_expression::ValueExpr::parse(tokens, pos);
-        if rhs.is_err() {
-            error!(ErrorKind::ParserError, "Expected RHS expression").panic();
-        }
-
-        Ok(Box::new(MathExpr {
-            lhs: lhs.unwrap(),
-            operation: op,
-            rhs: rhs.unwrap()
-        }))
-
+impl MathExpr {
+    pub fn new(token: Token, lhs: Box<dyn ASTExpr>, operation: MathOperation, rhs: Box<dyn ASTExpr>) -> MathExpr {
+        MathExpr { token, lhs, rhs, operation }
     }
 }
 
-
 impl ASTExpr for MathExpr {
-    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &inkwell::builder::Builder<'a>, scope_manager: &'b mut super::scope::ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &inkwell::builder::Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
         let lhs = self.lhs.generate(context, module, builder, scope_manager);
         if lhs.is_none() {
             error!(ErrorKind::CompilerError, "Expected LHS for binary expression").panic();
@@ -68,31 +62,25 @@ impl ASTExpr for MathExpr {
             error!(ErrorKind::CompilerError, "Expected RHS for binary expression").panic();
         }
 
-        let lhs = lhs.unwrap();
-        let rhs = rhs.unwrap();
-        // TODO: Check if types are matching
-        if lhs.get_type() != rhs.get_type() {
-            error!(ErrorKind::CompilerError, "Binary operation types do not match",
-                   NameErrorComponent::new(format!("LHS: {}", lhs.get_type()))
-                   NameErrorComponent::new(format!("RHS: {}", lhs.get_type()))).panic();
-        }
-        if lhs.is_int_value() {
-            return Some(AnyValueEnum::IntValue(match self.operation {
-                MathOperation::ADD => builder.build_int_add(lhs.into_int_value(), rhs.into_int_value(), "iaddtmp"),
-                MathOperation::SUBTRACT => builder.build_int_sub(lhs.into_int_value(), rhs.into_int_value(), "isubtmp"),
-                MathOperation::MULTIPLY => builder.build_int_mul(lhs.into_int_value(), rhs.into_int_value(), "imultmp"),
-                MathOperation::DIVIDE => builder.build_int_signed_div(lhs.into_int_value(), rhs.into_int_value(), "isdivtmp")
-            }))
-        } else if lhs.is_float_value() {
-            return Some(AnyValueEnum::FloatValue(match self.operation {
-                MathOperation::ADD => builder.build_float_add(lhs.into_float_value(), rhs.into_float_value(), "faddtmp"),
-                MathOperation::SUBTRACT => builder.build_float_sub(lhs.into_float_value(), rhs.into_float_value(), "fsubtmp"),
-                MathOperation::MULTIPLY => builder.build_float_mul(lhs.into_float_value(), rhs.into_float_value(), "fmultmp"),
-                MathOperation::DIVIDE => builder.build_float_div(lhs.into_float_value(), rhs.into_float_value(), "fdivtmp")
-            }))
+        let data_type = self.lhs.data_type(scope_manager);
+        if data_type == DataType::String {
+            return Some(AnyValueEnum::StructValue(build_concat(context, builder, lhs.unwrap().into_struct_value(), rhs.unwrap().into_struct_value())));
         }
+        build_math(builder, self.operation, lhs.unwrap(), rhs.unwrap(), data_type.is_unsigned())
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        self.lhs.data_type(scope_manager)
+    }
 
-        todo!("Implement some error")
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let lhs = self.lhs.check(checker);
+        let rhs = self.rhs.check(checker);
+        check_math(checker, &self.token, self.operation, lhs, rhs)
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        Constant::math(self.operation, &self.lhs.evaluate(checker)?, &self.rhs.evaluate(checker)?)
     }
 
     fn to_string(&self) -> String {
@@ -100,10 +88,131 @@ impl ASTExpr for MathExpr {
     }
 }
 
+/// Builds the operation on two values of the same type
+pub fn build_math<'a>(builder: &Builder<'a>, operation: MathOperation, lhs: AnyValueEnum<'a>, rhs: AnyValueEnum<'a>, unsigned: bool) -> Option<AnyValueEnum<'a>> {
+    if lhs.get_type() != rhs.get_type() {
+        error!(ErrorKind::CompilerError, "Binary operation types do not match",
+               NameErrorComponent::new(format!("LHS: {}", lhs.get_type()))
+               NameErrorComponent::new(format!("RHS: {}", rhs.get_type()))).panic();
+    }
+    if lhs.is_int_value() {
+        let (lhs, rhs) = (lhs.into_int_value(), rhs.into_int_value());
+        return Some(AnyValueEnum::IntValue(match operation {
+            MathOperation::ADD => builder.build_int_add(lhs, rhs, "iaddtmp"),
+            MathOperation::SUBTRACT => builder.build_int_sub(lhs, rhs, "isubtmp"),
+            MathOperation::MULTIPLY => builder.build_int_mul(lhs, rhs, "imultmp"),
+            MathOperation::DIVIDE if unsigned => builder.build_int_unsigned_div(lhs, rhs, "iudivtmp"),
+            MathOperation::DIVIDE => builder.build_int_signed_div(lhs, rhs, "isdivtmp"),
+            MathOperation::MODULO if unsigned => builder.build_int_unsigned_rem(lhs, rhs, "iuremtmp"),
+            MathOperation::MODULO => builder.build_int_signed_rem(lhs, rhs, "isremtmp"),
+            MathOperation::BIT_AND => builder.build_and(lhs, rhs, "andtmp"),
+            MathOperation::BIT_OR => builder.build_or(lhs, rhs, "ortmp"),
+            MathOperation::BIT_XOR => builder.build_xor(lhs, rhs, "xortmp"),
+            MathOperation::SHIFT_LEFT => builder.build_left_shift(lhs, rhs, "shltmp"),
+            // Signed values keep their sign
+            MathOperation::SHIFT_RIGHT => builder.build_right_shift(lhs, rhs, !unsigned, "shrtmp"),
+        }))
+    } else if lhs.is_float_value() {
+        let value_type = lhs.get_type();
+        let (lhs, rhs) = (lhs.into_float_value(), rhs.into_float_value());
+        return Some(AnyValueEnum::FloatValue(match operation {
+            MathOperation::ADD => builder.build_float_add(lhs, rhs, "faddtmp"),
+            MathOperation::SUBTRACT => builder.build_float_sub(lhs, rhs, "fsubtmp"),
+            MathOperation::MULTIPLY => builder.build_float_mul(lhs, rhs, "fmultmp"),
+            MathOperation::DIVIDE => builder.build_float_div(lhs, rhs, "fdivtmp"),
+            MathOperation::MODULO => builder.build_float_rem(lhs, rhs, "fremtmp"),
+            _ => error!(ErrorKind::CompilerError, "Bitwise operations need integers",
+                        NameErrorComponent::new(format!("Operation {:?} can not be used on {}", operation, value_type))).panic()
+        }))
+    }
+
+    error!(ErrorKind::CompilerError, "Values can not be used in math operation",
+           NameErrorComponent::new(format!("Type: {}", lhs.get_type()))).panic()
+}
+
+/// Checks that the operation can be used on the types, returns the type of the result
+pub fn check_math(checker: &mut TypeChecker, token: &Token, operation: MathOperation, lhs: DataType, rhs: DataType) -> DataType {
+    if lhs == DataType::Unknown || rhs == DataType::Unknown {
+        return DataType::Unknown;
+    }
+    if lhs != rhs {
+        checker.error(error!(ErrorKind::TypeError, "Binary operation types do not match",
+                             ErrorTokenComponent::new(format!("Operands of {:?} have different types", operation), Some(token.clone()))
+                             NameErrorComponent::new(format!("LHS: {}", lhs))
+                             NameErrorComponent::new(format!("RHS: {}", rhs))));
+        return DataType::Unknown;
+    }
+    // Strings can only be concatenated
+    if lhs == DataType::String && matches!(operation, MathOperation::ADD) {
+        return lhs;
+    }
+    // Characters can be shifted and scaled, like `'a' + 1 as char`
+    if lhs == DataType::Char && matches!(operation, MathOperation::ADD | MathOperation::SUBTRACT | MathOperation::MULTIPLY | MathOperation::DIVIDE) {
+        return lhs;
+    }
+    let bitwise = matches!(operation, MathOperation::BIT_AND | MathOperation::BIT_OR | MathOperation::BIT_XOR | MathOperation::SHIFT_LEFT | MathOperation::SHIFT_RIGHT);
+    if bitwise && lhs.is_float() {
+        checker.error(error!(ErrorKind::TypeError, "Bitwise operations need integers",
+                             ErrorTokenComponent::new(format!("Operation {:?} can not be used on {}", operation, lhs), Some(token.clone()))));
+        return DataType::Unknown;
+    }
+    if !lhs.is_integer() && !lhs.is_float() && !(bitwise && lhs == DataType::Bool) {
+        checker.error(error!(ErrorKind::TypeError, "Values can not be used in math operation",
+                             ErrorTokenComponent::new(format!("Operation {:?} can not be used on {}", operation, lhs), Some(token.clone()))));
+        return DataType::Unknown;
+    }
+    lhs
+}
+
+pub struct NegateExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+}
+
+impl NegateExpr {
+    pub fn new(token: Token, value: Box<dyn ASTExpr>) -> NegateExpr {
+        NegateExpr { token, value }
+    }
+}
+
+impl ASTExpr for NegateExpr {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        match self.value.generate(context, module, builder, scope_manager) {
+            Some(AnyValueEnum::IntValue(value)) => Some(AnyValueEnum::IntValue(builder.build_int_neg(value, "negtmp"))),
+            Some(AnyValueEnum::FloatValue(value)) => Some(AnyValueEnum::FloatValue(builder.build_float_neg(value, "fnegtmp"))),
+            _ => error!(ErrorKind::CompilerError, "Only numbers can be negated",
+                        ErrorTokenComponent::new("Invalid value for '-'".to_string(), Some(self.token.clone()))).panic()
+        }
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        self.value.data_type(scope_manager)
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let data_type = self.value.check(checker);
+        if data_type != DataType::Unknown && !data_type.is_integer() && !data_type.is_float() {
+            checker.error(error!(ErrorKind::TypeError, "Only numbers can be negated",
+                                 ErrorTokenComponent::new("Invalid value for '-'".to_string(), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("Found: {}", data_type))));
+            return DataType::Unknown;
+        }
+        data_type
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        self.value.evaluate(checker)?.negate()
+    }
+
+    fn to_string(&self) -> String {
+        format!("NEGATE {}", self.value.to_string())
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::{test_token, lexer::lexer::TokenType, parser::expressions::Parseable};
+    use crate::{test_token, lexer::lexer::TokenType, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::compiler::run_source;
 
     #[test]
     fn parse_add_i32() {
@@ -114,7 +223,7 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "ADD I32(5), I32(3)");
@@ -129,7 +238,7 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "SUBTRACT I32(5), I32(3)");
@@ -144,7 +253,7 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "MULTIPLY I32(5), I32(3)");
@@ -159,7 +268,7 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "DIVIDE I32(5), I32(3)");
@@ -174,7 +283,7 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "ADD F64(5.0), F64(3.0)");
@@ -189,7 +298,7 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "SUBTRACT F64(5.0), F64(3.0)");
@@ -204,7 +313,7 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "MULTIPLY F64(5.0), F64(3.0)");
@@ -219,9 +328,27 @@ mod tests {
             test_token!(TokenType::Separator(';'))
         ];
         let mut pos = 0;
-        let expr = super::MathExpr::parse(&tokens, &mut pos);
+        let expr = ValueExpr::parse(&tokens, &mut pos);
         assert!(expr.is_ok());
         let expr = expr.unwrap();
         assert_eq!(expr.to_string(), "DIVIDE F64(5.0), F64(3.0)");
     }
+
+    #[test]
+    fn run_operators() {
+        assert_eq!(run_source("fun main(): i32 { 10 - 4 - 3 }"), 3);
+        assert_eq!(run_source("fun main(): i32 { 2 + 3 * 4 }"), 14);
+        assert_eq!(run_source("fun main(): i32 { (2 + 3) * 4 % 7 }"), 6);
+        assert_eq!(run_source("fun main(): i32 { 1 << 4 | 3 & 6 ^ 1 }"), 19);
+        assert_eq!(run_source("fun main(): i32 { var x: i32 = 5; -x + 8 }"), 3);
+        assert_eq!(run_source("fun main(): i32 { (7.9 as i32) + (300 as i8 as i32) + (true_one() as i32) } fun true_one(): bool { 1 as bool }"), 52);
+    }
+
+    #[test]
+    fn run_unsigned() {
+        assert_eq!(run_source("fun main(): i32 { var x: u32 = 4000000000u32; (x / 1000000000u32) as i32 }"), 4);
+        assert_eq!(run_source("fun main(): i32 { var x: u8 = 200u8; if (x > 100u8) { 1 } else { 0 } }"), 1);
+        assert_eq!(run_source("fun main(): i32 { (255u8 as i32) + (250u8 >> 4u8) as i32 }"), 270);
+        assert_eq!(run_source("fun half(x: u16): u16 { x / 2u16 } fun main(): i32 { half(65000u16) as i32 }"), 32500);
+    }
 }
@@ -1,13 +1,25 @@
-use inkwell::{builder::Builder, module::Module, context::Context, values::AnyValueEnum, basic_block::BasicBlock };
+use inkwell::{builder::Builder, module::Module, context::Context, values::{AnyValueEnum, PointerValue}, basic_block::BasicBlock };
 
-use crate::{lexer::lexer::{Token, TokenType}, utils::error::Error};
+use crate::{compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::{Token, TokenType}, utils::error::Error};
 
-use self::{function_expression::FunctionExpr, scope::ScopeManager};
+use self::{function_expression::FunctionExpr, data_types::DataType, scope::ScopeManager};
 pub mod scope;
 
 pub trait ASTExpr {
     fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>>;
     fn to_string(&self) -> String;
+    /// Called for every global expression before `declare`, so types can be used in any function signature
+    fn declare_types<'a, 'b>(&self, context: &'a Context, scope_manager: &'b mut ScopeManager<'a>) {}
+    /// Called for every global expression before any code is generated, so functions can be used before their definition
+    fn declare<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, scope_manager: &'b mut ScopeManager<'a>) {}
+    /// Type of the generated value, used where the LLVM type is not enough, like signedness of integers
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType { DataType::Unknown }
+    /// Checks the types of the expression before code is generated, errors are reported to the checker
+    fn check(&self, checker: &mut TypeChecker) -> DataType { DataType::Unknown }
+    /// Value of the expression if it can be evaluated at compile time, called after the expression was checked
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> { None }
+    /// Address of the value if it is stored in memory, so it can be changed in place
+    fn pointer<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<PointerValue<'a>> { None }
 }
 
 pub trait Parseable {
@@ -23,6 +35,9 @@ impl ASTExpr for VoidExpr {
     fn to_string(&self) -> String {
         "NOP".to_string()
     }
+    fn check(&self, _checker: &mut TypeChecker) -> DataType {
+        DataType::Void
+    }
 }
 impl Parseable for VoidExpr {
     fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
@@ -40,3 +55,21 @@ pub mod literal_expression;
 pub mod data_types;
 pub mod variable_expression;
 pub mod math_expression;
+pub mod call_expression;
+pub mod return_expression;
+pub mod if_expression;
+pub mod comparison_expression;
+pub mod logical_expression;
+pub mod loop_expression;
+pub mod assignment_expression;
+pub mod cast_expression;
+pub mod string_expression;
+pub mod index_expression;
+pub mod array_expression;
+pub mod tuple_expression;
+pub mod range_expression;
+pub mod match_expression;
+pub mod enum_expression;
+pub mod static_get_expression;
+pub mod class_expression;
+pub mod dynamic_get_expression;
@@ -0,0 +1,113 @@
+use inkwell::{builder::Builder, context::Context, module::Module, types::{BasicTypeEnum, StructType}, values::{AnyValueEnum, IntValue, StructValue}};
+
+use crate::{compiler::checker::TypeChecker, lexer::lexer::Token, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, data_types::DataType, scope::ScopeManager};
+
+/// Range of integers, like `0..n` without `n` or `0..=n` with it
+pub struct RangeExpr {
+    token: Token,
+    start: Box<dyn ASTExpr>,
+    end: Box<dyn ASTExpr>,
+    inclusive: bool,
+}
+
+impl RangeExpr {
+    pub fn new(token: Token, start: Box<dyn ASTExpr>, end: Box<dyn ASTExpr>, inclusive: bool) -> RangeExpr {
+        RangeExpr { token, start, end, inclusive }
+    }
+}
+
+impl ASTExpr for RangeExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let start = self.start.generate(context, module, builder, scope_manager);
+        let end = self.end.generate(context, module, builder, scope_manager);
+        let (start, end) = match (start, end) {
+            (Some(AnyValueEnum::IntValue(start)), Some(AnyValueEnum::IntValue(end))) if start.get_type() == end.get_type() => (start, end),
+            (Some(start), Some(end)) => error!(ErrorKind::CompilerError, "Range has to be made of integers of the same type",
+                                              ErrorTokenComponent::new("Invalid range".to_string(), Some(self.token.clone()))
+                                              NameErrorComponent::new(format!("Start: {}", start.get_type()))
+                                              NameErrorComponent::new(format!("End: {}", end.get_type()))).panic(),
+            _ => error!(ErrorKind::CompilerError, "Range has to be made of integers of the same type",
+                        ErrorTokenComponent::new("Range does not have a value".to_string(), Some(self.token.clone()))).panic(),
+        };
+
+        let inclusive = context.bool_type().const_int(self.inclusive as u64, false);
+        let range = range_type(context, start.get_type().into()).get_undef();
+        let range = builder.build_insert_value(range, start, 0, "rangetmp").unwrap();
+        let range = builder.build_insert_value(range, end, 1, "rangetmp").unwrap();
+        let range = builder.build_insert_value(range, inclusive, 2, "rangetmp").unwrap();
+        Some(AnyValueEnum::StructValue(range.into_struct_value()))
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        match self.start.data_type(scope_manager) {
+            DataType::Unknown => DataType::Range(Box::new(self.end.data_type(scope_manager))),
+            start => DataType::Range(Box::new(start)),
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let start = self.start.check(checker);
+        let end = self.end.check(checker);
+        let valid = |data_type: &DataType| data_type.is_integer() || matches!(data_type, DataType::Char | DataType::Unknown);
+        if !valid(&start) || !valid(&end) || (start != end && start != DataType::Unknown && end != DataType::Unknown) {
+            checker.error(error!(ErrorKind::TypeError, "Range has to be made of integers of the same type",
+                                 ErrorTokenComponent::new("Invalid range".to_string(), Some(self.token.clone()))
+                                 NameErrorComponent::new(format!("Start: {}", start))
+                                 NameErrorComponent::new(format!("End: {}", end))));
+            return DataType::Unknown;
+        }
+        DataType::Range(Box::new(if start == DataType::Unknown { end } else { start }))
+    }
+
+    fn to_string(&self) -> String {
+        let range = if self.inclusive { "..=" } else { ".." };
+        format!("{}{}{}", self.start.to_string(), range, self.end.to_string())
+    }
+}
+
+/// Ranges store both ends and whether the end is included
+pub fn range_type<'a>(context: &'a Context, element: BasicTypeEnum<'a>) -> StructType<'a> {
+    context.struct_type(&[element, element, context.bool_type().into()], false)
+}
+
+/// Start, end and whether the end is included
+pub fn range_parts<'a>(builder: &Builder<'a>, range: StructValue<'a>) -> (IntValue<'a>, IntValue<'a>, IntValue<'a>) {
+    let start = builder.build_extract_value(range, 0, "rangestart").unwrap().into_int_value();
+    let end = builder.build_extract_value(range, 1, "rangeend").unwrap().into_int_value();
+    let inclusive = builder.build_extract_value(range, 2, "rangeincl").unwrap().into_int_value();
+    (start, end, inclusive)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::{checker::check_source, compiler::run_source};
+
+    fn parse_value(input: &str) -> String {
+        let tokens = tokenize(input);
+        let mut pos = 0;
+        ValueExpr::parse(&tokens, &mut pos).unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(parse_value("0..n - 1;"), "I32(0)..SUBTRACT VarCall n, I32(1)");
+        assert_eq!(parse_value("a[i..j][0]..=x as i32;"), "Index Slice VarCall a[VarCall i..VarCall j][I32(0)]..=Cast VarCall x as I32");
+        assert_eq!(parse_value("f(1..=2, x);"), "Call f(I32(1)..=I32(2), VarCall x)");
+    }
+
+    #[test]
+    fn check_ranges() {
+        assert!(check_source("fun f(r: range<i64>): i64 { var total = 0i64; for (i in r) { total += i; } for (c in 'a'..='z') { total += c as i64; } total }").is_empty());
+        assert_eq!(check_source("fun f(): void { for (k in 5) {} }"), vec!["TypeError: For loop needs a range"]);
+        assert_eq!(check_source("fun f(): void { 1..2.5; }"), vec!["TypeError: Range has to be made of integers of the same type"]);
+        assert_eq!(check_source("fun f(r: range<i64>): void { var t: range = r; }"), vec!["TypeError: Variable types do not match"]);
+    }
+
+    #[test]
+    fn run_ranges() {
+        assert_eq!(run_source("fun main(): i32 { var r = 1..=4; sum(r) * 10 + sum(0..3) } fun sum(r: range): i32 { var total = 0; for (i in r) { total += i; } total }"), 103);
+    }
+}
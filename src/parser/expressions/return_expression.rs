@@ -0,0 +1,153 @@
+use inkwell::{builder::Builder, types::AnyType, values::{AnyValueEnum, FunctionValue}};
+
+use crate::{compiler::checker::{TypeChecker, compatible}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, VoidExpr, data_types::{DataType, ToBasic}, value_expression::ValueExpr, scope::ScopeManager};
+
+pub struct ReturnExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+}
+
+impl Parseable for ReturnExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should start with keyword "return" or "ret"
+        let token = tokens[*pos].clone();
+        if token.token_type != TokenType::Identifier("return".to_string()) && token.token_type != TokenType::Identifier("ret".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing return statement",
+                              ErrorTokenComponent::new("Expected 'return' keyword".to_string(), Some(token))));
+        }
+
+        // Could be followed by a value
+        *pos += 1;
+        let value = if tokens[*pos].token_type == TokenType::Separator(';') {
+            Box::new(VoidExpr {})
+        } else {
+            let start = *pos;
+            match ValueExpr::parse(tokens, pos) {
+                Ok(value) => value,
+                Err(err) if *pos > start => return Err(err),
+                Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing return statement",
+                                            ErrorTokenComponent::new("Expected return value or ';'".to_string(), Some(tokens[*pos].clone())))),
+            }
+        };
+
+        Ok(Box::new(ReturnExpr {
+            token,
+            value,
+        }))
+    }
+}
+
+impl ASTExpr for ReturnExpr {
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let function = match scope_manager.scope.function {
+            Some(ref function) => **function,
+            None => error!(ErrorKind::CompilerError, "Return outside of a function",
+                           ErrorTokenComponent::new("Can not return from here".to_string(), Some(self.token.clone()))).panic()
+        };
+        let value = self.value.generate(context, module, builder, scope_manager);
+        build_return(builder, function, value, &self.token);
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let found = self.value.check(checker);
+        let expected = match checker.return_type() {
+            Some(expected) => expected,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Return outside of a function",
+                                     ErrorTokenComponent::new("Can not return from here".to_string(), Some(self.token.clone()))));
+                return DataType::Never;
+            }
+        };
+        check_return(checker, &expected, &found, &self.token);
+        DataType::Never
+    }
+
+    fn to_string(&self) -> String {
+        format!("Return {}", self.value.to_string())
+    }
+}
+
+/// Returns the value from the function, checking it against the return type
+pub fn build_return<'a>(builder: &Builder<'a>, function: FunctionValue<'a>, value: Option<AnyValueEnum<'a>>, token: &Token) {
+    let name = function.get_name().to_string_lossy().to_string();
+    match (function.get_type().get_return_type(), value) {
+        (None, None) => { builder.build_return(None); }
+        (None, Some(_)) => error!(ErrorKind::CompilerError, "Can not return a value from void function",
+                                  ErrorTokenComponent::new(format!("Function '{}' does not return anything", name), Some(token.clone()))).panic(),
+        (Some(_), None) => error!(ErrorKind::CompilerError, "Expected return value",
+                                  ErrorTokenComponent::new(format!("Function '{}' has to return a value", name), Some(token.clone()))).panic(),
+        (Some(return_type), Some(value)) => {
+            if value.get_type() != return_type.as_any_type_enum() {
+                error!(ErrorKind::CompilerError, "Return type does not match",
+                       ErrorTokenComponent::new(format!("Function '{}' returns a value of wrong type", name), Some(token.clone()))
+                       NameErrorComponent::new(format!("Expected: {}", return_type))
+                       NameErrorComponent::new(format!("Found: {}", value.get_type()))).panic()
+            }
+            builder.build_return(Some(&value.to_basic()));
+        }
+    }
+}
+
+/// Checks the returned value against the return type of the function
+pub fn check_return(checker: &mut TypeChecker, expected: &DataType, found: &DataType, token: &Token) {
+    match (expected, found) {
+        (DataType::Void, DataType::Void | DataType::Unknown | DataType::Never) => {}
+        (DataType::Void, _) => checker.error(error!(ErrorKind::TypeError, "Can not return a value from void function",
+                                                    ErrorTokenComponent::new("Function does not return anything".to_string(), Some(token.clone())))),
+        (_, DataType::Void) => checker.error(error!(ErrorKind::TypeError, "Expected return value",
+                                                    ErrorTokenComponent::new("Function has to return a value".to_string(), Some(token.clone())))),
+        (expected, found) if !compatible(expected, found) => checker.error(error!(ErrorKind::TypeError, "Return type does not match",
+                                                                                  ErrorTokenComponent::new("Function returns a value of wrong type".to_string(), Some(token.clone()))
+                                                                                  NameErrorComponent::new(format!("Expected: {}", expected))
+                                                                                  NameErrorComponent::new(format!("Found: {}", found)))),
+        _ => {}
+    }
+}
+
+/// Whether the current block already ends with a terminator, like a return
+pub fn is_terminated(builder: &Builder) -> bool {
+    builder.get_insert_block().and_then(|block| block.get_terminator()).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::TokenType, test_token, parser::expressions::Parseable};
+    use crate::compiler::compiler::run_source;
+
+    #[test]
+    fn parse_return_value() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("return".to_string())),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("+".to_string())),
+            test_token!(TokenType::Number("1".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = super::ReturnExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Return ADD VarCall x, I32(1)");
+    }
+
+    #[test]
+    fn parse_return_without_value() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("ret".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = super::ReturnExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Return NOP");
+        assert_eq!(pos, 1);
+    }
+
+    #[test]
+    fn run_returns() {
+        assert_eq!(run_source("fun main(): i32 { return 42; }"), 42);
+        assert_eq!(run_source("fun main(): i32 { ret 1; 2 }"), 1);
+        assert_eq!(run_source("fun main(): i32 { 40 + 2 }"), 42);
+        assert_eq!(run_source("fun main(): void { return; }"), 0);
+    }
+}
@@ -1,41 +1,127 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use inkwell::targets::TargetData;
+use inkwell::types::StructType;
 use inkwell::values::{PointerValue, FunctionValue, BasicValueEnum};
 use inkwell::basic_block::BasicBlock;
 
+use crate::compiler::{checker::ClassSignature, units::Units};
+
+use super::data_types::DataType;
+
+/// Mutable variable, value is stored in the alloca
+#[derive(Debug, Clone)]
+pub struct Variable<'a> {
+    pub pointer: PointerValue<'a>,
+    pub data_type: DataType,
+}
+
+/// Value that can not be assigned to, like a function argument or a constant
+#[derive(Debug, Clone)]
+pub struct Value<'a> {
+    pub value: BasicValueEnum<'a>,
+    pub data_type: DataType,
+}
+
+/// Function with the types it was declared with
+#[derive(Debug, Clone)]
+pub struct Function<'a> {
+    pub value: FunctionValue<'a>,
+    pub arguments: Vec<DataType>,
+    pub return_type: DataType,
+}
+
+/// Enum with the field types of its variants, values are a tag and space for the largest variant
+#[derive(Debug, Clone)]
+pub struct Enum<'a> {
+    pub struct_type: StructType<'a>,
+    pub variants: Vec<(String, Vec<DataType>)>,
+    /// Whether other files can import the enum
+    pub exported: bool,
+}
+
+/// Class with the types of its members, methods are functions named `Class.method`
+#[derive(Debug, Clone)]
+pub struct Class<'a> {
+    pub struct_type: StructType<'a>,
+    pub signature: ClassSignature,
+    /// Whether other files can import the class, its public methods are exported with it
+    pub exported: bool,
+}
+
 #[derive(Debug)]
 pub struct Scope<'a> {
-    pub variables: HashMap<String, Rc<PointerValue<'a>>>,
-    pub functions: HashMap<String, Rc<FunctionValue<'a>>>,
-    pub fn_args: HashMap<String, Rc<BasicValueEnum<'a>>>,
+    pub variables: HashMap<String, Rc<Variable<'a>>>,
+    pub constants: HashMap<String, Rc<Value<'a>>>,
+    pub functions: HashMap<String, Rc<Function<'a>>>,
+    pub enums: HashMap<String, Rc<Enum<'a>>>,
+    pub classes: HashMap<String, Rc<Class<'a>>>,
+    pub fn_args: HashMap<String, Rc<Value<'a>>>,
     pub function: Option<Rc<FunctionValue<'a>>>,
     pub block: Option<Rc<BasicBlock<'a>>>
 }
 
+/// Blocks that `continue` and `break` jump to inside a loop
+#[derive(Debug)]
+pub struct LoopContext<'a> {
+    pub continue_block: BasicBlock<'a>,
+    pub break_block: BasicBlock<'a>,
+    pub broken: bool,
+}
+
 #[derive(Debug)]
 pub struct ScopeManager<'a> {
     pub scope: Scope<'a>,
     scopes: Vec<Box<Scope<'a>>>,
     pub function: Option<&'a FunctionValue<'a>>,
     pub block: Option<&'a BasicBlock<'a>>,
+    pub units: Units<'a>,
+    pub loops: Vec<LoopContext<'a>>,
+    /// Whether runtime checks, like array bounds, are generated
+    pub error_handler: bool,
+    /// Layout of the target, payloads of enums are sized with it
+    pub target_data: TargetData,
 }
 
 impl<'a> ScopeManager<'a> {
-    pub fn new() -> ScopeManager<'a> {
+    pub fn new(target_data: TargetData) -> ScopeManager<'a> {
         let mut global = Scope::new();
         ScopeManager {
             scope: global,
             scopes: vec![],
             function: None,
             block: None,
+            units: Units::new(),
+            loops: vec![],
+            error_handler: true,
+            target_data,
         }
     }
 
+    /// Starts a new file with an empty global scope, returns scopes of the previous file
+    pub fn enter_file(&mut self) -> (Scope<'a>, Vec<Box<Scope<'a>>>) {
+        let scope = std::mem::replace(&mut self.scope, Scope::new());
+        (scope, std::mem::take(&mut self.scopes))
+    }
+
+    pub fn exit_file(&mut self, saved: (Scope<'a>, Vec<Box<Scope<'a>>>)) {
+        self.scope = saved.0;
+        self.scopes = saved.1;
+    }
+
     pub fn create_scope(&mut self) {
         let scope = self.scope.extend();
-        self.scopes.push(Box::new(self.scope.extend()));
-        self.scope = scope; 
+        self.scopes.push(Box::new(std::mem::replace(&mut self.scope, scope)));
+    }
+
+    pub fn enter_loop(&mut self, continue_block: BasicBlock<'a>, break_block: BasicBlock<'a>) {
+        self.loops.push(LoopContext { continue_block, break_block, broken: false });
+    }
+
+    /// Returns context of the loop, to know if it was exited with `break`
+    pub fn exit_loop(&mut self) -> LoopContext<'a> {
+        self.loops.pop().expect("Cannot exit loop outside of a loop")
     }
 
     pub fn exit_scope(&mut self) {
@@ -48,19 +134,44 @@ impl<'a> Scope<'a> {
     pub fn new() -> Scope<'a> {
         Scope {
             variables: HashMap::new(),
+            constants: HashMap::new(),
             functions: HashMap::new(),
+            enums: HashMap::new(),
+            classes: HashMap::new(),
             fn_args: HashMap::new(),
             function: None,
             block: None,
         }
     }
 
+    /// Struct of the enum or class with the name
+    pub fn named_type(&self, name: &str) -> Option<StructType<'a>> {
+        match (self.enums.get(name), self.classes.get(name)) {
+            (Some(declared), _) => Some(declared.struct_type),
+            (_, Some(declared)) => Some(declared.struct_type),
+            _ => None,
+        }
+    }
+
+    /// Whether every enum and class the type is made of is declared, unknown names are reported by the checker
+    pub fn declares(&self, data_type: &DataType) -> bool {
+        data_type.names().iter().all(|name| self.named_type(name).is_some())
+    }
+
+    /// Whether the layout of every enum and class the type is made of is known, so it can be stored
+    pub fn stores(&self, data_type: &DataType) -> bool {
+        data_type.names().iter().all(|name| self.named_type(name).map_or(false, |struct_type| !struct_type.is_opaque()))
+    }
+
     pub fn extend(&self) -> Scope<'a> {
         Scope {
             variables: self.variables.clone(),
+            constants: self.constants.clone(),
             functions: self.functions.clone(),
+            enums: self.enums.clone(),
+            classes: self.classes.clone(),
             fn_args: self.fn_args.clone(),
-            function: None,
+            function: self.function.clone(),
             block: None
         }
     }
@@ -0,0 +1,226 @@
+use inkwell::{builder::Builder, context::Context, module::Module, values::{AnyValue, AnyValueEnum, BasicMetadataValueEnum, BasicValueEnum}};
+
+use crate::{compiler::checker::{TypeChecker, compatible}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, data_types::{DataType, ToBasic}, value_expression::ValueExpr, enum_expression::{build_variant, variant_type}, class_expression::init_name, call_expression::check_arguments, variable_definition_expression::build_entry_alloca, scope::ScopeManager};
+
+/// Member of a type, like the enum variant `Shape::Circle(1.0)` or the static method `Counter::new(1)`
+pub struct StaticGetExpr {
+    token: Token,
+    owner: String,
+    member: String,
+    arguments: Vec<Box<dyn ASTExpr>>,
+}
+
+impl Parseable for StaticGetExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Should be a name followed by "::" and another name
+        let token = tokens[*pos].clone();
+        let not_static_get = |pos: usize| error!(ErrorKind::ParserError, "Error while parsing static get",
+                                                 ErrorTokenComponent::new("Expected 'Type::member'".to_string(), Some(tokens[pos].clone())));
+        let owner = match token.token_type {
+            TokenType::Identifier(ref owner) => owner.clone(),
+            _ => return Err(not_static_get(*pos)),
+        };
+        if tokens[*pos + 1].token_type != TokenType::Separator(':') || tokens[*pos + 2].token_type != TokenType::Separator(':') {
+            return Err(not_static_get(*pos + 1));
+        }
+        *pos += 3;
+        let member = match tokens[*pos].token_type {
+            TokenType::Identifier(ref member) => member.clone(),
+            _ => error!(ErrorKind::ParserError, "Error while parsing static get",
+                        ErrorTokenComponent::new("Expected member name after '::'".to_string(), Some(tokens[*pos].clone()))).panic()
+        };
+
+        // Could be followed by arguments in parentheses
+        *pos += 1;
+        let mut arguments = vec![];
+        if tokens[*pos].token_type == TokenType::Paren('(') {
+            *pos += 1;
+            while tokens[*pos].token_type != TokenType::Paren(')') {
+                match ValueExpr::parse(tokens, pos) {
+                    Ok(argument) => arguments.push(argument),
+                    Err(_) => error!(ErrorKind::ParserError, "Error while parsing static get",
+                                     ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone()))).panic()
+                }
+                if tokens[*pos].token_type == TokenType::Separator(',') {
+                    *pos += 1;
+                } else if tokens[*pos].token_type != TokenType::Paren(')') {
+                    error!(ErrorKind::ParserError, "Error while parsing static get",
+                           ErrorTokenComponent::new("Expected ',' or ')'".to_string(), Some(tokens[*pos].clone()))).panic()
+                }
+            }
+            *pos += 1;
+        }
+
+        Ok(Box::new(StaticGetExpr {
+            token,
+            owner,
+            member,
+            arguments,
+        }))
+    }
+}
+
+impl ASTExpr for StaticGetExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        if scope_manager.scope.classes.contains_key(&self.owner) {
+            return self.generate_call(context, module, builder, scope_manager);
+        }
+        let declared = match scope_manager.scope.enums.get(&self.owner) {
+            Some(declared) => declared.clone(),
+            None => error!(ErrorKind::CompilerError, "Type does not exist",
+                           ErrorTokenComponent::new(format!("Type {} was not declared", self.owner), Some(self.token.clone()))).panic()
+        };
+        let variant = match declared.variants.iter().position(|(name, _)| *name == self.member) {
+            Some(variant) => variant,
+            None => error!(ErrorKind::CompilerError, "Variant does not exist",
+                           ErrorTokenComponent::new(format!("{} has no variant {}", self.owner, self.member), Some(self.token.clone()))).panic()
+        };
+
+        // Fields are inserted one by one, so they do not have to be constants
+        let mut fields = variant_type(context, &declared.variants[variant].1).get_undef();
+        for (i, argument) in self.arguments.iter().enumerate() {
+            let value: BasicValueEnum<'a> = match argument.generate(context, module, builder, scope_manager) {
+                Some(value) => value.to_basic(),
+                None => error!(ErrorKind::CompilerError, "Expected value for variant field",
+                               ErrorTokenComponent::new(format!("Field {} has no value", i), Some(self.token.clone()))).panic()
+            };
+            fields = builder.build_insert_value(fields, value, i as u32, "fieldtmp").unwrap().into_struct_value();
+        }
+        Some(AnyValueEnum::StructValue(build_variant(context, builder, &declared, variant, fields)))
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        if let Some(class) = scope_manager.scope.classes.get(&self.owner) {
+            return match class.signature.methods.get(&self.member) {
+                Some(method) if method.this && method.signature.return_type == DataType::Void => DataType::Named(self.owner.clone()),
+                Some(method) => method.signature.return_type.clone(),
+                None => DataType::Unknown,
+            };
+        }
+        match scope_manager.scope.enums.get(&self.owner) {
+            Some(_) => DataType::Named(self.owner.clone()),
+            None => DataType::Unknown,
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let found = self.arguments.iter().map(|argument| argument.check(checker)).collect::<Vec<DataType>>();
+        if checker.class(&self.owner).is_some() {
+            return self.check_call(checker, &found);
+        }
+        let variants = match checker.enum_variants(&self.owner) {
+            Some(variants) => variants,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Type does not exist",
+                                     ErrorTokenComponent::new(format!("Type {} was not declared", self.owner), Some(self.token.clone()))));
+                return DataType::Unknown;
+            }
+        };
+        let fields = match variants.into_iter().find(|(name, _)| *name == self.member) {
+            Some((_, fields)) => fields,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Variant does not exist",
+                                     ErrorTokenComponent::new(format!("{} has no variant {}", self.owner, self.member), Some(self.token.clone()))));
+                return DataType::Unknown;
+            }
+        };
+
+        if fields.len() != found.len() {
+            checker.error(error!(ErrorKind::TypeError, "Wrong number of variant fields",
+                                 ErrorTokenComponent::new(format!("{}::{} has {} fields, but {} were given", self.owner, self.member, fields.len(), found.len()), Some(self.token.clone()))));
+        } else {
+            for (i, (expected, found)) in fields.iter().zip(found.iter()).enumerate() {
+                if !compatible(expected, found) {
+                    checker.error(error!(ErrorKind::TypeError, "Variant field types do not match",
+                                         ErrorTokenComponent::new(format!("Field {} of {}::{} has a different type", i, self.owner, self.member), Some(self.token.clone()))
+                                         NameErrorComponent::new(format!("Expected: {}", expected))
+                                         NameErrorComponent::new(format!("Found: {}", found))));
+                }
+            }
+        }
+        DataType::Named(self.owner.clone())
+    }
+
+    fn to_string(&self) -> String {
+        if self.arguments.is_empty() {
+            format!("Static {}::{}", self.owner, self.member)
+        } else {
+            format!("Static {}::{}({})", self.owner, self.member, self.arguments.iter().map(|argument| argument.to_string()).collect::<Vec<String>>().join(", "))
+        }
+    }
+}
+
+impl StaticGetExpr {
+    /// Static methods with `this` get a new instance, which is the value of the call unless they return one
+    fn generate_call<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let class = scope_manager.scope.classes.get(&self.owner).unwrap().clone();
+        let (function, this) = match (scope_manager.scope.functions.get(&format!("{}.{}", self.owner, self.member)), class.signature.methods.get(&self.member)) {
+            (Some(function), Some(method)) => (function.value, method.this),
+            _ => error!(ErrorKind::CompilerError, "Method does not exist",
+                        ErrorTokenComponent::new(format!("{} has no method {}", self.owner, self.member), Some(self.token.clone()))).panic()
+        };
+
+        let mut values: Vec<BasicMetadataValueEnum<'a>> = vec![];
+        let instance = if this {
+            let init = module.get_function(&init_name(&self.owner)).unwrap();
+            let memory = build_entry_alloca(context, builder, class.struct_type.into(), "instance");
+            let value = builder.build_call(init, &[], "inittmp").try_as_basic_value().left().unwrap();
+            builder.build_store(memory, value);
+            values.push(memory.into());
+            Some(memory)
+        } else {
+            None
+        };
+        for (i, argument) in self.arguments.iter().enumerate() {
+            match argument.generate(context, module, builder, scope_manager) {
+                Some(value) => values.push(value.to_basic().into()),
+                None => error!(ErrorKind::CompilerError, "Expected value for function argument",
+                               ErrorTokenComponent::new(format!("Argument {} of '{}' has no value", i + 1, self.member), Some(self.token.clone()))).panic()
+            }
+        }
+
+        let returns = function.get_type().get_return_type().is_some();
+        let call = builder.build_call(function, &values, if returns { "calltmp" } else { "" });
+        match instance {
+            Some(memory) if !returns => Some(builder.build_load(memory, "instanceval").as_any_value_enum()),
+            _ => call.try_as_basic_value().left().map(|value| value.as_any_value_enum()),
+        }
+    }
+
+    fn check_call(&self, checker: &mut TypeChecker, found: &[DataType]) -> DataType {
+        let class = checker.class(&self.owner).unwrap();
+        let method = match class.methods.get(&self.member) {
+            Some(method) => method,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Method does not exist",
+                                     ErrorTokenComponent::new(format!("{} has no method {}", self.owner, self.member), Some(self.token.clone()))));
+                return DataType::Unknown;
+            }
+        };
+        checker.check_visible(&self.owner, &self.member, method.public, &self.token);
+        if !method.is_static {
+            checker.error(error!(ErrorKind::TypeError, "Method is not static",
+                                 ErrorTokenComponent::new(format!("{}::{} has to be called on an instance", self.owner, self.member), Some(self.token.clone()))));
+        } else {
+            check_arguments(checker, &self.token, &format!("{}::{}", self.owner, self.member), &method.signature.arguments, found);
+        }
+        match method.signature.return_type {
+            DataType::Void if method.this => DataType::Named(self.owner.clone()),
+            ref return_type => return_type.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::{Parseable, value_expression::ValueExpr}};
+
+    #[test]
+    fn parse_static_get() {
+        let tokens = tokenize("Shape::Rect(1.0, w * 2.0) + Shape::Empty;");
+        let mut pos = 0;
+        assert_eq!(ValueExpr::parse(&tokens, &mut pos).unwrap().to_string(), "ADD Static Shape::Rect(F64(1.0), MULTIPLY VarCall w, F64(2.0)), Static Shape::Empty");
+    }
+}
@@ -0,0 +1,220 @@
+use std::str::Chars;
+
+use inkwell::{AddressSpace, IntPredicate, builder::Builder, context::Context, module::{Linkage, Module}, types::StructType, values::{AnyValueEnum, IntValue, PointerValue, StructValue}};
+
+use crate::{compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::token_component::ErrorTokenComponent}, error};
+
+use super::{ASTExpr, Parseable, data_types::DataType, scope::ScopeManager, array_expression::build_slice};
+
+pub struct StringLiteralExpr {
+    value: String,
+}
+
+impl Parseable for StringLiteralExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let token = tokens[*pos].clone();
+        match token.token_type {
+            TokenType::String(ref literal) => {
+                *pos += 1;
+                // Token still contains the quotes
+                Ok(Box::new(StringLiteralExpr {
+                    value: unescape(&literal[1..literal.len() - 1], &token)?,
+                }))
+            }
+            _ => Err(error!(ErrorKind::ParserError, "Error while parsing string literal",
+                            ErrorTokenComponent::new("Expected string literal".to_string(), Some(token.clone()))))
+        }
+    }
+}
+
+impl ASTExpr for StringLiteralExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, _builder: &Builder<'a>, _scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        Some(AnyValueEnum::StructValue(build_string_constant(context, module, &self.value)))
+    }
+
+    fn data_type<'a>(&self, _scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::String
+    }
+
+    fn check(&self, _checker: &mut TypeChecker) -> DataType {
+        DataType::String
+    }
+
+    fn evaluate(&self, _checker: &TypeChecker) -> Option<Constant> {
+        Some(Constant::Str(self.value.clone()))
+    }
+
+    fn to_string(&self) -> String {
+        format!("Str({:?})", self.value)
+    }
+}
+
+/// Replaces escape sequences in the literal, the token is used for errors
+pub fn unescape(literal: &str, token: &Token) -> Result<String, Error> {
+    let mut value = String::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        value.push(match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('u') => match unicode_escape(&mut chars) {
+                Some(c) => c,
+                None => return Err(error!(ErrorKind::ParserError, "Error while parsing escape sequence",
+                                          ErrorTokenComponent::new("Expected unicode escape like '\\u{1F600}'".to_string(), Some(token.clone())))),
+            },
+            c => return Err(error!(ErrorKind::ParserError, "Error while parsing escape sequence",
+                                   ErrorTokenComponent::new(format!("Unknown escape sequence '\\{}'", c.map(String::from).unwrap_or_default()), Some(token.clone())))),
+        });
+    }
+    Ok(value)
+}
+
+/// Reads the hexadecimal code point of `\u{..}`
+fn unicode_escape(chars: &mut Chars) -> Option<char> {
+    if chars.next()? != '{' {
+        return None;
+    }
+    let mut code = String::new();
+    loop {
+        match chars.next()? {
+            '}' => break,
+            c => code.push(c),
+        }
+    }
+    if code.is_empty() || code.len() > 6 {
+        return None;
+    }
+    char::from_u32(u32::from_str_radix(&code, 16).ok()?)
+}
+
+/// Strings are a pointer to their bytes and the number of bytes, they are not null terminated
+pub fn string_type<'a>(context: &'a Context) -> StructType<'a> {
+    context.struct_type(&[context.i8_type().ptr_type(AddressSpace::Generic).into(), context.i64_type().into()], false)
+}
+
+/// Bytes of the string are stored in a global, so the value is a constant
+pub fn build_string_constant<'a>(context: &'a Context, module: &Module<'a>, value: &str) -> StructValue<'a> {
+    let bytes = value.as_bytes();
+    let data = module.add_global(context.i8_type().array_type(bytes.len() as u32), None, "str");
+    data.set_initializer(&context.const_string(bytes, false));
+    data.set_constant(true);
+    data.set_unnamed_addr(true);
+    data.set_linkage(Linkage::Private);
+    let pointer = data.as_pointer_value().const_cast(context.i8_type().ptr_type(AddressSpace::Generic));
+    context.const_struct(&[pointer.into(), context.i64_type().const_int(bytes.len() as u64, false).into()], false)
+}
+
+/// Copies both strings into newly allocated memory
+pub fn build_concat<'a>(context: &'a Context, builder: &Builder<'a>, lhs: StructValue<'a>, rhs: StructValue<'a>) -> StructValue<'a> {
+    let (lhs_data, lhs_len) = string_parts(builder, lhs);
+    let (rhs_data, rhs_len) = string_parts(builder, rhs);
+    let len = builder.build_int_add(lhs_len, rhs_len, "strlen");
+    let data = builder.build_array_malloc(context.i8_type(), len, "strdata").unwrap();
+    builder.build_memcpy(data, 1, lhs_data, 1, lhs_len).unwrap();
+    let end = unsafe { builder.build_in_bounds_gep(data, &[lhs_len], "strend") };
+    builder.build_memcpy(end, 1, rhs_data, 1, rhs_len).unwrap();
+    build_slice(builder, string_type(context), data, len)
+}
+
+/// Strings are equal when they have the same length and the same bytes
+pub fn build_string_equal<'a>(context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, lhs: StructValue<'a>, rhs: StructValue<'a>) -> IntValue<'a> {
+    let (lhs_data, lhs_len) = string_parts(builder, lhs);
+    let (rhs_data, rhs_len) = string_parts(builder, rhs);
+    let same_len = builder.build_int_compare(IntPredicate::EQ, lhs_len, rhs_len, "samelen");
+    // No bytes are compared if the lengths differ, so memcmp never reads past the shorter string
+    let len = builder.build_select(same_len, lhs_len, context.i64_type().const_zero(), "cmplen").into_int_value();
+    let memcmp = match module.get_function("memcmp") {
+        Some(memcmp) => memcmp,
+        None => {
+            let pointer = context.i8_type().ptr_type(AddressSpace::Generic);
+            let fn_type = context.i32_type().fn_type(&[pointer.into(), pointer.into(), context.i64_type().into()], false);
+            module.add_function("memcmp", fn_type, Some(Linkage::External))
+        }
+    };
+    let result = builder.build_call(memcmp, &[lhs_data.into(), rhs_data.into(), len.into()], "memcmp").try_as_basic_value().left().unwrap().into_int_value();
+    let same_bytes = builder.build_int_compare(IntPredicate::EQ, result, context.i32_type().const_zero(), "samebytes");
+    builder.build_and(same_len, same_bytes, "streq")
+}
+
+/// Writes the integer in decimal into newly allocated memory, unsigned integers are never negative
+pub fn build_int_to_string<'a>(context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, value: IntValue<'a>, unsigned: bool) -> StructValue<'a> {
+    let value = if value.get_type().get_bit_width() == 64 {
+        value
+    } else if unsigned {
+        builder.build_int_z_extend(value, context.i64_type(), "strint")
+    } else {
+        builder.build_int_s_extend(value, context.i64_type(), "strint")
+    };
+    // Longest value is -9223372036854775808, snprintf also writes a null byte
+    let size = context.i64_type().const_int(21, false);
+    let data = builder.build_array_malloc(context.i8_type(), size, "strdata").unwrap();
+    let snprintf = match module.get_function("snprintf") {
+        Some(snprintf) => snprintf,
+        None => {
+            let pointer = context.i8_type().ptr_type(AddressSpace::Generic);
+            let fn_type = context.i32_type().fn_type(&[pointer.into(), context.i64_type().into(), pointer.into()], true);
+            module.add_function("snprintf", fn_type, Some(Linkage::External))
+        }
+    };
+    let format = builder.build_global_string_ptr(if unsigned { "%llu" } else { "%lld" }, "intfmt").as_pointer_value();
+    let len = builder.build_call(snprintf, &[data.into(), size.into(), format.into(), value.into()], "strlen").try_as_basic_value().left().unwrap().into_int_value();
+    let len = builder.build_int_z_extend(len, context.i64_type(), "strlen");
+    build_slice(builder, string_type(context), data, len)
+}
+
+pub fn string_parts<'a>(builder: &Builder<'a>, string: StructValue<'a>) -> (PointerValue<'a>, IntValue<'a>) {
+    let data = builder.build_extract_value(string, 0, "strdata").unwrap().into_pointer_value();
+    let len = builder.build_extract_value(string, 1, "strlen").unwrap().into_int_value();
+    (data, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::{Parseable, value_expression::ValueExpr}};
+    use crate::compiler::{checker::check_source, compiler::run_source};
+
+    fn parse_value(input: &str) -> String {
+        let tokens = tokenize(input);
+        let mut pos = 0;
+        ValueExpr::parse(&tokens, &mut pos).unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_escape_sequences() {
+        assert_eq!(parse_value(r#""a\tb\n\"c\"\\";"#), r#"Str("a\tb\n\"c\"\\")"#);
+        assert_eq!(parse_value(r#""\u{48}\u{1F600}";"#), "Str(\"H\u{1F600}\")");
+    }
+
+    #[test]
+    fn parse_string_operations() {
+        assert_eq!(parse_value(r#"a + "b" == c;"#), r#"EQUAL ADD VarCall a, Str("b"), VarCall c"#);
+        assert_eq!(parse_value(r#""abc"[i + 1];"#), r#"Index Str("abc")[ADD VarCall i, I32(1)]"#);
+    }
+
+    #[test]
+    fn check_strings() {
+        assert!(check_source(r#"fun f(s: str): bool { var t = s + "!"; s != t }"#).is_empty());
+        assert_eq!(check_source("fun f(s: str): bool { s < s }"), vec!["TypeError: Strings can only be compared for equality"]);
+        assert_eq!(check_source("fun f(s: str): str { s - s }"), vec!["TypeError: Values can not be used in math operation"]);
+        assert!(check_source("fun f(x: u32): str { x as str + (-1 as str) }").is_empty());
+        assert_eq!(check_source("fun f(s: str): i32 { s as i32 }"), vec!["TypeError: Invalid cast"]);
+        assert_eq!(check_source("fun f(c: char): str { c as str }"), vec!["TypeError: Invalid cast"]);
+    }
+
+    #[test]
+    fn run_strings() {
+        assert_eq!(run_source(r#"const greeting = "Hello"; fun main(): i32 { var s = greeting + ", "; s += "world"; s[7] as i32 + (s == "Hello, world") as i32 }"#), 120);
+        assert_eq!(run_source(r#"fun main(): i32 { ("ab" != "abc") as i32 + "\u{48}i\n"[2] as i32 }"#), 11);
+        let source = r#"fun text(x: u32, y: i8): str { x as str + " " + y as str } fun main(): i32 { (text(4294967295u32, -128i8) == "4294967295 -128") as i32 + (250u8 as str == "250") as i32 }"#;
+        assert_eq!(run_source(source), 2);
+    }
+}
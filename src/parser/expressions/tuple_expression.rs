@@ -0,0 +1,295 @@
+use std::{cell::RefCell, rc::Rc};
+
+use colored::*;
+use inkwell::{builder::Builder, context::Context, module::Module, types::{BasicTypeEnum, StructType}, values::{AnyValueEnum, BasicValueEnum, StructValue}};
+
+use crate::{compiler::checker::{TypeChecker, BindingKind}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+
+use super::{ASTExpr, Parseable, data_types::{DataType, ToAny, ToBasic}, value_expression::ValueExpr, variable_definition_expression::build_entry_alloca, scope::{ScopeManager, Variable}};
+
+/// Tuple literal, like `(1, 2.0)` or `(1,)`
+pub struct TupleLiteralExpr {
+    token: Token,
+    elements: Vec<Box<dyn ASTExpr>>,
+}
+
+impl TupleLiteralExpr {
+    pub fn new(token: Token, elements: Vec<Box<dyn ASTExpr>>) -> TupleLiteralExpr {
+        TupleLiteralExpr { token, elements }
+    }
+}
+
+/// Element of a tuple, like `t.0`
+pub struct TupleFieldExpr {
+    token: Token,
+    value: Box<dyn ASTExpr>,
+    index: u32,
+}
+
+impl TupleFieldExpr {
+    pub fn new(token: Token, value: Box<dyn ASTExpr>, index: u32) -> TupleFieldExpr {
+        TupleFieldExpr { token, value, index }
+    }
+}
+
+/// Defines a variable for every element of a tuple, like `(a, b) := (1, 2)`, `_` skips the element
+pub struct DestructureExpr {
+    token: Token,
+    names: Vec<String>,
+    value: Box<dyn ASTExpr>,
+    // Types of the elements, they are known after the expression was checked
+    element_types: RefCell<Vec<DataType>>,
+}
+
+impl Parseable for DestructureExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let token = tokens[*pos].clone();
+        let not_destructuring = |pos: usize| error!(ErrorKind::ParserError, "Error while parsing destructuring",
+                                                    ErrorTokenComponent::new("Expected names in parentheses followed by ':='".to_string(), Some(tokens[pos].clone())));
+        if token.token_type != TokenType::Paren('(') {
+            return Err(not_destructuring(*pos));
+        }
+
+        // Should be followed by names separated by commas
+        *pos += 1;
+        let mut names = vec![];
+        loop {
+            match tokens[*pos].token_type {
+                TokenType::Identifier(ref name) => names.push(name.clone()),
+                _ => return Err(not_destructuring(*pos)),
+            }
+            *pos += 1;
+            match tokens[*pos].token_type {
+                TokenType::Separator(',') => *pos += 1,
+                TokenType::Paren(')') => break,
+                _ => return Err(not_destructuring(*pos)),
+            }
+        }
+
+        // Should be followed by ":="
+        *pos += 1;
+        if tokens[*pos].token_type != TokenType::Separator(':') || tokens[*pos + 1].token_type != TokenType::Operator("=".to_string()) {
+            return Err(not_destructuring(*pos));
+        }
+
+        // Should be followed by a value
+        *pos += 2;
+        let value = match ValueExpr::parse(tokens, pos) {
+            Ok(value) => value,
+            Err(_) => error!(ErrorKind::ParserError, "Error while parsing destructuring",
+                             ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone()))).panic()
+        };
+
+        Ok(Box::new(DestructureExpr {
+            token,
+            names,
+            value,
+            element_types: RefCell::new(vec![]),
+        }))
+    }
+}
+
+impl ASTExpr for TupleLiteralExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let mut values: Vec<BasicValueEnum<'a>> = vec![];
+        for (i, element) in self.elements.iter().enumerate() {
+            match element.generate(context, module, builder, scope_manager) {
+                Some(value) => values.push(value.to_basic()),
+                None => error!(ErrorKind::CompilerError, "Expected value for tuple element",
+                               ErrorTokenComponent::new(format!("Element {} has no value", i), Some(self.token.clone()))).panic()
+            }
+        }
+
+        // Elements are inserted one by one, so they do not have to be constants
+        let types = values.iter().map(|value| value.get_type()).collect::<Vec<BasicTypeEnum<'a>>>();
+        let mut tuple = context.struct_type(&types, false).get_undef();
+        for (i, value) in values.into_iter().enumerate() {
+            tuple = builder.build_insert_value(tuple, value, i as u32, "tupletmp").unwrap().into_struct_value();
+        }
+        Some(AnyValueEnum::StructValue(tuple))
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        DataType::Tuple(self.elements.iter().map(|element| element.data_type(scope_manager)).collect())
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let mut types = vec![];
+        for (i, element) in self.elements.iter().enumerate() {
+            let found = element.check(checker);
+            if matches!(found, DataType::Void | DataType::Never) {
+                checker.error(error!(ErrorKind::TypeError, "Tuple elements have to be values",
+                                     ErrorTokenComponent::new(format!("Element {} of type {} can not be stored", i, found), Some(self.token.clone()))));
+                types.push(DataType::Unknown);
+            } else {
+                types.push(found);
+            }
+        }
+        DataType::Tuple(types)
+    }
+
+    fn to_string(&self) -> String {
+        format!("Tuple ({})", self.elements.iter().map(|element| element.to_string()).collect::<Vec<String>>().join(", "))
+    }
+}
+
+impl ASTExpr for TupleFieldExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        match self.value.generate(context, module, builder, scope_manager) {
+            Some(AnyValueEnum::StructValue(tuple)) => Some(build_tuple_field(builder, tuple, self.index, &self.token).to_any()),
+            _ => error!(ErrorKind::CompilerError, "Value is not a tuple",
+                        ErrorTokenComponent::new(format!("Field {} does not exist", self.index), Some(self.token.clone()))).panic()
+        }
+    }
+
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        match self.value.data_type(scope_manager) {
+            DataType::Tuple(mut types) if (self.index as usize) < types.len() => types.swap_remove(self.index as usize),
+            _ => DataType::Unknown,
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let value = self.value.check(checker);
+        check_tuple_field(checker, &self.token, value, self.index as i128)
+    }
+
+    fn to_string(&self) -> String {
+        format!("Field {}.{}", self.value.to_string(), self.index)
+    }
+}
+
+impl ASTExpr for DestructureExpr {
+    fn generate<'a, 'b>(&self, context: &'a Context, module: &Module<'a>, builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let tuple = match self.value.generate(context, module, builder, scope_manager) {
+            Some(AnyValueEnum::StructValue(tuple)) => tuple,
+            _ => error!(ErrorKind::CompilerError, "Destructured value is not a tuple",
+                        ErrorTokenComponent::new("Expected tuple".to_string(), Some(self.token.clone()))).panic()
+        };
+
+        // Every element is stored in a new variable, like with `var`
+        let element_types = self.element_types.borrow();
+        for (i, (name, data_type)) in self.names.iter().zip(element_types.iter()).enumerate() {
+            if name == "_" {
+                continue;
+            }
+            let value = build_tuple_field(builder, tuple, i as u32, &self.token);
+            let alloca = build_entry_alloca(context, builder, value.get_type(), name);
+            builder.build_store(alloca, value);
+            scope_manager.scope.fn_args.remove(name);
+            scope_manager.scope.constants.remove(name);
+            scope_manager.scope.variables.insert(name.to_string(), Rc::new(Variable { pointer: alloca, data_type: data_type.clone() }));
+        }
+        None
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        let types = match self.value.check(checker) {
+            DataType::Tuple(types) if types.len() == self.names.len() => types,
+            DataType::Tuple(types) => {
+                checker.error(error!(ErrorKind::TypeError, "Wrong number of names in destructuring",
+                                     ErrorTokenComponent::new(format!("Tuple has {} elements, but {} names were given", types.len(), self.names.len()), Some(self.token.clone()))));
+                vec![DataType::Unknown; self.names.len()]
+            }
+            DataType::Unknown => vec![DataType::Unknown; self.names.len()],
+            found => {
+                checker.error(error!(ErrorKind::TypeError, "Destructured value is not a tuple",
+                                     ErrorTokenComponent::new("Expected tuple".to_string(), Some(self.token.clone()))
+                                     NameErrorComponent::new(format!("Found: {}", found))));
+                vec![DataType::Unknown; self.names.len()]
+            }
+        };
+
+        // Value is checked first, so it can not use the new variables
+        for (name, data_type) in self.names.iter().zip(types.iter()) {
+            if name != "_" {
+                checker.define(name, data_type.clone(), BindingKind::Variable);
+            }
+        }
+        *self.element_types.borrow_mut() = types;
+        DataType::Void
+    }
+
+    fn to_string(&self) -> String {
+        format!("Destructure ({}) = {}", self.names.join(", "), self.value.to_string())
+    }
+}
+
+/// Checks that the tuple has the element, returns the type of the element
+pub fn check_tuple_field(checker: &mut TypeChecker, token: &Token, value: DataType, index: i128) -> DataType {
+    match value {
+        DataType::Tuple(mut types) if (0..types.len() as i128).contains(&index) => types.swap_remove(index as usize),
+        DataType::Tuple(types) => {
+            checker.error(error!(ErrorKind::TypeError, "Tuple field does not exist",
+                                 ErrorTokenComponent::new(format!("Tuple has {} elements, but field {} was used", types.len(), index), Some(token.clone()))));
+            DataType::Unknown
+        }
+        DataType::Unknown => DataType::Unknown,
+        value => {
+            checker.error(error!(ErrorKind::TypeError, "Value is not a tuple",
+                                 ErrorTokenComponent::new(format!("Field {} does not exist on {}", index, value.to_string().green()), Some(token.clone()))));
+            DataType::Unknown
+        }
+    }
+}
+
+/// Tuples are anonymous structs of their elements
+pub fn tuple_type<'a>(context: &'a Context, elements: &[DataType]) -> StructType<'a> {
+    let types = elements.iter().map(|element| element.into_basic_type(context)).collect::<Vec<BasicTypeEnum<'a>>>();
+    context.struct_type(&types, false)
+}
+
+pub fn build_tuple_field<'a>(builder: &Builder<'a>, tuple: StructValue<'a>, index: u32, token: &Token) -> BasicValueEnum<'a> {
+    match builder.build_extract_value(tuple, index, "field") {
+        Some(value) => value,
+        None => error!(ErrorKind::CompilerError, "Tuple field does not exist",
+                       ErrorTokenComponent::new(format!("Field {} does not exist", index), Some(token.clone()))).panic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::{Parseable, value_expression::ValueExpr, basic_expression::BasicExpr}};
+    use crate::compiler::{checker::check_source, compiler::run_source};
+
+    fn parse_value(input: &str) -> String {
+        let tokens = tokenize(input);
+        let mut pos = 0;
+        ValueExpr::parse(&tokens, &mut pos).unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_tuples() {
+        assert_eq!(parse_value("(1, x + 2);"), "Tuple (I32(1), ADD VarCall x, I32(2))");
+        assert_eq!(parse_value("(1,);"), "Tuple (I32(1))");
+        assert_eq!(parse_value("(1);"), "I32(1)");
+    }
+
+    #[test]
+    fn parse_tuple_fields() {
+        assert_eq!(parse_value("t.1 + t.0.1;"), "ADD Field VarCall t.1, Field Field VarCall t.0.1");
+        assert_eq!(parse_value("f().0[2];"), "Index Field Call f().0[I32(2)]");
+    }
+
+    #[test]
+    fn parse_destructuring() {
+        let tokens = tokenize("(a, _) := (1, 2); (a, b);");
+        let mut pos = 0;
+        assert_eq!(BasicExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Destructure (a, _) = Tuple (I32(1), I32(2))");
+        assert_eq!(BasicExpr::parse(&tokens, &mut pos).unwrap().to_string(), "Tuple (VarCall a, VarCall b)");
+    }
+
+    #[test]
+    fn check_tuples() {
+        assert!(check_source("fun f(): (i32, f64) { var t = (1, 2.5); (a, b) := t; (t.0 + a, b + t[1]) }").is_empty());
+        assert_eq!(check_source("fun f(t: (i32, f64)): void { var i = 1; t[i]; }"), vec!["TypeError: Tuple index has to be a constant"]);
+        assert_eq!(check_source("fun f(t: (i32, f64)): void { t.2; }"), vec!["TypeError: Tuple field does not exist"]);
+        assert_eq!(check_source("fun f(t: (i32, f64)): void { (x, y, z) := t; }"), vec!["TypeError: Wrong number of names in destructuring"]);
+        assert_eq!(check_source("fun f(): void { (p, q) := 5; }"), vec!["TypeError: Destructured value is not a tuple"]);
+    }
+
+    #[test]
+    fn run_tuples() {
+        assert_eq!(run_source("fun main(): i32 { (q, r) := divmod(17, 5); var t: (i32, (u8, f64)) = (q, (2u8, 0.5)); (_, inner) := t; q * 100 + r * 10 + t.1.0 as i32 + (inner[1] * 2.0) as i32 } fun divmod(a: i32, b: i32): (i32, i32) { (a / b, a % b) }"), 324);
+    }
+}
@@ -1,18 +1,316 @@
-use crate::{try_parse, utils::error::Error};
+use crate::{try_parse, error, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::token_component::ErrorTokenComponent}};
 
-use super::{Parseable, math_expression::MathExpr, variable_definition_expression::VarDefExpr, block_expression::BlockExpr, literal_expression::LiteralExpr, variable_expression::VariableCallExpr }; 
+use super::{ASTExpr, Parseable, math_expression::{MathExpr, MathOperation, NegateExpr}, variable_definition_expression::VarDefExpr, block_expression::BlockExpr, literal_expression::LiteralExpr, variable_expression::VariableCallExpr, call_expression::CallExpr, if_expression::IfExpr, comparison_expression::{CompareExpr, CompareOperation}, logical_expression::{LogicalExpr, LogicalOperation, NotExpr}, cast_expression::CastExpr, index_expression::{IndexExpr, SliceExpr}, tuple_expression::{TupleLiteralExpr, TupleFieldExpr}, range_expression::RangeExpr, match_expression::MatchExpr, static_get_expression::StaticGetExpr, dynamic_get_expression::DynamicGetExpr, data_types::DataType };
 
+// Any value, including operators
 pub struct ValueExpr {}
-pub struct NoMathValueExpr {}
+// Value without operators
+pub struct PrimaryValueExpr {}
+
+/// Binding power of operators, higher binds tighter
+pub mod precedence {
+    pub const LOGICAL_OR: u8 = 1;
+    pub const LOGICAL_AND: u8 = 2;
+    pub const BIT_OR: u8 = 3;
+    pub const BIT_XOR: u8 = 4;
+    pub const BIT_AND: u8 = 5;
+    pub const EQUALITY: u8 = 6;
+    pub const COMPARISON: u8 = 7;
+    pub const SHIFT: u8 = 8;
+    pub const ADDITIVE: u8 = 9;
+    pub const MULTIPLICATIVE: u8 = 10;
+    pub const CAST: u8 = 11;
+    pub const UNARY: u8 = 12;
+}
+
+enum BinaryOperation {
+    Math(MathOperation),
+    Compare(CompareOperation),
+    Logical(LogicalOperation),
+}
 
 impl Parseable for ValueExpr {
-    fn parse(tokens: &Vec<crate::lexer::lexer::Token>, pos: &mut usize) -> Result<Box<dyn super::ASTExpr>, Error> {
-        return try_parse!(tokens, *pos, MathExpr NoMathValueExpr);
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        let value = parse_operators(tokens, pos, precedence::LOGICAL_OR)?;
+        parse_range(tokens, pos, value)
+    }
+}
+
+/// Ranges bind looser than any operator and can not be chained, like `0..n + 1`
+fn parse_range(tokens: &Vec<Token>, pos: &mut usize, start: Box<dyn ASTExpr>) -> Result<Box<dyn ASTExpr>, Error> {
+    let token = tokens[*pos].clone();
+    let inclusive = match token.token_type {
+        TokenType::Operator(ref op) if op == ".." => false,
+        TokenType::Operator(ref op) if op == "..=" => true,
+        _ => return Ok(start),
+    };
+    *pos += 1;
+    let end_pos = *pos;
+    let end = match parse_operators(tokens, pos, precedence::LOGICAL_OR) {
+        Ok(end) => end,
+        Err(err) if *pos > end_pos => return Err(err),
+        Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing range",
+                                    ErrorTokenComponent::new("Expected end of the range".to_string(), Some(tokens[*pos].clone())))),
+    };
+    Ok(Box::new(RangeExpr::new(token, start, end, inclusive)))
+}
+
+impl Parseable for PrimaryValueExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        return try_parse!(tokens, *pos, LiteralExpr BlockExpr IfExpr MatchExpr VarDefExpr StaticGetExpr CallExpr VariableCallExpr);
     }
 }
 
-impl Parseable for NoMathValueExpr {
-    fn parse(tokens: &Vec<crate::lexer::lexer::Token>, pos: &mut usize) -> Result<Box<dyn super::ASTExpr>, Error> {
-        return try_parse!(tokens, *pos, LiteralExpr BlockExpr VarDefExpr VariableCallExpr);
+/// Parses a value with binary operators that bind at least as tight as `min_precedence`, all of them are left associative
+pub fn parse_operators(tokens: &Vec<Token>, pos: &mut usize, min_precedence: u8) -> Result<Box<dyn ASTExpr>, Error> {
+    let mut lhs = parse_unary(tokens, pos)?;
+    loop {
+        let token = tokens[*pos].clone();
+
+        // Cast, like `x as i64`
+        if token.token_type == TokenType::Identifier("as".to_string()) {
+            if precedence::CAST < min_precedence {
+                break;
+            }
+            *pos += 1;
+            let data_type = match DataType::parse_type(tokens, pos) {
+                Ok(data_type) => data_type,
+                Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing cast",
+                                            ErrorTokenComponent::new("Expected type after 'as'".to_string(), Some(tokens[*pos].clone())))),
+            };
+            lhs = Box::new(CastExpr::new(token, lhs, data_type));
+            continue;
+        }
+
+        let (precedence, operation) = match binary_operation(&token.token_type) {
+            Some((precedence, operation)) if precedence >= min_precedence => (precedence, operation),
+            _ => break,
+        };
+        *pos += 1;
+
+        // Only operators that bind tighter can be in the RHS, so the same operators are grouped to the left
+        let start = *pos;
+        let rhs = match parse_operators(tokens, pos, precedence + 1) {
+            Ok(rhs) => rhs,
+            Err(err) if *pos > start => return Err(err),
+            Err(_) => return Err(error!(ErrorKind::ParserError, "Expected RHS expression",
+                                        ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone())))),
+        };
+        lhs = match operation {
+            BinaryOperation::Math(operation) => Box::new(MathExpr::new(token, lhs, operation, rhs)),
+            BinaryOperation::Compare(operation) => Box::new(CompareExpr::new(token, lhs, operation, rhs)),
+            BinaryOperation::Logical(operation) => Box::new(LogicalExpr::new(token, lhs, operation, rhs)),
+        };
+    }
+    Ok(lhs)
+}
+
+/// Parses prefix and postfix operators and parentheses, like `-x`, `!x`, `s[i]` or `(a + b)`
+fn parse_unary(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+    let value = parse_prefix(tokens, pos)?;
+    parse_postfix(tokens, pos, value)
+}
+
+fn parse_prefix(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+    let token = tokens[*pos].clone();
+    match token.token_type {
+        TokenType::Operator(ref op) if op == "!" => {
+            *pos += 1;
+            let value = parse_operand(tokens, pos, "!")?;
+            Ok(Box::new(NotExpr::new(token, value)))
+        }
+        // Negative number literals are parsed as literals
+        TokenType::Operator(ref op) if op == "-" && !matches!(tokens[*pos + 1].token_type, TokenType::Number(_)) => {
+            *pos += 1;
+            let value = parse_operand(tokens, pos, "-")?;
+            Ok(Box::new(NegateExpr::new(token, value)))
+        }
+        // Comma makes it a tuple, like `(a, b)` or `(a,)`
+        TokenType::Paren('(') => {
+            *pos += 1;
+            let mut elements = vec![];
+            let mut tuple = false;
+            loop {
+                let start = *pos;
+                match ValueExpr::parse(tokens, pos) {
+                    Ok(value) => elements.push(value),
+                    Err(err) if *pos > start => return Err(err),
+                    Err(_) => return Err(error!(ErrorKind::ParserError, "Error while parsing parentheses",
+                                                ErrorTokenComponent::new("Expected value".to_string(), Some(tokens[*pos].clone())))),
+                }
+                if tokens[*pos].token_type != TokenType::Separator(',') {
+                    break;
+                }
+                tuple = true;
+                *pos += 1;
+                if tokens[*pos].token_type == TokenType::Paren(')') {
+                    break;
+                }
+            }
+            if tokens[*pos].token_type != TokenType::Paren(')') {
+                return Err(error!(ErrorKind::ParserError, "Error while parsing parentheses",
+                                  ErrorTokenComponent::new("Expected ')'".to_string(), Some(tokens[*pos].clone()))));
+            }
+            *pos += 1;
+            if tuple {
+                Ok(Box::new(TupleLiteralExpr::new(token, elements)))
+            } else {
+                Ok(elements.pop().unwrap())
+            }
+        }
+        _ => PrimaryValueExpr::parse(tokens, pos),
+    }
+}
+
+/// Parses indexing, slicing and tuple fields after a value, like `a[i]`, `a[1..n]` or `t.0`, it binds tighter than prefix operators
+fn parse_postfix(tokens: &Vec<Token>, pos: &mut usize, mut value: Box<dyn ASTExpr>) -> Result<Box<dyn ASTExpr>, Error> {
+    loop {
+        let token = tokens[*pos].clone();
+        if token.token_type == TokenType::Separator('.') {
+            value = match tokens[*pos + 1].token_type {
+                TokenType::Identifier(_) => DynamicGetExpr::parse_member(tokens, pos, value),
+                _ => parse_tuple_fields(tokens, pos, value)?,
+            };
+            continue;
+        }
+        if token.token_type != TokenType::Bracket('[') {
+            break;
+        }
+        *pos += 1;
+        let index = parse_index(tokens, pos)?;
+
+        // Range makes it a slice
+        let inclusive = match tokens[*pos].token_type {
+            TokenType::Operator(ref op) if op == ".." => Some(false),
+            TokenType::Operator(ref op) if op == "..=" => Some(true),
+            _ => None,
+        };
+        let end = match inclusive {
+            Some(_) => {
+                *pos += 1;
+                Some(parse_index(tokens, pos)?)
+            }
+            None => None,
+        };
+
+        if tokens[*pos].token_type != TokenType::Bracket(']') {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing index",
+                              ErrorTokenComponent::new("Expected ']'".to_string(), Some(tokens[*pos].clone()))));
+        }
+        *pos += 1;
+        value = match end {
+            Some(end) => Box::new(SliceExpr::new(token, value, index, end, inclusive.unwrap())),
+            None => Box::new(IndexExpr::new(token, value, index)),
+        };
+    }
+    Ok(value)
+}
+
+/// `t.0.1` is lexed as a number, so every part of it is a field
+fn parse_tuple_fields(tokens: &Vec<Token>, pos: &mut usize, mut value: Box<dyn ASTExpr>) -> Result<Box<dyn ASTExpr>, Error> {
+    let token = tokens[*pos].clone();
+    *pos += 1;
+    let fields = match tokens[*pos].token_type {
+        TokenType::Number(ref number) => number.split('.').map(|field| field.parse::<u32>().ok()).collect::<Option<Vec<u32>>>(),
+        _ => None,
+    };
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return Err(error!(ErrorKind::ParserError, "Error while parsing tuple field",
+                                  ErrorTokenComponent::new("Expected field number".to_string(), Some(tokens[*pos].clone())))),
+    };
+    *pos += 1;
+    for field in fields {
+        value = Box::new(TupleFieldExpr::new(token.clone(), value, field));
+    }
+    Ok(value)
+}
+
+/// Range is not parsed, so slices can keep its ends
+fn parse_index(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+    let start = *pos;
+    parse_operators(tokens, pos, precedence::LOGICAL_OR).map_err(|err| {
+        if *pos > start {
+            return err;
+        }
+        error!(ErrorKind::ParserError, "Error while parsing index",
+               ErrorTokenComponent::new("Expected index".to_string(), Some(tokens[*pos].clone())))
+    })
+}
+
+fn parse_operand(tokens: &Vec<Token>, pos: &mut usize, op: &str) -> Result<Box<dyn ASTExpr>, Error> {
+    let start = *pos;
+    parse_operators(tokens, pos, precedence::UNARY).map_err(|err| {
+        if *pos > start {
+            return err;
+        }
+        error!(ErrorKind::ParserError, "Error while parsing unary operator",
+               ErrorTokenComponent::new(format!("Expected value after '{}'", op), Some(tokens[*pos].clone())))
+    })
+}
+
+fn binary_operation(token_type: &TokenType) -> Option<(u8, BinaryOperation)> {
+    let op = match token_type {
+        TokenType::Operator(ref op) => op.as_str(),
+        _ => return None,
+    };
+    let operation = match op {
+        "||" => (precedence::LOGICAL_OR, BinaryOperation::Logical(LogicalOperation::OR)),
+        "&&" => (precedence::LOGICAL_AND, BinaryOperation::Logical(LogicalOperation::AND)),
+        "|" => (precedence::BIT_OR, BinaryOperation::Math(MathOperation::BIT_OR)),
+        "^" => (precedence::BIT_XOR, BinaryOperation::Math(MathOperation::BIT_XOR)),
+        "&" => (precedence::BIT_AND, BinaryOperation::Math(MathOperation::BIT_AND)),
+        "<<" | ">>" => (precedence::SHIFT, BinaryOperation::Math(MathOperation::parse(op)?)),
+        "+" | "-" => (precedence::ADDITIVE, BinaryOperation::Math(MathOperation::parse(op)?)),
+        "*" | "/" | "%" => (precedence::MULTIPLICATIVE, BinaryOperation::Math(MathOperation::parse(op)?)),
+        _ => match CompareOperation::parse(op)? {
+            operation @ (CompareOperation::EQUAL | CompareOperation::NOT_EQUAL) => (precedence::EQUALITY, BinaryOperation::Compare(operation)),
+            operation => (precedence::COMPARISON, BinaryOperation::Compare(operation)),
+        }
+    };
+    Some(operation)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::lexer::tokenize, parser::expressions::Parseable};
+
+    fn parse_value(input: &str) -> String {
+        let tokens = tokenize(input);
+        let mut pos = 0;
+        super::ValueExpr::parse(&tokens, &mut pos).unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_left_associative() {
+        assert_eq!(parse_value("a - b - c;"), "SUBTRACT SUBTRACT VarCall a, VarCall b, VarCall c");
+        assert_eq!(parse_value("a / b * c;"), "MULTIPLY DIVIDE VarCall a, VarCall b, VarCall c");
+    }
+
+    #[test]
+    fn parse_precedence() {
+        assert_eq!(parse_value("a * b + c;"), "ADD MULTIPLY VarCall a, VarCall b, VarCall c");
+        assert_eq!(parse_value("a + b % c;"), "ADD VarCall a, MODULO VarCall b, VarCall c");
+        assert_eq!(parse_value("a | b ^ c & d << 1;"), "BIT_OR VarCall a, BIT_XOR VarCall b, BIT_AND VarCall c, SHIFT_LEFT VarCall d, I32(1)");
+        assert_eq!(parse_value("a + 1 < b == c || d && e;"), "OR EQUAL LESS ADD VarCall a, I32(1), VarCall b, VarCall c, AND VarCall d, VarCall e");
+    }
+
+    #[test]
+    fn parse_parentheses() {
+        assert_eq!(parse_value("(a + b) * c;"), "MULTIPLY ADD VarCall a, VarCall b, VarCall c");
+        assert_eq!(parse_value("a - (b - c);"), "SUBTRACT VarCall a, SUBTRACT VarCall b, VarCall c");
+    }
+
+    #[test]
+    fn parse_unary_operators() {
+        assert_eq!(parse_value("-a * -2;"), "MULTIPLY NEGATE VarCall a, I32(-2)");
+        assert_eq!(parse_value("!a && !(b || c);"), "AND NOT VarCall a, NOT OR VarCall b, VarCall c");
+        assert_eq!(parse_value("a - -b;"), "SUBTRACT VarCall a, NEGATE VarCall b");
+    }
+
+    #[test]
+    fn parse_casts() {
+        assert_eq!(parse_value("-a as i64 + b;"), "ADD Cast NEGATE VarCall a as I64, VarCall b");
+        assert_eq!(parse_value("a * b as f64;"), "MULTIPLY VarCall a, Cast VarCall b as F64");
     }
 }
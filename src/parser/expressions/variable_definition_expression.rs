@@ -1,29 +1,40 @@
-use std::{rc::Rc};
+use std::{cell::RefCell, rc::Rc};
 use colored::*;
-use inkwell::{builder, values::{AnyValueEnum}};
+use inkwell::{builder, context::Context, module::{Linkage, Module}, types::BasicTypeEnum, values::{AnyValueEnum, PointerValue}};
 
-use crate::{lexer::lexer::TokenType, parser::expressions::value_expression::ValueExpr, utils::{error::Error, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+use crate::{compiler::{checker::{TypeChecker, BindingKind, compatible}, constant::Constant}, lexer::lexer::{Token, TokenType}, parser::expressions::value_expression::ValueExpr, utils::{error::{Error, ErrorKind}, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
 
-use super::{ASTExpr, Parseable, VoidExpr, data_types::{DataType, ToBasic}, scope::ScopeManager};
+use super::{ASTExpr, Parseable, VoidExpr, data_types::{DataType, ToAny, ToBasic}, scope::{ScopeManager, Variable, Value}};
 
 pub struct VarDefExpr {
+    token: Token,
     name: String,
+    // Unknown if the type is inferred from the value
     data_type: DataType,
+    // Type that the checker inferred from the value
+    inferred_type: RefCell<DataType>,
     is_defined: bool,
     value: Box<dyn ASTExpr>,
     is_mutable: bool,
+    // Value of a constant, the checker evaluates it at compile time
+    constant: RefCell<Option<Constant>>,
 }
 
+/// Constant defined outside of functions, like `const name = 5;`
+pub struct GlobalConstExpr {}
+
 impl Parseable for VarDefExpr {
     fn parse(tokens: &Vec<crate::lexer::lexer::Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
-        // Should start with keyword "var"
-        if tokens[*pos].token_type != TokenType::Identifier("var".to_string()) {
+        // Should start with keyword "var" or "const"
+        let is_mutable = tokens[*pos].token_type == TokenType::Identifier("var".to_string());
+        if !is_mutable && tokens[*pos].token_type != TokenType::Identifier("const".to_string()) {
             return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable declaration",
-                              ErrorTokenComponent::new("Expected 'var' keyword".to_string(), Some(tokens[*pos].clone()))));
+                              ErrorTokenComponent::new("Expected 'var' or 'const' keyword".to_string(), Some(tokens[*pos].clone()))));
         }
 
         // Should be followed by a name
         *pos += 1;
+        let token = tokens[*pos].clone();
         let mut name = "".to_string();
         match tokens[*pos].token_type {
             TokenType::Identifier(ref s) => {
@@ -33,107 +44,225 @@ impl Parseable for VarDefExpr {
                         ErrorTokenComponent::new("Expected variable name".to_string(), Some(tokens[*pos].clone()))).panic()
         }
 
-        // TODO: Make type optional if can resolve type from value
-        // Should be followed by a colon
-        *pos += 1;
-        if tokens[*pos].token_type != TokenType::Separator(':') {
-            error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable type",
-                   ErrorTokenComponent::new("Expected ':'".to_string(), Some(tokens[*pos].clone()))).panic()
-        }
-
-        // Should be followed by a type
+        // Could be followed by a colon and a type, otherwise the type is inferred from the value
         *pos += 1;
-        let mut var_type = Ok(DataType::Unknown);
-        let type_error = error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable type",
-                                ErrorTokenComponent::new("Expected type".to_string(), Some(tokens[*pos].clone())));
-        match tokens[*pos].token_type {
-            TokenType::Identifier(ref s) => {
-                var_type = DataType::parse(&tokens[*pos]);
-                if !var_type.is_ok() {
-                    type_error.panic()
+        let mut var_type = DataType::Unknown;
+        if tokens[*pos].token_type == TokenType::Separator(':') {
+            *pos += 1;
+            let type_error = error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable type",
+                                    ErrorTokenComponent::new("Expected type".to_string(), Some(tokens[*pos].clone())));
+            match tokens[*pos].token_type {
+                TokenType::Identifier(_) | TokenType::Paren('(') => {
+                    var_type = match DataType::parse_type(tokens, pos) {
+                        Ok(data_type) => data_type,
+                        Err(_) => type_error.panic()
+                    };
                 }
+                _ => type_error.panic()
             }
-            _ => type_error.panic()
-        }
-        if !var_type.is_ok() {
-            type_error.panic()
+        } else if tokens[*pos].token_type != TokenType::Operator("=".to_string()) {
+            error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable type",
+                   ErrorTokenComponent::new("Expected ':' or '='".to_string(), Some(tokens[*pos].clone()))).panic()
         }
 
-        // Can be followed by an equals sign
-        *pos += 1;
+        // Can be followed by an equals sign, constants always have a value
         if tokens[*pos].token_type == TokenType::Operator("=".to_string()) {
             *pos += 1;
+        } else if !is_mutable {
+            error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing constant definition",
+                   ErrorTokenComponent::new("Expected '='".to_string(), Some(tokens[*pos].clone()))).panic()
         } else {
             return Ok(Box::new(VarDefExpr {
+                token,
                 name,
-                data_type: var_type.unwrap_or_else(|_| panic!("TODO")),
+                data_type: var_type,
+                inferred_type: RefCell::new(DataType::Unknown),
                 is_defined: false,
                 value: Box::new(VoidExpr {}),
-                is_mutable: true,
+                is_mutable,
+                constant: RefCell::new(None),
             }));
         }
         
         // Should be followed by a value
-        let value = ValueExpr::parse(tokens, pos);
-        match value {
-            Ok(v) => {
-                Ok(Box::new(VarDefExpr {
-                    name,
-                    data_type: var_type.unwrap_or_else(|_| panic!("TODO")),
-                    is_defined: true,
-                    value: v,
-                    is_mutable: true,
-                }))
-            }
-            Err(_e) => {
-                error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable definition",
-                             ErrorTokenComponent::new("Expected variable value".to_string(), Some(tokens[*pos].clone()))).panic_val();
-                panic!("Error while parsing variable definition")
-            }
+        let value = ValueExpr::parse(tokens, pos).map_err(|_| {
+            error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable definition",
+                   ErrorTokenComponent::new("Expected variable value".to_string(), Some(tokens[*pos].clone())))
+        })?;
+        Ok(Box::new(VarDefExpr {
+            token,
+            name,
+            data_type: var_type,
+            inferred_type: RefCell::new(DataType::Unknown),
+            is_defined: true,
+            value,
+            is_mutable,
+            constant: RefCell::new(None),
+        }))
+    }
+}
+
+impl Parseable for GlobalConstExpr {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn ASTExpr>, Error> {
+        // Only constants can be defined outside of functions
+        if tokens[*pos].token_type != TokenType::Identifier("const".to_string()) {
+            return Err(error!(ErrorKind::ParserError, "Error while parsing constant definition",
+                              ErrorTokenComponent::new("Expected 'const' keyword".to_string(), Some(tokens[*pos].clone()))));
         }
+        let constant = VarDefExpr::parse(tokens, pos)?;
+
+        // Should be followed by a semicolon
+        if tokens[*pos].token_type != TokenType::Separator(';') {
+            error!(ErrorKind::ParserError, "Error while parsing constant definition",
+                   ErrorTokenComponent::new("Expected ';'".to_string(), Some(tokens[*pos].clone()))).panic()
+        }
+        *pos += 1;
+        Ok(constant)
+    }
+}
+
+impl VarDefExpr {
+    /// Constants are not generated, their value was already evaluated by the checker
+    fn generate_constant<'a>(&self, context: &'a Context, module: &Module<'a>, scope_manager: &mut ScopeManager<'a>) -> Option<AnyValueEnum<'a>> {
+        let value = match *self.constant.borrow() {
+            Some(ref constant) => constant.into_llvm(context, module),
+            None => error!(ErrorKind::CompilerError, "Constant value is not known at compile time",
+                           ErrorTokenComponent::new(format!("Value of '{}' can not be evaluated", &self.name.green()), Some(self.token.clone()))).panic()
+        };
+        // Constants outside of functions are also emitted as globals
+        if scope_manager.scope.function.is_none() {
+            let global = module.add_global(value.get_type(), None, &self.name);
+            global.set_initializer(&value);
+            global.set_constant(true);
+            global.set_linkage(Linkage::Internal);
+        }
+        let data_type = self.constant.borrow().as_ref().unwrap().data_type();
+        scope_manager.scope.fn_args.remove(&self.name);
+        scope_manager.scope.variables.remove(&self.name);
+        scope_manager.scope.constants.insert(self.name.to_string(), Rc::new(Value { value, data_type }));
+        Some(value.to_any())
     }
 }
 
 impl ASTExpr for VarDefExpr {
     fn to_string(&self) -> String {
+        let keyword = if self.is_mutable { "Var" } else { "Const" };
+        let name = match self.data_type {
+            DataType::Unknown => self.name.clone(),
+            ref data_type => format!("{:?} {}", data_type, self.name),
+        };
         if self.is_defined {
-            format!("Var {:?} {} = {}", self.data_type, self.name, self.value.to_string())
+            format!("{} {} = {}", keyword, name, self.value.to_string())
         } else {
-            format!("Var {:?} {}", self.data_type, self.name)
+            format!("{} {}", keyword, name)
         }
     }
 
-    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &builder::Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        checker.check_type(&self.data_type, &self.token);
+        let mut data_type = self.data_type.clone();
+        if self.is_defined {
+            let found = self.value.check(checker);
+            if data_type == DataType::Unknown {
+                // Type is inferred from the value
+                data_type = match found {
+                    DataType::Void | DataType::Never => {
+                        checker.error(error!(ErrorKind::TypeError, "Can not infer type of variable",
+                                             ErrorTokenComponent::new(format!("Value of '{}' does not have a type", &self.name.green()), Some(self.token.clone()))));
+                        DataType::Unknown
+                    }
+                    found => found,
+                };
+            } else if !compatible(&data_type, &found) {
+                checker.error(error!(ErrorKind::TypeError, "Variable types do not match",
+                                     ErrorTokenComponent::new(format!("Variable '{}' is defined with a value of different type", &self.name.green()), Some(self.token.clone()))
+                                     NameErrorComponent::new(format!("Expected: {}", data_type))
+                                     NameErrorComponent::new(format!("Found: {}", found))));
+            }
+        }
+        *self.inferred_type.borrow_mut() = data_type.clone();
+
+        // Value is checked first, so it can not use the variable itself
         if self.is_mutable {
-            // Create alloca
-            let alloca = builder.build_alloca(self.data_type.into_basic_type(context), &self.name);
-            // Store value if defined
-            if self.is_defined {
-                let value = self.value.generate(context, module, builder, scope_manager);
-                if value.is_none() {
-                    error!(crate::utils::error::ErrorKind::CompilerError, "Expected value for variable definition", 
-                           NameErrorComponent::new(format!("Expected value for variable '{}', but got nothing", &self.name.green()))).panic();
+            checker.define(&self.name, data_type, BindingKind::Variable);
+            return DataType::Void;
+        }
+        match self.value.evaluate(checker) {
+            Some(value) => {
+                *self.constant.borrow_mut() = Some(value.clone());
+                checker.define_constant(&self.name, value);
+            }
+            None => {
+                if data_type != DataType::Unknown {
+                    checker.error(error!(ErrorKind::TypeError, "Constant value is not known at compile time",
+                                         ErrorTokenComponent::new(format!("Value of '{}' depends on the runtime or can not be evaluated", &self.name.green()), Some(self.token.clone()))));
                 }
-                builder.build_store(alloca, value.unwrap().to_basic());
+                checker.define(&self.name, data_type, BindingKind::Constant);
             }
-            // Add alloca to variables
-            scope_manager.scope.variables.insert(self.name.to_string(), Rc::new(alloca));
-            // Return alloca 
-            Some(AnyValueEnum::PointerValue(alloca))
-        } else {
-            // Return value if defined
-            if self.is_defined {
-                self.value.generate(context, module, builder, scope_manager)
-            } else {
-                panic!("Variable '{}' is immutable and is not defined", self.name)
+        }
+        DataType::Void
+    }
+
+    fn generate<'a, 'b>(&self, context: &'a inkwell::context::Context, module: &inkwell::module::Module<'a>, builder: &builder::Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<inkwell::values::AnyValueEnum<'a>> {
+        if !self.is_mutable {
+            return self.generate_constant(context, module, scope_manager);
+        }
+
+        // Value is generated first, so the type can be taken from it if it was not inferred
+        let value = if self.is_defined {
+            match self.value.generate(context, module, builder, scope_manager) {
+                Some(value) => Some(value),
+                None => error!(crate::utils::error::ErrorKind::CompilerError, "Expected value for variable definition",
+                               ErrorTokenComponent::new(format!("Expected value for variable '{}', but got nothing", &self.name.green()), Some(self.token.clone()))).panic()
             }
+        } else {
+            None
+        };
+        let data_type = match (&self.data_type, self.inferred_type.borrow().clone()) {
+            (DataType::Unknown, DataType::Unknown) => self.value.data_type(scope_manager),
+            (DataType::Unknown, inferred) => inferred,
+            (data_type, _) => data_type.clone(),
+        };
+        if data_type == DataType::Unknown {
+            error!(crate::utils::error::ErrorKind::CompilerError, "Can not infer type of variable",
+                   ErrorTokenComponent::new(format!("Type of '{}' is not known", &self.name.green()), Some(self.token.clone()))).panic()
         }
+
+        // Create alloca and store value if defined
+        let alloca = build_entry_alloca(context, builder, data_type.into_basic_type(context), &self.name);
+        if let Some(value) = value {
+            builder.build_store(alloca, value.to_basic());
+        }
+        // Add alloca to variables, it shadows arguments and constants with the same name
+        scope_manager.scope.fn_args.remove(&self.name);
+        scope_manager.scope.constants.remove(&self.name);
+        scope_manager.scope.variables.insert(self.name.to_string(), Rc::new(Variable { pointer: alloca, data_type }));
+        // Return alloca 
+        Some(AnyValueEnum::PointerValue(alloca))
     }
 }
 
+/// Allocates the variable in the entry block of the function, so allocas inside loops do not grow the stack
+pub fn build_entry_alloca<'a>(context: &'a Context, builder: &builder::Builder<'a>, data_type: BasicTypeEnum<'a>, name: &str) -> PointerValue<'a> {
+    let entry = builder.get_insert_block()
+        .and_then(|block| block.get_parent())
+        .and_then(|function| function.get_first_basic_block());
+    let entry = match entry {
+        Some(entry) => entry,
+        None => return builder.build_alloca(data_type, name),
+    };
+    let entry_builder = context.create_builder();
+    match entry.get_first_instruction() {
+        Some(instruction) => entry_builder.position_before(&instruction),
+        None => entry_builder.position_at_end(entry),
+    }
+    entry_builder.build_alloca(data_type, name)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{lexer::lexer::TokenType, test_token, parser::expressions::Parseable};
+    use crate::compiler::{checker::check_source, compiler::run_source};
 
 
     #[test]
@@ -152,4 +281,81 @@ mod tests {
         assert_eq!(expr.to_string(), "Var I32 x");
     }
 
+    #[test]
+    fn parse_variable_without_value_after_equals() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("var".to_string())),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("=".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = super::VarDefExpr::parse(&tokens, &mut pos);
+        assert_eq!(expr.err().unwrap().message(), "Error while parsing variable definition");
+    }
+
+    #[test]
+    fn parse_variable_without_type() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("var".to_string())),
+            test_token!(TokenType::Identifier("x".to_string())),
+            test_token!(TokenType::Operator("=".to_string())),
+            test_token!(TokenType::Number("5u8".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = super::VarDefExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Var x = U8(5)");
+        assert_eq!(pos, 4);
+    }
+
+    #[test]
+    fn parse_global_constant() {
+        let tokens = vec![
+            test_token!(TokenType::Identifier("const".to_string())),
+            test_token!(TokenType::Identifier("size".to_string())),
+            test_token!(TokenType::Separator(':')),
+            test_token!(TokenType::Identifier("i64".to_string())),
+            test_token!(TokenType::Operator("=".to_string())),
+            test_token!(TokenType::Number("4i64".to_string())),
+            test_token!(TokenType::Operator("*".to_string())),
+            test_token!(TokenType::Number("8i64".to_string())),
+            test_token!(TokenType::Separator(';')),
+        ];
+        let mut pos = 0;
+        let expr = super::GlobalConstExpr::parse(&tokens, &mut pos).unwrap();
+        assert_eq!(expr.to_string(), "Const I64 size = MULTIPLY I64(4), I64(8)");
+        assert_eq!(pos, 9);
+    }
+
+    #[test]
+    fn check_inferred_types() {
+        assert!(check_source("fun half(x: u8): u8 { x / 2u8 } fun f(): u8 { var x = 200u8; var y = x + half(x); var z = 1.5; y }").is_empty());
+        assert_eq!(check_source("fun g(): void {} fun f(): void { var w = g(); }"), vec!["TypeError: Can not infer type of variable"]);
+        assert_eq!(check_source("fun f(): void { var z = 1.5; z + 1; }"), vec!["TypeError: Binary operation types do not match"]);
+    }
+
+    #[test]
+    fn check_constants() {
+        assert!(check_source("const a = 6; const b = a * 7 > 40 || 1 / 0 > 0; fun f(x: i32): i32 { x + a }").is_empty());
+        assert_eq!(check_source("const a = 6; fun f(x: i32): void { const c = x + a; }"), vec!["TypeError: Constant value is not known at compile time"]);
+        assert_eq!(check_source("fun f(): void { const d: i32 = 1 / 0; }"), vec!["TypeError: Constant value is not known at compile time"]);
+        assert_eq!(check_source("const a = 6; fun f(): void { a = 2; }"), vec!["TypeError: Can not assign to constant"]);
+        assert_eq!(check_source("const b = true; fun f(): void { b = 1; }"), vec![
+            "TypeError: Can not assign to constant",
+            "TypeError: Assignment types do not match",
+        ]);
+    }
+
+    #[test]
+    fn run_constants() {
+        assert_eq!(run_source("const size = 4 * 8; const half: i32 = size / 2; fun main(): i32 { const one = (size == 32) as i32; half + one }"), 17);
+        assert_eq!(run_source("const big = 300 as u8; fun main(): i32 { big as i32 + 1 }"), 45);
+    }
+
+    #[test]
+    fn run_inferred_types() {
+        assert_eq!(run_source("fun main(): i32 { var t1 = 0; var t2 = 1; var next = t1 + t2; for (i in 0..5) { t1 = t2; t2 = next; next = t1 + t2; } t2 }"), 8);
+        assert_eq!(run_source("fun main(): i32 { var x = 250u8; x += 10u8; var half = x as f64 / 2.0; half as i32 }"), 2);
+    }
 }
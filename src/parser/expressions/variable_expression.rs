@@ -1,24 +1,26 @@
-use inkwell::values::{AnyValueEnum, AnyValue};
+use inkwell::{builder::Builder, context::Context, module::Module, values::{AnyValueEnum, AnyValue, PointerValue}};
 use colored::*;
-use crate::{lexer::lexer::TokenType, utils::{error::Error, error_components::{token_component::ErrorTokenComponent, name_component::NameErrorComponent}}, error};
+use crate::{compiler::{checker::TypeChecker, constant::Constant}, lexer::lexer::{Token, TokenType}, utils::{error::{Error, ErrorKind}, error_components::token_component::ErrorTokenComponent}, error};
 
-use super::{Parseable, ASTExpr, data_types::ToAny, ScopeManager};
+use super::{Parseable, ASTExpr, data_types::{DataType, ToAny}, ScopeManager};
 
 pub struct VariableCallExpr {
+    token: Token,
     name: String,
 }
 
 impl Parseable for VariableCallExpr {
-    fn parse(tokens: &Vec<crate::lexer::lexer::Token>, pos: &mut usize) -> Result<Box<dyn super::ASTExpr>, Error> {
-        let name = &tokens[*pos].token_type;
-        match name {
+    fn parse(tokens: &Vec<Token>, pos: &mut usize) -> Result<Box<dyn super::ASTExpr>, Error> {
+        let token = tokens[*pos].clone();
+        match token.token_type {
             TokenType::Identifier(ref s) => {
                 *pos += 1;
                 return Ok(Box::new(VariableCallExpr {
+                    token: token.clone(),
                     name: s.clone(),
                 }));
             },
-            _ => return Err(error!(crate::utils::error::ErrorKind::ParserError, "Error while parsing variable call",
+            _ => return Err(error!(ErrorKind::ParserError, "Error while parsing variable call",
                                    ErrorTokenComponent::new("Expected variable name".to_string(), Some(tokens[*pos].clone()))))
         }
     }
@@ -29,22 +31,65 @@ impl ASTExpr for VariableCallExpr {
         // Get function argument
         let fn_var = scope_manager.scope.fn_args.get(&self.name);
         if fn_var.is_some() {
-            return Some(fn_var.unwrap().as_any_value_enum())
+            return Some(fn_var.unwrap().value.as_any_value_enum())
+        }
+        // Get constant
+        if let Some(constant) = scope_manager.scope.constants.get(&self.name) {
+            return Some(constant.value.as_any_value_enum())
         }
         // Get normal variable
         let var = scope_manager.scope.variables.get(&self.name);
         if var.is_none() {
-            error!(crate::utils::error::ErrorKind::CompilerError, "Variable does not exist",
-                   NameErrorComponent::new(format!("Variable '{}' does not exist", &self.name.green()))).panic();
+            error!(ErrorKind::CompilerError, "Variable does not exist",
+                   ErrorTokenComponent::new(format!("Variable '{}' does not exist", &self.name.green()), Some(self.token.clone()))).panic();
             return None;
         }
         let var = var.unwrap();
-        let var = *var.clone();
-        let load = builder.build_load(var, self.name.as_str());
+        let load = builder.build_load(var.pointer, self.name.as_str());
         let nload = load.as_any_value_enum();
         Some(nload)
     }
  
+    fn data_type<'a>(&self, scope_manager: &ScopeManager<'a>) -> DataType {
+        let scope = &scope_manager.scope;
+        if let Some(value) = scope.fn_args.get(&self.name).or_else(|| scope.constants.get(&self.name)) {
+            return value.data_type.clone();
+        }
+        match scope.variables.get(&self.name) {
+            Some(variable) => variable.data_type.clone(),
+            None => DataType::Unknown,
+        }
+    }
+
+    fn check(&self, checker: &mut TypeChecker) -> DataType {
+        match checker.lookup(&self.name) {
+            Some(binding) => binding.data_type,
+            None => {
+                checker.error(error!(ErrorKind::TypeError, "Variable does not exist",
+                                     ErrorTokenComponent::new(format!("Variable '{}' does not exist", &self.name.green()), Some(self.token.clone()))));
+                DataType::Unknown
+            }
+        }
+    }
+
+    /// Variables are stored in memory, `this` already is a pointer
+    fn pointer<'a, 'b>(&self, _context: &'a Context, _module: &Module<'a>, _builder: &Builder<'a>, scope_manager: &'b mut ScopeManager<'a>) -> Option<PointerValue<'a>> {
+        if let Some(argument) = scope_manager.scope.fn_args.get(&self.name) {
+            return match argument.data_type {
+                DataType::Reference(_) => Some(argument.value.into_pointer_value()),
+                _ => None,
+            };
+        }
+        if scope_manager.scope.constants.contains_key(&self.name) {
+            return None;
+        }
+        scope_manager.scope.variables.get(&self.name).map(|variable| variable.pointer)
+    }
+
+    fn evaluate(&self, checker: &TypeChecker) -> Option<Constant> {
+        checker.lookup(&self.name).and_then(|binding| binding.value)
+    }
+
     fn to_string(&self) -> String {
         format!("VarCall {}", self.name)
     }
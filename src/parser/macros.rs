@@ -2,19 +2,36 @@
 #[macro_export]
 macro_rules! try_parse {
     ( $tokens: expr, $pos: expr, $( $expr: ty ) + ) => {(|| {
+        // Expression that failed after its first token was recognized, its error is reported
+        let start_pos = $pos;
+        let mut furthest: Option<(usize, crate::utils::error::Error)> = None;
         $(
-            let temp_pos = $pos;
             let expr = <$expr as Parseable>::parse(&$tokens, &mut $pos);
             match expr {
+                // Expression that got further explains the error better, like `x = 1 +;` parsed as `x`
+                Ok(_) if furthest.as_ref().map_or(false, |(pos, _)| *pos > $pos) => {
+                    let (pos, err) = furthest.unwrap();
+                    $pos = pos;
+                    return Err(err);
+                }
                 Ok(expr) => return Ok(expr),
                 Err(err) => {
-                    $pos = temp_pos;
-                    println!("Ignoring error: {}", err.to_short_string());
+                    if $pos > furthest.as_ref().map_or(start_pos, |(pos, _)| *pos) {
+                        furthest = Some(($pos, err));
+                    }
+                    $pos = start_pos;
                 }
             }
         )+
 
-        Err(crate::error!(crate::utils::error::ErrorKind::ParserError, "Could not parse any"))
+        // Position is left at the error, so callers know that something was recognized
+        match furthest {
+            Some((pos, err)) => {
+                $pos = pos;
+                Err(err)
+            }
+            None => Err(crate::error!(crate::utils::error::ErrorKind::ParserError, "Could not parse any")),
+        }
     })()}
 }
 
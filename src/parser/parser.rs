@@ -1,9 +1,9 @@
-use crate::{lexer::lexer::Token, try_parse, utils::error::Error};
+use crate::{lexer::lexer::{Token, TokenType}, try_parse, utils::{error::{Error, ErrorKind}, error_components::token_component::ErrorTokenComponent}, error};
 
-use super::expressions::{ASTExpr, import_expression::ImportExpr, Parseable, function_expression::FunctionExpr};
+use super::expressions::{ASTExpr, import_expression::ImportExpr, Parseable, function_expression::FunctionExpr, variable_definition_expression::GlobalConstExpr, enum_expression::EnumExpr, class_expression::ClassExpr};
 
 
-pub fn parse(tokens: &Vec<Token>) -> Vec<Box<dyn ASTExpr>> {
+pub fn parse(tokens: &Vec<Token>) -> Result<Vec<Box<dyn ASTExpr>>, Error> {
     let mut parser = Parser::new(tokens);
     parser.parse()
 }
@@ -21,23 +21,23 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse(&mut self) -> Vec<Box<dyn ASTExpr>> {
+    fn parse(&mut self) -> Result<Vec<Box<dyn ASTExpr>>, Error> {
         let mut ast: Vec<Box<dyn ASTExpr>> = Vec::new();
 
-        while self.pos < self.tokens.len() {
-            // Global things to parse
-            match try_parse!(self.tokens, self.pos, ImportExpr FunctionExpr) {
-                Ok(expr) => { 
-                    ast.push(expr);
-                },
-                Err(err) => {
-                    err.print_err();
-                    break;
+        while self.pos < self.tokens.len() && self.tokens[self.pos].token_type != TokenType::EOF {
+            // Global things to parse, errors inside of them are reported as they are
+            let start = self.pos;
+            let expr = try_parse!(self.tokens, self.pos, ImportExpr FunctionExpr GlobalConstExpr EnumExpr ClassExpr).map_err(|err| {
+                if self.pos > start {
+                    return err;
                 }
-            }
+                error!(ErrorKind::ParserError, "Error while parsing global expression",
+                       ErrorTokenComponent::new("Expected import, function, constant, enum or class definition".to_string(), Some(self.tokens[self.pos].clone())))
+            })?;
+            ast.push(expr);
         }
         
-        ast
+        Ok(ast)
     }
     
     fn parse_expr<T>(&mut self) -> Result<Box<dyn ASTExpr>, Error> where T: Parseable {
@@ -45,3 +45,31 @@ impl<'a> Parser<'a> {
     }
     
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::lexer::tokenize;
+
+    fn parse_error(source: &str) -> String {
+        match super::parse(&tokenize(source)) {
+            Ok(_) => String::new(),
+            Err(err) => err.message().to_string(),
+        }
+    }
+
+    #[test]
+    fn report_errors_inside_of_definitions() {
+        assert_eq!(parse_error("fun main(): i32 { var x = 1; x = ; x }"), "Error while parsing assignment");
+        assert_eq!(parse_error("fun main(): i32 { var x = 1; x = x + ; x }"), "Expected RHS expression");
+        assert_eq!(parse_error("fun main(): i32 { (1 + 2 }"), "Error while parsing parentheses");
+        assert_eq!(parse_error("fun main(): i32 { if (1) { -a[1 } 2 }"), "Error while parsing index");
+        assert_eq!(parse_error(r#"fun main(): str { "\q" }"#), "Error while parsing escape sequence");
+        assert_eq!(parse_error("enum Shape { Circle(f64 }"), "Error while parsing enum");
+        assert_eq!(parse_error("class Point { var x i32; }"), "Error while parsing class");
+        assert_eq!(parse_error("class Point { fun get(this: &this): i32 { 1 * } }"), "Expected RHS expression");
+        assert_eq!(parse_error("fun f(x: i32): i32 { for (i in 0..) {} 0 }"), "Error while parsing range");
+        assert_eq!(parse_error("fun f(x: i32): i32 { match x { 1 => 2 *, _ => 0 } }"), "Expected RHS expression");
+        assert_eq!(parse_error("x = 1;"), "Error while parsing global expression");
+        assert_eq!(parse_error("fun main(): i32 { var x = 1; x += 2; x }"), "");
+    }
+}
@@ -1,16 +1,22 @@
 use std::fmt::Debug;
 use colored::*;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ErrorKind {
     LexerError,
     ParserError,
-    CompilerError
+    CompilerError,
+    TypeError,
+    DriverError
 }
 
 pub trait ErrorComponent {
     fn to_err_string(&self) -> String;
 }
 
+/// Panic payload used to unwind out of the compiler after an error was already printed
+pub struct CompilationFailed;
+
 pub struct Error {
     kind: ErrorKind,
     message: String,
@@ -41,7 +47,9 @@ impl Error {
         let mut error_message: String = match self.kind {
             ErrorKind::LexerError => format!("{}: {}", "Lexer error".red().bold(), self.message),
             ErrorKind::ParserError => format!("{}: {}", "Parser error".red().bold(), self.message),
-            ErrorKind::CompilerError => format!("{}: {}", "Compiler error".red().bold(), self.message)
+            ErrorKind::CompilerError => format!("{}: {}", "Compiler error".red().bold(), self.message),
+            ErrorKind::TypeError => format!("{}: {}", "Type error".red().bold(), self.message),
+            ErrorKind::DriverError => format!("{}: {}", "Error".red().bold(), self.message)
         };
         error_message.push('\n');
 
@@ -50,27 +58,35 @@ impl Error {
             error_message.push('\n');
         }
 
-        println!("{}", error_message);
-        error_message.push('\n');
+        eprintln!("{}", error_message);
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
     }
 
     pub fn to_short_string(&self) -> String {
         format!("{}", self.message.red())
     }
 
-    pub fn panic(&self) {
+    pub fn panic(&self) -> ! {
         self.print_err();
-        panic!("{}", "Compilation failed due to previous error".red().bold());
+        eprintln!("{}", "Compilation failed due to previous error".red().bold());
+        // Unwind without invoking the panic hook, the error was already reported
+        std::panic::resume_unwind(Box::new(CompilationFailed));
     }
 
     pub fn panic_val(&self) -> &Self {
-        self.panic();
-        return self;
+        self.panic()
     }
 
 }
 
-#[macro_export()]
+#[macro_export]
 macro_rules! error {
     ($kind: expr, $message: literal, $( $component: expr ) *) => {
         {
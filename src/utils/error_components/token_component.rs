@@ -13,8 +13,10 @@ impl ErrorComponent for ErrorTokenComponent {
                 crate::lexer::lexer::TokenType::EOF => "EOF".green().to_string(),
                 crate::lexer::lexer::TokenType::Paren(ref s) => format!("'{}'", s.to_string().green()),
                 crate::lexer::lexer::TokenType::Brace(ref s) => format!("'{}'", s.to_string().green()),
+                crate::lexer::lexer::TokenType::Bracket(ref s) => format!("'{}'", s.to_string().green()),
                 crate::lexer::lexer::TokenType::Number(ref s) => format!("{}", s.green()),
                 crate::lexer::lexer::TokenType::String(ref s) => format!("{}", s.green()),
+                crate::lexer::lexer::TokenType::Char(ref s) => format!("{}", s.green()),
                 crate::lexer::lexer::TokenType::Operator(ref s) => format!("'{}'", s.green()),
                 crate::lexer::lexer::TokenType::Separator(ref s) => format!("'{}'", s.to_string().green()),
                 crate::lexer::lexer::TokenType::Identifier(ref s) => s.green().to_string()